@@ -0,0 +1,76 @@
+//! Сравнение скорости чтения и записи большого CSV-файла при разных размерах буфера.
+//!
+//! Базовая линия — `Parser::new` и `Serializer::new` с буфером по умолчанию; для каждого размера
+//! выводится ускорение относительно нее. Выигрыш зависит от носителя: если файл уже в кеше
+//! страниц, время уходит на разбор CSV и разница укладывается в погрешность.
+//!
+//! Запуск: `cargo bench --bench buffer_capacity`.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::time::{Duration, Instant};
+use transactions_parser::{Parser, Serializer, YPBankCsvRecord};
+
+const RECORDS: u64 = 500_000;
+const ROUNDS: u32 = 5;
+const CAPACITIES: [usize; 3] = [64 * 1024, 256 * 1024, 1024 * 1024];
+
+fn prepare_file() -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("ypbank_bench_{}.csv", std::process::id()));
+    let mut writer = BufWriter::new(File::create(&path).unwrap());
+
+    writeln!(writer, "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION").unwrap();
+    for id in 0..RECORDS {
+        writeln!(writer, "{},TRANSFER,{},{},{},{},SUCCESS,\"Benchmark record {}\"", id, id % 1000, id % 997, id * 3, 1_700_000_000_000 + id, id).unwrap();
+    }
+    writer.flush().unwrap();
+
+    path
+}
+
+/// Лучшее время из `ROUNDS` запусков.
+fn best_of(mut run: impl FnMut()) -> Duration {
+    let mut best = Duration::MAX;
+
+    for _ in 0..ROUNDS {
+        let started = Instant::now();
+        run();
+        best = best.min(started.elapsed());
+    }
+
+    best
+}
+
+fn report(label: &str, elapsed: Duration, baseline: Duration, size_mb: f64) {
+    println!(
+        "{:<22} {:>8.2?} ({:>6.1} MB/s, x{:.2} to baseline)",
+        label,
+        elapsed,
+        size_mb / elapsed.as_secs_f64(),
+        baseline.as_secs_f64() / elapsed.as_secs_f64()
+    );
+}
+
+fn main() {
+    let path = prepare_file();
+    let size_mb = fs::metadata(&path).unwrap().len() as f64 / (1024.0 * 1024.0);
+    let records: Vec<YPBankCsvRecord> = Parser::<YPBankCsvRecord, _>::new(File::open(&path).unwrap()).into_vec().unwrap();
+
+    let read = |parser: Parser<YPBankCsvRecord, File>| assert_eq!(parser.count() as u64, RECORDS);
+    let baseline = best_of(|| read(Parser::new(File::open(&path).unwrap())));
+    report("read, Parser::new", baseline, baseline, size_mb);
+    for capacity in CAPACITIES {
+        let elapsed = best_of(|| read(Parser::with_buffer_capacity(File::open(&path).unwrap(), capacity)));
+        report(&format!("read, {} KB", capacity / 1024), elapsed, baseline, size_mb);
+    }
+
+    let write = |mut serializer: Serializer<YPBankCsvRecord, io::Sink>| serializer.serialize(records.iter().cloned()).unwrap();
+    let baseline = best_of(|| write(Serializer::new(io::sink())));
+    report("write, Serializer::new", baseline, baseline, size_mb);
+    for capacity in CAPACITIES {
+        let elapsed = best_of(|| write(Serializer::with_buffer_capacity(io::sink(), capacity)));
+        report(&format!("write, {} KB", capacity / 1024), elapsed, baseline, size_mb);
+    }
+
+    fs::remove_file(&path).unwrap();
+}
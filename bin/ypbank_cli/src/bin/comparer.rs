@@ -3,7 +3,7 @@ use std::collections::HashSet;
 use std::fs::File;
 use std::io::{Error, ErrorKind, Read, Write};
 use std::path::Path;
-use transactions_parser::{Parser, Readable, Transaction, YPBankBinRecord, YPBankCsvRecord, YPBankTextRecord};
+use transactions_parser::{diff_with, DiffOptions, Parser, Readable, Transaction, YPBankBinRecord, YPBankCsvRecord, YPBankTextRecord};
 
 #[derive(ClapParser, Debug)]
 #[command(version, about, long_about = None)]
@@ -19,6 +19,10 @@ struct Args {
 
     #[arg(long = "file2-format")]
     second_file_format: String,
+
+    /// Допустимое расхождение временных меток (мс), при котором транзакции считаются совпадающими.
+    #[arg(long = "timestamp-tolerance", default_value_t = 0)]
+    timestamp_tolerance_ms: u64,
 }
 
 fn main() -> std::io::Result<()> {
@@ -40,12 +44,12 @@ fn main() -> std::io::Result<()> {
     let second_file = File::open(args.second_file)?;
 
     match (args.first_file_format.as_str(), args.second_file_format.as_str()) {
-        ("txt", "csv") => compare::<YPBankTextRecord, YPBankCsvRecord, _, _, _>(first_file, second_file, std::io::stdout()),
-        ("txt", "bin") => compare::<YPBankTextRecord, YPBankBinRecord, _, _, _>(first_file, second_file, std::io::stdout()),
-        ("csv", "txt") => compare::<YPBankCsvRecord, YPBankTextRecord, _, _, _>(first_file, second_file, std::io::stdout()),
-        ("csv", "bin") => compare::<YPBankCsvRecord, YPBankBinRecord, _, _, _>(first_file, second_file, std::io::stdout()),
-        ("bin", "txt") => compare::<YPBankBinRecord, YPBankTextRecord, _, _, _>(first_file, second_file, std::io::stdout()),
-        ("bin", "csv") => compare::<YPBankBinRecord, YPBankCsvRecord, _, _, _>(first_file, second_file, std::io::stdout()),
+        ("txt", "csv") => compare::<YPBankTextRecord, YPBankCsvRecord, _, _, _>(first_file, second_file, std::io::stdout(), args.timestamp_tolerance_ms),
+        ("txt", "bin") => compare::<YPBankTextRecord, YPBankBinRecord, _, _, _>(first_file, second_file, std::io::stdout(), args.timestamp_tolerance_ms),
+        ("csv", "txt") => compare::<YPBankCsvRecord, YPBankTextRecord, _, _, _>(first_file, second_file, std::io::stdout(), args.timestamp_tolerance_ms),
+        ("csv", "bin") => compare::<YPBankCsvRecord, YPBankBinRecord, _, _, _>(first_file, second_file, std::io::stdout(), args.timestamp_tolerance_ms),
+        ("bin", "txt") => compare::<YPBankBinRecord, YPBankTextRecord, _, _, _>(first_file, second_file, std::io::stdout(), args.timestamp_tolerance_ms),
+        ("bin", "csv") => compare::<YPBankBinRecord, YPBankCsvRecord, _, _, _>(first_file, second_file, std::io::stdout(), args.timestamp_tolerance_ms),
 
         _ => {
             Err(Error::new(ErrorKind::InvalidInput, format!("Unsupported format combination: {} -> {}", args.first_file_format, args.second_file_format)))
@@ -53,77 +57,70 @@ fn main() -> std::io::Result<()> {
     }
 }
 
-fn compare<TFormat1, TFormat2, TSource1, TSource2, TOutput>(first_source: TSource1, second_source: TSource2, mut output: TOutput) -> Result<(), Error>
+fn compare<TFormat1, TFormat2, TSource1, TSource2, TOutput>(first_source: TSource1, second_source: TSource2, mut output: TOutput, timestamp_tolerance_ms: u64) -> Result<(), Error>
 where
-    TFormat1: Readable<TSource1>,
-    TFormat2: Readable<TSource2>,
+    TFormat1: Readable<TSource1> + Into<Transaction>,
+    TFormat2: Readable<TSource2> + Into<Transaction>,
     TSource1: Read,
     TSource2: Read,
     TOutput: Write
 {
-    let mut first_parser = Parser::<TFormat1, _>::new(first_source);
-    let mut second_parser = Parser::<TFormat2, _>::new(second_source);
-
-    let mut first_set: HashSet<Transaction> = HashSet::new();
-
-    first_parser
-        .by_ref()
-        .map(|res| res.into())
-        .try_for_each(|transaction: Transaction| -> Result<(), Error> {
-            let tx_id = transaction.id;
-            if !first_set.insert(transaction) {
-                return Err(Error::new(
-                    ErrorKind::InvalidData,
-                    format!("Duplicate transaction found in file 1 with id: {}", tx_id)
-                ));
-            }
-            Ok(())
-        })?;
-
-
-    if let Some(err) = first_parser.read_error {
-        return Err(err.into());
-    }
-
-    let mut files_is_same = true;
-    let mut uniq_transactions_in_second_file = 0;
-
-    second_parser.by_ref()
-        .map(|res| res.into())
-        .try_for_each(|transaction: Transaction| -> Result<(), Error> {
-            if !first_set.remove(&transaction) { // Если во втором файле есть задублированные транзакции, то они считаются уникальными для второго файла, так как в первом файле их нет (иначе будет ошибка)
-                files_is_same = false;
-                uniq_transactions_in_second_file += 1;
-
-                writeln!(output, "Transaction with id {} is only in file 2", transaction.id)?;
-            }
-            Ok(())
-        })?;
-
-    if let Some(err) = second_parser.read_error {
-        return Err(err.into());
-    }
+    let first = read_unique::<TFormat1, _>(first_source, "file 1")?;
+    let second = read_unique::<TFormat2, _>(second_source, "file 2")?;
 
-    files_is_same &= first_set.is_empty();
+    let options = DiffOptions { timestamp_tolerance_ms, ..DiffOptions::default() };
+    let diff = diff_with(&first, &second, options);
 
-    if files_is_same {
+    if diff.is_empty() {
         writeln!(output, "Files are identical")?;
 
         return Ok(());
     }
 
-    let uniq_transactions_in_first_file = first_set.len();
-    for transaction in first_set {
+    for transaction in &diff.only_in_left {
         writeln!(output, "Transaction with id {} is only in file 1", transaction.id)?;
     }
+    for transaction in &diff.only_in_right {
+        writeln!(output, "Transaction with id {} is only in file 2", transaction.id)?;
+    }
+    for mismatch in &diff.mismatched {
+        writeln!(output, "Transaction with id {} differs", mismatch.left.id)?;
+        for field in &mismatch.fields {
+            writeln!(output, "    {}: {:?} in file 1, {:?} in file 2", field.field, field.left, field.right)?;
+        }
+    }
 
     writeln!(output, "---------------------------------------------")?;
-    writeln!(output, "Total unique transactions in file 1: {}", uniq_transactions_in_first_file)?;
-    writeln!(output, "Total unique transactions in file 2: {}", uniq_transactions_in_second_file)?;
+    writeln!(output, "Total unique transactions in file 1: {}", diff.only_in_left.len())?;
+    writeln!(output, "Total unique transactions in file 2: {}", diff.only_in_right.len())?;
+    writeln!(output, "Total mismatched transactions: {}", diff.mismatched.len())?;
 
     Ok(())
 }
 
+/// Читает все транзакции файла; сверка идет по `id`, поэтому повтор `id` считается ошибкой.
+fn read_unique<TFormat, TSource>(source: TSource, label: &'static str) -> Result<Vec<Transaction>, Error>
+where
+    TFormat: Readable<TSource> + Into<Transaction>,
+    TSource: Read,
+{
+    let mut ids = HashSet::new();
+
+    Parser::<TFormat, _>::new(source)
+        .labeled(label)
+        .map(|res| -> Result<Transaction, Error> {
+            let transaction: Transaction = res?.into();
+            if !ids.insert(transaction.id) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Duplicate transaction found in {} with id: {}", label, transaction.id)
+                ));
+            }
+            Ok(transaction)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -149,6 +146,7 @@ mod tests {
             timestamp: 1234567890,
             transaction_status: TransactionStatus::Success,
             description: "test".to_string(),
+            currency: None,
         }
     }
 
@@ -164,7 +162,7 @@ TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
         let source2 = Cursor::new(csv_data);
         let mut output = Vec::new();
 
-        let result = compare::<YPBankCsvRecord, YPBankCsvRecord, _, _, _>(source1, source2, &mut output);
+        let result = compare::<YPBankCsvRecord, YPBankCsvRecord, _, _, _>(source1, source2, &mut output, 0);
 
         assert!(result.is_ok());
         let output_str = String::from_utf8(output).unwrap();
@@ -213,7 +211,7 @@ TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
         let source2 = Cursor::new(csv_data);
         let mut output = Vec::new();
 
-        let result = compare::<YPBankTextRecord, YPBankCsvRecord, _, _, _>(source1, source2, &mut output);
+        let result = compare::<YPBankTextRecord, YPBankCsvRecord, _, _, _>(source1, source2, &mut output, 0);
 
         assert!(result.is_ok());
         let output_str = String::from_utf8(output).unwrap();
@@ -245,7 +243,7 @@ TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
         let source2 = Cursor::new(csv_data);
         let mut output = Vec::new();
 
-        let result = compare::<YPBankBinRecord, YPBankCsvRecord, _, _, _>(source1, source2, &mut output);
+        let result = compare::<YPBankBinRecord, YPBankCsvRecord, _, _, _>(source1, source2, &mut output, 0);
 
         assert!(result.is_ok());
         let output_str = String::from_utf8(output).unwrap();
@@ -298,7 +296,7 @@ DESCRIPTION: \"test\"
         let source2 = Cursor::new(bin_data);
         let mut output = Vec::new();
 
-        let result = compare::<YPBankTextRecord, YPBankBinRecord, _, _, _>(source1, source2, &mut output);
+        let result = compare::<YPBankTextRecord, YPBankBinRecord, _, _, _>(source1, source2, &mut output, 0);
 
         assert!(result.is_ok());
         let output_str = String::from_utf8(output).unwrap();
@@ -310,4 +308,41 @@ DESCRIPTION: \"test\"
         assert!(!output_str.contains("Transaction with id 1"));
         assert!(!output_str.contains("Transaction with id 6"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_timestamp_tolerance() {
+        let csv_data1 = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,1,2,100,1234567000,SUCCESS,\"test\"
+";
+        let csv_data2 = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,1,2,100,1234567900,SUCCESS,\"test\"
+";
+
+        let mut output = Vec::new();
+        compare::<YPBankCsvRecord, YPBankCsvRecord, _, _, _>(Cursor::new(csv_data1), Cursor::new(csv_data2), &mut output, 1000).unwrap();
+        assert!(String::from_utf8(output).unwrap().contains("Files are identical"));
+
+        let mut output = Vec::new();
+        compare::<YPBankCsvRecord, YPBankCsvRecord, _, _, _>(Cursor::new(csv_data1), Cursor::new(csv_data2), &mut output, 0).unwrap();
+        let output_str = String::from_utf8(output).unwrap();
+        assert!(output_str.contains("Transaction with id 1 differs\n    TIMESTAMP: \"1234567000\" in file 1, \"1234567900\" in file 2\n"));
+        assert!(!output_str.contains("is only in file"));
+    }
+
+    #[test]
+    fn test_duplicate_id_is_an_error() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,1,2,100,1234567890,SUCCESS,\"test\"
+1,DEPOSIT,1,2,200,1234567890,SUCCESS,\"test\"
+";
+        let mut output = Vec::new();
+
+        let error = compare::<YPBankCsvRecord, YPBankCsvRecord, _, _, _>(Cursor::new(csv_data), Cursor::new(csv_data), &mut output, 0).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(error.to_string().contains("file 1 with id: 1"));
+    }
+}
@@ -2,7 +2,7 @@ use clap::Parser as ClapParser;
 use std::fs::File;
 use std::io::{Error, ErrorKind, Read, Write};
 use std::path::Path;
-use transactions_parser::{Parser, Readable, Writable, YPBankBinRecord, YPBankCsvRecord, YPBankTextRecord};
+use transactions_parser::{Parser, Readable, Transaction, Writable, YPBankBinRecord, YPBankCsvRecord, YPBankTextRecord};
 
 #[derive(ClapParser, Debug)]
 #[command(version, about, long_about = None)]
@@ -45,21 +45,27 @@ fn main() -> std::io::Result<()> {
 
 fn convert<TFrom, TTo, TSource, TTarget>(source: TSource, target: TTarget) -> Result<(), Error>
 where
-    TFrom: Readable<TSource>,
+    TFrom: Readable<TSource> + Into<Transaction>,
     TTo: Writable,
     TSource: Read,
     TTarget: Write
 {
-    let mut parser = Parser::<TFrom, _>::new(source);
+    let parser = Parser::<TFrom, _>::new(source);
     let mut serializer = transactions_parser::Serializer::<TTo, _>::new(target);
-
-    let target_records = parser
-        .by_ref()
-        .map(|res| TTo::from(res.into()));
+    let mut read_error = None;
+
+    // конвертация останавливается на первой ошибке чтения
+    let target_records = parser.map_while(|res| match res {
+        Ok(record) => Some(TTo::from(record.into())),
+        Err(e) => {
+            read_error = Some(e);
+            None
+        }
+    });
 
     serializer.serialize(target_records).map_err(|e| e.into())?;
 
-    if let Some(err) = parser.read_error {
+    if let Some(err) = read_error {
         return Err(err.into());
     }
 
@@ -0,0 +1,139 @@
+use crate::errors::CsvRecordError;
+use crate::{Parser, YPBankCsvRecord};
+use std::collections::VecDeque;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+/// Асинхронный парсер CSV поверх `tokio::io::AsyncRead` (возможность `async`).
+///
+/// Читает источник построчно, не загружая его целиком, и передает каждую полную запись
+/// синхронному `Parser<YPBankCsvRecord, _>`, поэтому правила разбора, сообщения об ошибках
+/// и пропуск испорченных строк совпадают с синхронным путем. Записи возвращает `next().await`.
+pub struct AsyncParser<R> {
+    source: BufReader<R>,
+    parser: Parser<YPBankCsvRecord, VecDeque<u8>>,
+    /// Сколько содержательных строк (включая заголовок) передано парсеру, но еще не прочитано.
+    queued: usize,
+    header_read: bool,
+    source_finished: bool,
+    finished: bool,
+}
+
+impl<R: AsyncRead + Unpin> AsyncParser<R> {
+    /// Создает парсер поверх асинхронного источника; первая строка — заголовок.
+    pub fn new(source: R) -> Self {
+        Self {
+            source: BufReader::new(source),
+            parser: Parser::new(VecDeque::new()),
+            queued: 0,
+            header_read: false,
+            source_finished: false,
+            finished: false,
+        }
+    }
+
+    /// Читает следующую запись.
+    ///
+    /// Как и итератор `Parser`, выдает `Err` для испорченной записи и продолжает со следующей;
+    /// после ошибки чтения источника возвращает `None`.
+    pub async fn next(&mut self) -> Option<Result<YPBankCsvRecord, CsvRecordError>> {
+        if self.finished {
+            return None;
+        }
+
+        // перед первой записью парсеру нужен еще и заголовок
+        let needed = if self.header_read { 1 } else { 2 };
+        while self.queued < needed && !self.source_finished {
+            if let Err(e) = self.queue_row().await {
+                self.finished = true;
+                return Some(Err(e.into()));
+            }
+        }
+
+        let record = self.parser.next();
+        self.queued = self.queued.saturating_sub(needed);
+        self.header_read = true;
+
+        record
+    }
+
+    /// Передает парсеру следующую строку CSV целиком, вместе с продолжениями поля в кавычках.
+    ///
+    /// Строки из пробелов, запятых и пустых кавычек парсер пропускает, поэтому они не учитываются в `queued`:
+    /// иначе парсер, пропустив такую строку, принял бы опустевший буфер за конец данных.
+    async fn queue_row(&mut self) -> std::io::Result<()> {
+        let mut in_quotes = false;
+        let mut has_content = false;
+        let mut line = Vec::new();
+
+        loop {
+            line.clear();
+            if self.source.read_until(b'\n', &mut line).await? == 0 {
+                self.source_finished = true;
+                break;
+            }
+
+            for &byte in &line {
+                match byte {
+                    b'"' => in_quotes = !in_quotes,
+                    b',' => {}
+                    byte if byte.is_ascii_whitespace() => {}
+                    _ => has_content = true,
+                }
+            }
+            self.parser.reader.source_mut().extend(&line);
+
+            if !in_quotes {
+                break;
+            }
+        }
+
+        if has_content {
+            self.queued += 1;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const CSV: &str = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Salary\"
+
+2,TRANSFER,10,20,50,2000,PENDING,\"Rent,
+second line\"
+, , ,,,,,
+3,WITHDRAWAL,10,0,x,3000,SUCCESS,\"Broken\"
+4,WITHDRAWAL,10,0,25,4000,FAILURE,\"Cash\"";
+
+    fn describe(result: Result<YPBankCsvRecord, CsvRecordError>) -> Result<YPBankCsvRecord, String> {
+        result.map_err(|e| e.to_string())
+    }
+
+    #[tokio::test]
+    async fn async_parser_matches_sync_parser() {
+        let expected: Vec<_> = Parser::<YPBankCsvRecord, _>::new(Cursor::new(CSV)).map(describe).collect();
+
+        let mut parser = AsyncParser::new(CSV.as_bytes());
+        let mut actual = Vec::new();
+        while let Some(record) = parser.next().await {
+            actual.push(describe(record));
+        }
+
+        assert_eq!(actual.len(), 4);
+        assert!(actual[2].is_err());
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn empty_and_header_only_sources_yield_nothing() {
+        assert!(AsyncParser::new(&b""[..]).next().await.is_none());
+
+        let header = "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n";
+        assert!(AsyncParser::new(header.as_bytes()).next().await.is_none());
+    }
+}
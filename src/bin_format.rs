@@ -1,6 +1,6 @@
 use crate::common::{Transaction, TransactionStatus, TransactionType};
 use crate::{Readable, Writable};
-use std::io::{BufReader, Error, ErrorKind, Read, Write};
+use std::io::{BufReader, Error, ErrorKind, Read, Seek, SeekFrom, Write};
 
 /// Запись транзакции в бинарном формате.
 ///
@@ -8,6 +8,8 @@ use std::io::{BufReader, Error, ErrorKind, Read, Write};
 /// 1. Магические байты `YPBN` (4 байта) перед каждой записью.
 /// 2. Размер записи (u32, Big-Endian).
 /// 3. Поля записи в бинарном виде (числа в Big-Endian, описание с префиксом длины).
+///
+/// Код валюты (`Transaction::currency`) в формате не хранится: при записи он отбрасывается.
 #[derive(Debug, PartialEq)]
 pub struct YPBankBinRecord {
     id: u64,
@@ -31,6 +33,7 @@ impl From<YPBankBinRecord> for Transaction {
             timestamp: value.timestamp,
             transaction_status: value.transaction_status,
             description: value.description,
+            currency: None,
         }
     }
 }
@@ -50,6 +53,49 @@ impl From<Transaction> for YPBankBinRecord {
     }
 }
 
+//noinspection DuplicatedCode
+impl YPBankBinRecord {
+    /// Уникальный идентификатор транзакции.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Тип операции.
+    pub fn transaction_type(&self) -> TransactionType {
+        self.transaction_type
+    }
+
+    /// ID отправителя.
+    pub fn from_user_id(&self) -> u64 {
+        self.from_user_id
+    }
+
+    /// ID получателя.
+    pub fn to_user_id(&self) -> u64 {
+        self.to_user_id
+    }
+
+    /// Сумма операции.
+    pub fn amount(&self) -> i64 {
+        self.amount
+    }
+
+    /// Временная метка операции.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Статус транзакции.
+    pub fn transaction_status(&self) -> TransactionStatus {
+        self.transaction_status
+    }
+
+    /// Описание транзакции.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
 impl<R: Read> Readable<R> for YPBankBinRecord {
     type Reader = BufReader<R>;
     type Error = Error;
@@ -58,10 +104,29 @@ impl<R: Read> Readable<R> for YPBankBinRecord {
         BufReader::new(source)
     }
 
+    fn build_reader_with_capacity(source: R, capacity: usize) -> Self::Reader {
+        BufReader::with_capacity(capacity, source)
+    }
+
+    fn rewind(reader: &mut Self::Reader) -> Result<(), Error>
+    where
+        R: Seek
+    {
+        reader.seek(SeekFrom::Start(0)).map(|_| ())
+    }
+
     fn read(reader: &mut Self::Reader) -> Result<Self, Error> {
         let mut magic = [0u8; 4];
 
-        let bytes_read = reader.read(&mut magic)?;
+        let mut bytes_read = 0;
+
+        // источник может отдавать данные порциями, поэтому дочитываем magic до конца или до EOF
+        while bytes_read < magic.len() {
+            match reader.read(&mut magic[bytes_read..])? {
+                0 => break,
+                n => bytes_read += n,
+            }
+        }
 
         if bytes_read == 0 {
             //normal eof
@@ -76,60 +141,72 @@ impl<R: Read> Readable<R> for YPBankBinRecord {
             return Err(Error::new(ErrorKind::InvalidData, "Invalid magic bytes"));
         }
 
-        let mut size_buf = [0u8; 4];
-        reader.read_exact(&mut size_buf)?;
-        let _record_size = u32::from_be_bytes(size_buf);
+        // магические байты прочитаны, поэтому конец данных внутри записи — это обрезанный файл, а не штатный EOF
+        read_body(reader).map_err(|e| match e.kind() {
+            ErrorKind::UnexpectedEof => Error::new(ErrorKind::InvalidData, "File truncated inside record"),
+            _ => e,
+        })
+    }
+}
 
-        let mut u64_buf = [0u8; 8];
-        reader.read_exact(&mut u64_buf)?;
-        let id = u64::from_be_bytes(u64_buf);
+/// Читает запись после магических байтов: размер и поля.
+fn read_body<R: Read>(reader: &mut BufReader<R>) -> Result<YPBankBinRecord, Error> {
+    let mut size_buf = [0u8; 4];
+    reader.read_exact(&mut size_buf)?;
+    let _record_size = u32::from_be_bytes(size_buf);
 
-        let mut u8_buf = [0u8; 1];
-        reader.read_exact(&mut u8_buf)?;
-        let transaction_type = TransactionType::try_from(u8::from_be_bytes(u8_buf))?;
+    let mut u64_buf = [0u8; 8];
+    reader.read_exact(&mut u64_buf)?;
+    let id = u64::from_be_bytes(u64_buf);
 
-        reader.read_exact(&mut u64_buf)?;
-        let from_user_id = u64::from_be_bytes(u64_buf);
+    let mut u8_buf = [0u8; 1];
+    reader.read_exact(&mut u8_buf)?;
+    let transaction_type = TransactionType::try_from(u8::from_be_bytes(u8_buf))?;
 
-        reader.read_exact(&mut u64_buf)?;
-        let to_user_id = u64::from_be_bytes(u64_buf);
+    reader.read_exact(&mut u64_buf)?;
+    let from_user_id = u64::from_be_bytes(u64_buf);
 
-        reader.read_exact(&mut u64_buf)?;
-        let amount = i64::from_be_bytes(u64_buf);
+    reader.read_exact(&mut u64_buf)?;
+    let to_user_id = u64::from_be_bytes(u64_buf);
 
-        reader.read_exact(&mut u64_buf)?;
-        let timestamp = u64::from_be_bytes(u64_buf);
+    reader.read_exact(&mut u64_buf)?;
+    let amount = i64::from_be_bytes(u64_buf);
 
-        reader.read_exact(&mut u8_buf)?;
-        let transaction_status = TransactionStatus::try_from(u8::from_be_bytes(u8_buf))?;
+    reader.read_exact(&mut u64_buf)?;
+    let timestamp = u64::from_be_bytes(u64_buf);
 
-        let mut len_buf = [0u8; 4];
-        reader.read_exact(&mut len_buf)?;
-        let desc_len = u32::from_be_bytes(len_buf) as usize;
-        let mut description = String::new();
+    reader.read_exact(&mut u8_buf)?;
+    let transaction_status = TransactionStatus::try_from(u8::from_be_bytes(u8_buf))?;
 
-        if desc_len > 0 {
-            let mut desc_bytes = vec![0u8; desc_len];
-            reader.read_exact(&mut desc_bytes)?;
-            description = String::from_utf8(desc_bytes)
-                .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
-        }
+    let mut len_buf = [0u8; 4];
+    reader.read_exact(&mut len_buf)?;
+    let desc_len = u32::from_be_bytes(len_buf) as usize;
 
-        Ok(YPBankBinRecord {
-            id,
-            transaction_type,
-            from_user_id,
-            to_user_id,
-            amount,
-            timestamp,
-            transaction_status,
-            description,
-        })
+    // буфер растет по мере чтения: испорченная длина не должна приводить к огромному выделению памяти
+    let mut desc_bytes = Vec::new();
+    reader.by_ref().take(desc_len as u64).read_to_end(&mut desc_bytes)?;
+    if desc_bytes.len() < desc_len {
+        return Err(Error::from(ErrorKind::UnexpectedEof));
     }
+
+    let description = String::from_utf8(desc_bytes)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+    Ok(YPBankBinRecord {
+        id,
+        transaction_type,
+        from_user_id,
+        to_user_id,
+        amount,
+        timestamp,
+        transaction_status,
+        description,
+    })
 }
 
 impl Writable for YPBankBinRecord {
     type Error = Error;
+    type Options = ();
 
     fn write_header<W: Write>(_: &mut W) -> Result<(), Error> {
         Ok(())
@@ -209,7 +286,7 @@ mod tests {
         assert_eq!(&body[25..33], &record.amount.to_be_bytes());
         assert_eq!(&body[33..41], &record.timestamp.to_be_bytes());
 
-        assert_eq!(body[41], record.transaction_status.into());
+        assert_eq!(body[41], record.transaction_status.as_u8());
         assert_eq!(&body[42..46], &(record.description.len() as u32).to_be_bytes());
         assert_eq!(&body[46..], record.description.as_bytes());
     }
@@ -224,9 +301,8 @@ mod tests {
         let cursor = Cursor::new(buffer);
         let mut parser = Parser::<YPBankBinRecord, _>::new(cursor);
 
-        let read_record = parser.next().expect("Should return a record");
+        let read_record = parser.next().expect("Should return a record").unwrap();
         assert!(parser.next().is_none());
-        assert!(parser.read_error.is_none(), "Expected no read error, got: {:?}", parser.read_error);
 
         assert_eq!(read_record, original_record);
     }
@@ -242,14 +318,9 @@ mod tests {
         let cursor = Cursor::new(buffer);
         let mut parser = Parser::<YPBankBinRecord, _>::new(cursor);
 
-        let result = parser.next();
-        assert!(result.is_none());
-
-        if let Some(err) = parser.read_error {
-            assert_eq!(err.kind(), ErrorKind::InvalidData);
-        } else {
-            panic!("Expected an error, but got none");
-        }
+        let err = parser.next().expect("Expected an error, but got none").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(parser.next().is_none());
     }
 
     #[test]
@@ -264,9 +335,34 @@ mod tests {
         let cursor = Cursor::new(truncated_buffer);
         let mut parser = Parser::<YPBankBinRecord, _>::new(cursor);
 
-        let result = parser.next();
-        assert!(result.is_none());
-        assert!(parser.read_error.is_none(), "Expected no read error, got: {:?}", parser.read_error);
+        let err = parser.next().expect("Truncated record should be an error").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn every_truncation_point_is_a_clean_error() {
+        let mut buffer = Vec::new();
+        sample_record().write(&mut buffer).unwrap();
+
+        for len in 1..buffer.len() {
+            let mut parser = Parser::<YPBankBinRecord, _>::new(Cursor::new(&buffer[..len]));
+
+            let err = parser.next().expect("Truncated record should be an error").unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData, "Truncated at {} bytes", len);
+        }
+    }
+
+    #[test]
+    fn oversized_description_length_is_an_error() {
+        let mut buffer = Vec::new();
+        sample_record().write(&mut buffer).unwrap();
+        // длина описания: 4 (magic) + 4 (size) + 8 + 1 + 8 + 8 + 8 + 8 + 1 = 50
+        buffer[50..54].copy_from_slice(&u32::MAX.to_be_bytes());
+
+        let mut parser = Parser::<YPBankBinRecord, _>::new(Cursor::new(buffer));
+
+        assert_eq!(parser.next().unwrap().unwrap_err().kind(), ErrorKind::InvalidData);
     }
 
     #[test]
@@ -331,14 +427,13 @@ mod tests {
         let cursor = Cursor::new(buffer);
         let mut parser = Parser::<YPBankBinRecord, _>::new(cursor);
 
-        let read_r1 = parser.next().expect("First record expected");
+        let read_r1 = parser.next().expect("First record expected").unwrap();
         assert_eq!(read_r1, record1);
 
-        let read_r2 = parser.next().expect("Second record expected");
+        let read_r2 = parser.next().expect("Second record expected").unwrap();
         assert_eq!(read_r2, record2);
 
         assert!(parser.next().is_none());
-        assert!(parser.read_error.is_none(), "Expected no read error, got: {:?}", parser.read_error);
     }
 
     #[test]
@@ -353,13 +448,9 @@ mod tests {
         let cursor = Cursor::new(buffer);
         let mut parser = Parser::<YPBankBinRecord, _>::new(cursor);
 
-        assert!(parser.next().is_none());
-        if let Some(err) = parser.read_error {
-            assert_eq!(err.kind(), ErrorKind::InvalidData);
-            assert!(err.to_string().contains("Wrong transaction type value"));
-        } else {
-            panic!("Expected an error for invalid TransactionType, but got none");
-        }
+        let err = parser.next().expect("Expected an error for invalid TransactionType, but got none").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains("Invalid transaction type value"));
 
         let mut buffer = Vec::new();
         record.write(&mut buffer).unwrap();
@@ -369,12 +460,8 @@ mod tests {
         let cursor = Cursor::new(buffer);
         let mut parser = Parser::<YPBankBinRecord, _>::new(cursor);
 
-        assert!(parser.next().is_none());
-        if let Some(err) = parser.read_error {
-            assert_eq!(err.kind(), ErrorKind::InvalidData);
-            assert!(err.to_string().contains("Wrong transaction status value"));
-        } else {
-            panic!("Expected an error for invalid TransactionStatus, but got none");
-        }
+        let err = parser.next().expect("Expected an error for invalid TransactionStatus, but got none").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains("Invalid transaction status value"));
     }
 }
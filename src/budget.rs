@@ -0,0 +1,158 @@
+use crate::errors::{BudgetError, BudgetExceeded, BudgetLimit};
+use crate::{Parser, Readable};
+use std::cell::Cell;
+use std::io::Read;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Ограничения на ресурсы, расходуемые при разборе одного потока.
+///
+/// Защищает от враждебных входных данных: разбор прерывается, как только превышен
+/// либо объем прочитанных байтов, либо время с начала разбора.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceBudget {
+    /// Максимальное число байтов, прочитанных из источника.
+    pub max_bytes: u64,
+    /// Максимальное время разбора.
+    pub max_duration: Duration,
+}
+
+/// Источник, подсчитывающий прочитанные байты.
+pub struct CountingSource<R> {
+    inner: R,
+    bytes_read: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for CountingSource<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.bytes_read.set(self.bytes_read.get() + count as u64);
+        Ok(count)
+    }
+}
+
+/// Парсер, проверяющий `ResourceBudget` перед чтением каждой записи.
+///
+/// Учитываются байты, фактически прочитанные из источника, включая упреждающее чтение в буфер.
+/// При превышении бюджета итератор один раз выдает `BudgetError::Exceeded` и затем возвращает `None`.
+pub struct BudgetedParser<TRecord, Source>
+where
+    TRecord: Readable<CountingSource<Source>>,
+    Source: Read
+{
+    parser: Parser<TRecord, CountingSource<Source>>,
+    budget: ResourceBudget,
+    bytes_read: Rc<Cell<u64>>,
+    started: Instant,
+    exceeded: bool,
+}
+
+impl<TRecord, Source> BudgetedParser<TRecord, Source>
+where
+    TRecord: Readable<CountingSource<Source>>,
+    Source: Read
+{
+    /// Создает парсер с указанным бюджетом; отсчет времени начинается в момент создания.
+    pub fn new(source: Source, budget: ResourceBudget) -> Self {
+        let bytes_read = Rc::new(Cell::new(0));
+        let source = CountingSource {
+            inner: source,
+            bytes_read: bytes_read.clone(),
+        };
+
+        Self {
+            parser: Parser::new(source),
+            budget,
+            bytes_read,
+            started: Instant::now(),
+            exceeded: false,
+        }
+    }
+
+    fn check_budget(&self) -> Result<(), BudgetExceeded> {
+        if self.bytes_read.get() > self.budget.max_bytes {
+            return Err(BudgetExceeded { which: BudgetLimit::Bytes });
+        }
+
+        if self.started.elapsed() > self.budget.max_duration {
+            return Err(BudgetExceeded { which: BudgetLimit::Duration });
+        }
+
+        Ok(())
+    }
+}
+
+impl<TRecord, Source> Iterator for BudgetedParser<TRecord, Source>
+where
+    TRecord: Readable<CountingSource<Source>>,
+    Source: Read
+{
+    type Item = Result<TRecord, BudgetError<TRecord::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.exceeded {
+            return None;
+        }
+
+        if let Err(e) = self.check_budget() {
+            self.exceeded = true;
+            return Some(Err(BudgetError::Exceeded(e)));
+        }
+
+        self.parser.next().map(|record| record.map_err(BudgetError::Read))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::YPBankCsvRecord;
+    use std::io::Cursor;
+    use std::thread;
+
+    const CSV_DATA: &str = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc 1\"
+2,WITHDRAWAL,10,0,50,2000,PENDING,\"Desc 2\"
+";
+
+    /// Источник, замедляющий каждое чтение.
+    struct SlowSource<R>(R);
+
+    impl<R: Read> Read for SlowSource<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            thread::sleep(Duration::from_millis(20));
+            self.0.read(buf)
+        }
+    }
+
+    #[test]
+    fn generous_budget_reads_everything() {
+        let budget = ResourceBudget { max_bytes: 1024, max_duration: Duration::from_secs(60) };
+        let mut parser = BudgetedParser::<YPBankCsvRecord, _>::new(Cursor::new(CSV_DATA), budget);
+
+        assert_eq!(parser.by_ref().map(Result::unwrap).count(), 2);
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn byte_budget_is_enforced() {
+        let budget = ResourceBudget { max_bytes: 16, max_duration: Duration::from_secs(60) };
+        let mut parser = BudgetedParser::<YPBankCsvRecord, _>::new(Cursor::new(CSV_DATA), budget);
+
+        assert!(parser.next().unwrap().is_ok());
+        assert!(matches!(parser.next(), Some(Err(BudgetError::Exceeded(BudgetExceeded { which: BudgetLimit::Bytes })))));
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn time_budget_is_enforced() {
+        let budget = ResourceBudget { max_bytes: u64::MAX, max_duration: Duration::from_millis(5) };
+        let source = SlowSource(Cursor::new(CSV_DATA));
+        let mut parser = BudgetedParser::<YPBankCsvRecord, _>::new(source, budget);
+
+        assert!(parser.next().unwrap().is_ok());
+        assert!(matches!(parser.next(), Some(Err(BudgetError::Exceeded(BudgetExceeded { which: BudgetLimit::Duration })))));
+        assert!(parser.next().is_none());
+    }
+}
@@ -0,0 +1,126 @@
+use crate::common::Transaction;
+use crate::errors::ParseError;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+
+/// Настройки приведения пакета транзакций к каноническому виду.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalPolicy {
+    /// Управляющие символы, допустимые в описании.
+    pub allowed_control_chars: Vec<char>,
+    /// Считать ошибкой записи с одинаковым `id`, но разным содержимым.
+    /// Если выключено, из таких записей остается первая.
+    pub reject_conflicting_duplicates: bool,
+}
+
+impl Default for CanonicalPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_control_chars: Transaction::DEFAULT_ALLOWED_CONTROL_CHARS.to_vec(),
+            reject_conflicting_duplicates: true,
+        }
+    }
+}
+
+/// Приводит пакет транзакций к каноническому виду за один вызов.
+///
+/// Шаги выполняются в фиксированном порядке:
+/// 1. проверка — первая ошибка чтения или описание с недопустимыми управляющими символами
+///    прерывает обработку;
+/// 2. удаление дубликатов по `id` — полные копии отбрасываются, конфликтующие записи
+///    обрабатываются согласно `policy`;
+/// 3. нормализация описаний — обрезка пробелов по краям и замена серий пробельных символов одним пробелом;
+/// 4. сортировка по (`timestamp`, `id`).
+pub fn canonicalize<I, E>(records: I, policy: &CanonicalPolicy) -> Result<Vec<Transaction>, ParseError>
+where
+    I: IntoIterator<Item = Result<Transaction, E>>,
+    E: Into<ParseError>,
+{
+    let mut batch: Vec<Transaction> = Vec::new();
+    let mut positions: HashMap<u64, usize> = HashMap::new();
+
+    for record in records {
+        let transaction = record.map_err(Into::into)?;
+
+        transaction
+            .description_is_printable(&policy.allowed_control_chars)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+        match positions.get(&transaction.id) {
+            Some(&position) if batch[position] == transaction => {}
+            Some(_) if policy.reject_conflicting_duplicates => {
+                let message = format!("Conflicting duplicates of transaction {}", transaction.id);
+                return Err(Error::new(ErrorKind::InvalidData, message).into());
+            }
+            Some(_) => {}
+            None => {
+                positions.insert(transaction.id, batch.len());
+                batch.push(transaction);
+            }
+        }
+    }
+
+    for transaction in &mut batch {
+        transaction.description = transaction.description.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+
+    batch.sort_by_key(|t| (t.timestamp, t.id));
+
+    Ok(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::test_transaction;
+
+    #[test]
+    fn messy_batch_becomes_canonical() {
+        let records = vec![
+            Transaction { timestamp: 200, description: "  Late   payment ".to_string(), ..test_transaction(3) },
+            Transaction { timestamp: 100, description: "First".to_string(), ..test_transaction(1) },
+            Transaction { timestamp: 100, description: "Same\ttime".to_string(), ..test_transaction(2) },
+            Transaction { timestamp: 100, description: "First".to_string(), ..test_transaction(1) },
+        ];
+
+        let canonical = canonicalize(records.into_iter().map(Ok::<_, ParseError>), &CanonicalPolicy::default()).unwrap();
+
+        assert_eq!(
+            canonical,
+            vec![
+                Transaction { timestamp: 100, description: "First".to_string(), ..test_transaction(1) },
+                Transaction { timestamp: 100, description: "Same time".to_string(), ..test_transaction(2) },
+                Transaction { timestamp: 200, description: "Late payment".to_string(), ..test_transaction(3) },
+            ]
+        );
+    }
+
+    #[test]
+    fn conflicting_duplicates_follow_policy() {
+        let records = || vec![
+            Transaction { timestamp: 100, description: "First".to_string(), ..test_transaction(1) },
+            Transaction { timestamp: 150, description: "Changed".to_string(), ..test_transaction(1) },
+        ].into_iter().map(Ok::<_, ParseError>);
+
+        assert!(canonicalize(records(), &CanonicalPolicy::default()).is_err());
+
+        let lenient = CanonicalPolicy {
+            reject_conflicting_duplicates: false,
+            ..CanonicalPolicy::default()
+        };
+        assert_eq!(canonicalize(records(), &lenient).unwrap(), vec![Transaction { timestamp: 100, description: "First".to_string(), ..test_transaction(1) }]);
+    }
+
+    #[test]
+    fn non_printable_description_fails_validation() {
+        let records = vec![
+            Ok::<_,
+            ParseError>(Transaction { timestamp: 100, description: "Bell\x07".to_string(), ..test_transaction(1) }),
+        ];
+
+        assert!(matches!(
+            canonicalize(records, &CanonicalPolicy::default()),
+            Err(ParseError::Io(e)) if e.kind() == ErrorKind::InvalidData
+        ));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::common::Transaction;
+use crate::csv_format::CsvReader;
+use crate::errors::ChainError;
+use crate::{Writable, YPBankCsvRecord};
+use sha2::{Digest, Sha256};
+use std::io::{Error, ErrorKind, Read, Write};
+
+/// Имя столбца с цепочечной контрольной суммой.
+const CHAIN_COLUMN: &str = "CHAIN";
+
+/// Вычисляет звено цепочки: `SHA-256(previous || record_bytes)`.
+fn next_link(previous: &[u8; 32], record_bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(previous);
+    hasher.update(record_bytes);
+    hasher.finalize().into()
+}
+
+/// Строка CSV-записи без перевода строки — данные, по которым считается звено цепочки.
+fn record_bytes(record: &YPBankCsvRecord) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    record.write(&mut bytes)?;
+    bytes.pop();
+    Ok(bytes)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Записывает транзакции в CSV с дополнительным столбцом `CHAIN`.
+///
+/// Значение `CHAIN` — шестнадцатеричный SHA-256 от предыдущего звена (для первой записи —
+/// 32 нулевых байта) и байтов CSV-строки записи. Изменение любой записи нарушает цепочку
+/// начиная с неё (см. `verify_chain`). Столбец `CURRENCY` не записывается: валюта отбрасывается.
+pub fn serialize_with_chain<I, W>(records: I, mut writer: W) -> Result<(), Error>
+where
+    I: IntoIterator<Item = Transaction>,
+    W: Write,
+{
+    writeln!(writer, "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION,{}", CHAIN_COLUMN)?;
+
+    let mut link = [0u8; 32];
+    for transaction in records {
+        let bytes = record_bytes(&YPBankCsvRecord::from(Transaction { currency: None, ..transaction }))?;
+        link = next_link(&link, &bytes);
+
+        writer.write_all(&bytes)?;
+        writeln!(writer, ",{}", to_hex(&link))?;
+    }
+
+    writer.flush()
+}
+
+/// Проверяет цепочку контрольных сумм в CSV, записанном `serialize_with_chain`.
+///
+/// Возвращает `ChainError::Broken` с номером (с нуля) первой записи, звено которой не совпало.
+pub fn verify_chain<R: Read>(source: R) -> Result<(), ChainError> {
+    let mut reader = CsvReader::new(source);
+    let invalid_data = |e: csv::Error| Error::new(ErrorKind::InvalidData, e);
+
+    let mut headers = reader.inner.headers().map_err(invalid_data)?.clone();
+    if headers.iter().next_back() != Some(CHAIN_COLUMN) {
+        return Err(Error::new(ErrorKind::InvalidData, "Missing CHAIN column").into());
+    }
+    headers.truncate(headers.len() - 1);
+
+    let mut link = [0u8; 32];
+    let mut record = csv::StringRecord::new();
+    let mut index = 0;
+
+    while reader.inner.read_record(&mut record).map_err(invalid_data)? {
+        let expected = record.get(headers.len()).unwrap_or_default().to_owned();
+        record.truncate(headers.len());
+
+        let parsed: YPBankCsvRecord = record.deserialize(Some(&headers)).map_err(invalid_data)?;
+        link = next_link(&link, &record_bytes(&parsed)?);
+
+        if to_hex(&link) != expected {
+            return Err(ChainError::Broken { index });
+        }
+
+        index += 1;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionStatus, TransactionType};
+    use std::io::Cursor;
+
+    fn transactions() -> Vec<Transaction> {
+        (1..=3)
+            .map(|id| Transaction {
+                id,
+                transaction_type: TransactionType::Transfer,
+                from_user_id: 10,
+                to_user_id: 20,
+                amount: id as i64 * 100,
+                timestamp: 1_700_000_000_000 + id,
+                transaction_status: TransactionStatus::Success,
+                description: format!("Payment {}", id),
+                currency: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn intact_chain_verifies() {
+        let mut output = Vec::new();
+        serialize_with_chain(transactions(), &mut output).unwrap();
+
+        assert!(verify_chain(Cursor::new(output)).is_ok());
+    }
+
+    #[test]
+    fn altered_amount_breaks_chain_at_that_record() {
+        let mut output = Vec::new();
+        serialize_with_chain(transactions(), &mut output).unwrap();
+
+        let tampered = String::from_utf8(output).unwrap().replace(",200,", ",999,");
+
+        assert!(matches!(verify_chain(Cursor::new(tampered)), Err(ChainError::Broken { index: 1 })));
+    }
+}
@@ -0,0 +1,109 @@
+use std::io::{self, Read};
+use std::sync::mpsc::Receiver;
+
+/// Источник данных, получающий байты порциями из канала.
+///
+/// Позволяет `Parser` разбирать записи по мере поступления данных, не собирая весь файл в памяти.
+/// Чтение блокируется до прихода следующей порции; когда отправитель закрывает канал,
+/// `ChannelReader` сообщает о конце данных.
+pub struct ChannelReader {
+    receiver: Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl ChannelReader {
+    /// Создает читателя поверх принимающей стороны канала.
+    pub fn new(receiver: Receiver<Vec<u8>>) -> Self {
+        Self {
+            receiver,
+            chunk: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        while self.position >= self.chunk.len() {
+            match self.receiver.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                Err(_) => return Ok(0), // отправитель закрыт — конец данных
+            }
+        }
+
+        let available = &self.chunk[self.position..];
+        let count = available.len().min(buf.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.position += count;
+
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Parser, Transaction, Writable, YPBankBinRecord, YPBankCsvRecord};
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn parses_csv_from_three_chunks_with_split_record() {
+        let (sender, receiver) = mpsc::channel();
+
+        let producer = thread::spawn(move || {
+            sender.send(b"TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n".to_vec()).unwrap();
+            sender.send(b"1,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc 1\"\n2,WITHDRAW".to_vec()).unwrap();
+            sender.send(b"AL,10,0,50,2000,PENDING,\"Desc 2\"\n".to_vec()).unwrap();
+        });
+
+        let parser = Parser::<YPBankCsvRecord, _>::new(ChannelReader::new(receiver));
+        let transactions: Vec<Transaction> = parser.map(|record| record.map(Into::into)).collect::<Result<_, _>>().unwrap();
+
+        producer.join().unwrap();
+
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].id, 1);
+        assert_eq!(transactions[1].id, 2);
+        assert_eq!(transactions[1].description, "Desc 2");
+    }
+
+    #[test]
+    fn parses_bin_with_chunk_boundary_inside_magic() {
+        let transaction = Transaction {
+            id: 7,
+            transaction_type: crate::TransactionType::Deposit,
+            from_user_id: 0,
+            to_user_id: 1,
+            amount: 10,
+            timestamp: 20,
+            transaction_status: crate::TransactionStatus::Success,
+            description: "Chunked".to_string(),
+            currency: None,
+        };
+
+        let mut bytes = Vec::new();
+        YPBankBinRecord::from(transaction.clone()).write(&mut bytes).unwrap();
+        YPBankBinRecord::from(transaction.clone()).write(&mut bytes).unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let split = bytes.len() / 2 + 2; // граница внутри магических байтов второй записи
+        sender.send(bytes[..split].to_vec()).unwrap();
+        sender.send(Vec::new()).unwrap();
+        sender.send(bytes[split..].to_vec()).unwrap();
+        drop(sender);
+
+        let parser = Parser::<YPBankBinRecord, _>::new(ChannelReader::new(receiver));
+        let transactions: Vec<Transaction> = parser.map(|record| record.map(Into::into)).collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(transactions, vec![transaction.clone(), transaction]);
+    }
+}
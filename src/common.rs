@@ -1,7 +1,10 @@
 use std::fmt::{Display, Formatter};
-use std::io::ErrorKind;
 use std::str::FromStr;
-use serde::{Deserialize, Serialize};
+use crate::errors::{InvalidEnumValue, LineError, NonPrintableDescription, ParseEnumError, ValidationError};
+#[cfg(feature = "chrono")]
+use crate::errors::TimestampOutOfRange;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::io::BufRead;
 
 /// Тип банковской транзакции.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Copy, Clone, Eq, Hash)]
@@ -15,7 +18,7 @@ pub enum TransactionType {
 }
 
 impl FromStr for TransactionType {
-    type Err = ();
+    type Err = ParseEnumError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -23,7 +26,11 @@ impl FromStr for TransactionType {
             "TRANSFER" => Ok(TransactionType::Transfer),
             "WITHDRAWAL" => Ok(TransactionType::Withdrawal),
 
-            _ => Err(()),
+            _ => Err(ParseEnumError {
+                enum_name: "TransactionType",
+                input: s.to_string(),
+                expected: &["DEPOSIT", "TRANSFER", "WITHDRAWAL"],
+            }),
         }
     }
 }
@@ -39,7 +46,7 @@ impl Display for TransactionType {
 }
 
 impl TryFrom<u8> for TransactionType {
-    type Error = std::io::Error;
+    type Error = InvalidEnumValue;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
@@ -47,11 +54,18 @@ impl TryFrom<u8> for TransactionType {
             1 => Ok(TransactionType::Transfer),
             2 => Ok(TransactionType::Withdrawal),
 
-            _ => Err(std::io::Error::new(ErrorKind::InvalidData, format!("Wrong transaction type value: {}", value))),
+            _ => Err(InvalidEnumValue { enum_name: "transaction type", value }),
         }
     }
 }
 
+impl TransactionType {
+    /// Числовой код типа, используемый в бинарном формате.
+    pub fn as_u8(&self) -> u8 {
+        u8::from(*self)
+    }
+}
+
 impl From<TransactionType> for u8 {
     fn from(transaction_type: TransactionType) -> Self {
         match transaction_type {
@@ -74,7 +88,7 @@ pub enum TransactionStatus {
 }
 
 impl FromStr for TransactionStatus {
-    type Err = ();
+    type Err = ParseEnumError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -82,13 +96,17 @@ impl FromStr for TransactionStatus {
             "FAILURE" => Ok(TransactionStatus::Failure),
             "SUCCESS" => Ok(TransactionStatus::Success),
 
-            _ => Err(()),
+            _ => Err(ParseEnumError {
+                enum_name: "TransactionStatus",
+                input: s.to_string(),
+                expected: &["PENDING", "SUCCESS", "FAILURE"],
+            }),
         }
     }
 }
 
 impl TryFrom<u8> for TransactionStatus {
-    type Error = std::io::Error;
+    type Error = InvalidEnumValue;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
@@ -96,11 +114,18 @@ impl TryFrom<u8> for TransactionStatus {
             1 => Ok(TransactionStatus::Failure),
             2 => Ok(TransactionStatus::Pending),
 
-            _ => Err(std::io::Error::new(ErrorKind::InvalidData, format!("Wrong transaction status value: {}", value))),
+            _ => Err(InvalidEnumValue { enum_name: "transaction status", value }),
         }
     }
 }
 
+impl TransactionStatus {
+    /// Числовой код статуса, используемый в бинарном формате.
+    pub fn as_u8(&self) -> u8 {
+        u8::from(*self)
+    }
+}
+
 impl From<TransactionStatus> for u8 {
     fn from(transaction_status: TransactionStatus) -> Self {
         match transaction_status {
@@ -121,6 +146,327 @@ impl Display for TransactionStatus {
     }
 }
 
+/// Литерал, которым `SentinelRendering::Literal` обычно обозначает внешнего контрагента.
+pub const EXTERNAL_USER_LITERAL: &str = "EXTERNAL";
+
+/// Способ вывода идентификатора внешнего контрагента (пользователь `0`) в текстовых форматах.
+///
+/// При чтении `0`, пустое значение и `EXTERNAL_USER_LITERAL` одинаково читаются как `0`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SentinelRendering {
+    /// Выводить `0`.
+    #[default]
+    Zero,
+    /// Выводить пустое значение.
+    Empty,
+    /// Выводить указанную строку (например, `EXTERNAL_USER_LITERAL`).
+    Literal(String),
+}
+
+impl SentinelRendering {
+    /// Возвращает текстовое представление идентификатора пользователя.
+    pub fn render(&self, user_id: u64) -> String {
+        match self {
+            _ if user_id != 0 => user_id.to_string(),
+            SentinelRendering::Zero => "0".to_string(),
+            SentinelRendering::Empty => String::new(),
+            SentinelRendering::Literal(literal) => literal.clone(),
+        }
+    }
+}
+
+/// Десериализует идентификатор пользователя, приводя обозначения внешнего контрагента к `0`.
+pub(crate) fn deserialize_user_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let value = String::deserialize(deserializer)?;
+
+    match value.trim() {
+        "" | EXTERNAL_USER_LITERAL => Ok(0),
+        other => other.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+/// Имя необязательного поля с кодом валюты.
+pub(crate) const CURRENCY_FIELD: &str = "CURRENCY";
+
+/// Проверяет, что `code` — три заглавные латинские буквы, как в кодах ISO 4217.
+pub(crate) fn is_currency_code(code: &str) -> bool {
+    code.len() == 3 && code.bytes().all(|byte| byte.is_ascii_uppercase())
+}
+
+/// Десериализует код валюты: пустое значение соответствует `None`, иначе код проверяется `is_currency_code`.
+pub(crate) fn deserialize_currency<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+
+    match value.trim() {
+        "" => Ok(None),
+        code if is_currency_code(code) => Ok(Some(code.to_owned())),
+        code => Err(serde::de::Error::custom(format!("invalid currency code {}: expected three uppercase letters", code))),
+    }
+}
+
+/// Значения по умолчанию, подставляемые вместо отсутствующих в записи полей.
+///
+/// Задаются при разборе, например, время модификации файла в качестве `timestamp`
+/// для источника, который не передает временные метки. Поля `None` остаются обязательными.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Defaults {
+    /// Тип операции.
+    pub transaction_type: Option<TransactionType>,
+    /// ID отправителя.
+    pub from_user_id: Option<u64>,
+    /// ID получателя.
+    pub to_user_id: Option<u64>,
+    /// Временная метка.
+    pub timestamp: Option<u64>,
+    /// Статус.
+    pub status: Option<TransactionStatus>,
+    /// Описание.
+    pub description: Option<String>,
+}
+
+impl Defaults {
+    /// Добавляет в набор пар «ключ — значение» заданные значения для отсутствующих ключей.
+    pub(crate) fn fill_missing(&self, fields: &mut std::collections::HashMap<String, String>) {
+        let defaults = [
+            ("TX_TYPE", self.transaction_type.map(|v| v.to_string())),
+            ("FROM_USER_ID", self.from_user_id.map(|v| v.to_string())),
+            ("TO_USER_ID", self.to_user_id.map(|v| v.to_string())),
+            ("TIMESTAMP", self.timestamp.map(|v| v.to_string())),
+            ("STATUS", self.status.map(|v| v.to_string())),
+            ("DESCRIPTION", self.description.clone()),
+        ];
+
+        for (key, value) in defaults {
+            if let Some(value) = value {
+                fields.entry(key.to_owned()).or_insert(value);
+            }
+        }
+    }
+}
+
+/// Имена полей записи в каноническом порядке (как в заголовке CSV).
+pub(crate) const FIELD_NAMES: [&str; 8] = ["TX_ID", "TX_TYPE", "FROM_USER_ID", "TO_USER_ID", "AMOUNT", "TIMESTAMP", "STATUS", "DESCRIPTION"];
+
+/// Числовые поля, для которых допускается шестнадцатеричная запись.
+pub(crate) const NUMERIC_FIELDS: [&str; 5] = ["TX_ID", "FROM_USER_ID", "TO_USER_ID", "AMOUNT", "TIMESTAMP"];
+
+/// Поля-перечисления, которые можно разбирать без учета регистра.
+pub(crate) const ENUM_FIELDS: [&str; 2] = ["TX_TYPE", "STATUS"];
+
+/// Проверяет, что значение числового поля `field` записано десятичными цифрами, но не помещается в тип поля.
+///
+/// Сумма хранится в `Transaction` как `i64`, поэтому для `AMOUNT` предел — `i64::MAX`.
+pub(crate) fn is_overflowing(field: &str, value: &str) -> bool {
+    let limit = match field {
+        "AMOUNT" => i64::MAX as u64,
+        _ => u64::MAX,
+    };
+    let value = value.trim();
+
+    !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit()) && value.parse::<u64>().map_or(true, |number| number > limit)
+}
+
+/// Преобразует значение вида `0x1F4` в десятичную запись; прочие значения возвращает без изменений.
+pub(crate) fn hex_to_decimal(value: &str) -> Result<std::borrow::Cow<'_, str>, String> {
+    let Some(digits) = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) else {
+        return Ok(value.into());
+    };
+
+    u64::from_str_radix(digits, 16)
+        .map(|number| number.to_string().into())
+        .map_err(|e| format!("Invalid hex number {}: {}", value, e))
+}
+
+/// 64-битный FNV-1a: не криптографический, но стабильный между версиями и платформами.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3))
+}
+
+/// Метка порядка байтов UTF-8, которую добавляют некоторые программы Windows.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Вид строки, прочитанной `LineSource`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LineKind {
+    /// Пустая строка или строка из одних пробелов.
+    Blank,
+    /// Комментарий: строка, начинающаяся с префикса комментария (по умолчанию `#`).
+    Comment,
+    /// Строка с данными.
+    Content,
+}
+
+/// Источник логических строк для построчных форматов.
+///
+/// Пропускает метку порядка байтов в начале, принимает переводы строк `\n`, `\r\n` и одиночный `\r`,
+/// обрезает пробелы, отличает комментарии от данных и считает строки. Длина строки ограничена:
+/// буфер не растет сверх `max_line_bytes`.
+pub(crate) struct LineSource<R> {
+    inner: R,
+    max_line_bytes: usize,
+    comment_prefix: char,
+    /// Число прочитанных строк.
+    line: usize,
+    /// Число байт, прочитанных с начала источника (включая метку порядка байтов).
+    offset: usize,
+    /// Проверено ли начало источника на метку порядка байтов.
+    bom_checked: bool,
+    buf: Vec<u8>,
+    /// Последняя прочитанная строка без окружающих пробелов.
+    current: String,
+    kind: LineKind,
+}
+
+impl<R: BufRead> LineSource<R> {
+    /// Создает источник строк длиной не более `max_line_bytes` байт.
+    pub(crate) fn new(inner: R, max_line_bytes: usize) -> Self {
+        Self {
+            inner,
+            max_line_bytes,
+            comment_prefix: '#',
+            line: 0,
+            offset: 0,
+            bom_checked: false,
+            buf: Vec::with_capacity(128),
+            current: String::new(),
+            kind: LineKind::Blank,
+        }
+    }
+
+    /// Задает ограничение длины строки в байтах.
+    pub(crate) fn set_max_line_bytes(&mut self, max_line_bytes: usize) {
+        self.max_line_bytes = max_line_bytes;
+    }
+
+    /// Задает символ, с которого начинаются строки-комментарии.
+    pub(crate) fn set_comment_prefix(&mut self, comment_prefix: char) {
+        self.comment_prefix = comment_prefix;
+    }
+
+    /// Читает следующую строку; `None` — данных больше нет.
+    ///
+    /// Содержимое строки доступно через `text` и `line`. Строка не в UTF-8 дает ошибку
+    /// `LineError::InvalidUtf8` со смещением первого неверного байта от начала источника.
+    pub(crate) fn next_line(&mut self) -> Result<Option<LineKind>, LineError> {
+        self.buf.clear();
+        let line_start = self.offset;
+        if self.read_raw_line()? == 0 {
+            return Ok(None);
+        }
+
+        let line = std::str::from_utf8(&self.buf)
+            .map_err(|e| LineError::InvalidUtf8 { byte_offset: line_start + e.valid_up_to() })?;
+        let trimmed = line.trim();
+
+        self.kind = match trimmed {
+            "" => LineKind::Blank,
+            _ if trimmed.starts_with(self.comment_prefix) => LineKind::Comment,
+            _ => LineKind::Content,
+        };
+        self.current.clear();
+        self.current.push_str(trimmed);
+
+        Ok(Some(self.kind))
+    }
+
+    /// Последняя прочитанная строка без окружающих пробелов; у комментария — целиком, вместе с префиксом.
+    pub(crate) fn line(&self) -> &str {
+        &self.current
+    }
+
+    /// Текст последней строки: у комментария — без префикса и пробелов после него.
+    pub(crate) fn text(&self) -> &str {
+        match self.kind {
+            LineKind::Comment => self.current[self.comment_prefix.len_utf8()..].trim_start(),
+            LineKind::Blank | LineKind::Content => &self.current,
+        }
+    }
+
+    /// Исходные байты последней прочитанной строки вместе с переводом строки.
+    pub(crate) fn raw(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Номер последней прочитанной строки (с единицы).
+    pub(crate) fn line_number(&self) -> usize {
+        self.line
+    }
+
+    /// Первый непрочитанный байт после метки порядка байтов; `None` — данных больше нет.
+    pub(crate) fn peek_byte(&mut self) -> std::io::Result<Option<u8>> {
+        self.skip_bom()?;
+        Ok(self.inner.fill_buf()?.first().copied())
+    }
+
+    /// Сбрасывает счетчик строк и проверку метки порядка байтов, например после перемотки источника.
+    pub(crate) fn reset(&mut self) {
+        self.line = 0;
+        self.offset = 0;
+        self.bom_checked = false;
+    }
+
+    /// Исходный читатель.
+    pub(crate) fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    fn skip_bom(&mut self) -> std::io::Result<()> {
+        if !self.bom_checked {
+            self.bom_checked = true;
+            if self.inner.fill_buf()?.starts_with(UTF8_BOM) {
+                self.inner.consume(UTF8_BOM.len());
+                self.offset += UTF8_BOM.len();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Читает строку вместе с завершающим переводом строки в `buf`, не допуская её роста сверх лимита.
+    fn read_raw_line(&mut self) -> Result<usize, LineError> {
+        self.skip_bom()?;
+        let mut total = 0;
+
+        loop {
+            let available = self.inner.fill_buf()?;
+            if available.is_empty() {
+                if total > 0 {
+                    self.line += 1;
+                }
+
+                return Ok(total);
+            }
+
+            let (chunk_len, line_ended) = match available.iter().position(|&b| b == b'\n' || b == b'\r') {
+                Some(pos) => (pos + 1, true),
+                None => (available.len(), false),
+            };
+
+            if self.buf.len() + chunk_len > self.max_line_bytes {
+                return Err(LineError::TooLong { bytes: self.buf.len() + chunk_len });
+            }
+
+            self.buf.extend_from_slice(&available[..chunk_len]);
+            self.inner.consume(chunk_len);
+            self.offset += chunk_len;
+            total += chunk_len;
+
+            if line_ended {
+                // `\r\n` — один перевод строки, даже если `\n` попал в следующую порцию буфера
+                if self.buf.last() == Some(&b'\r') && self.inner.fill_buf()?.first() == Some(&b'\n') {
+                    self.buf.push(b'\n');
+                    self.inner.consume(1);
+                    self.offset += 1;
+                    total += 1;
+                }
+
+                self.line += 1;
+                return Ok(total);
+            }
+        }
+    }
+}
+
 /// Основная структура, представляющая банковскую транзакцию.
 ///
 /// Содержит всю необходимую информацию о переводе или операции со счетом.
@@ -141,5 +487,716 @@ pub struct Transaction {
     /// Текущий статус транзакции.
     pub transaction_status: TransactionStatus,
     /// Текстовое описание или примечание к транзакции.
+    ///
+    /// Хранится без обрамляющих кавычек; каждый формат сам добавляет и снимает их при записи и чтении.
     pub description: String,
-}
\ No newline at end of file
+    /// Код валюты ISO 4217 (например, `USD`), если источник его указывает.
+    ///
+    /// Необязательное поле `CURRENCY`: старые файлы без него читаются с `None`, а при записи
+    /// оно выводится только для заданного кода. Бинарный формат валюту не хранит.
+    pub currency: Option<String>,
+}
+impl Transaction {
+    /// Описание, если оно задано: пустое описание соответствует `None`.
+    ///
+    /// При записи `None` выводится пустым полем. Чтобы описание из одних пробелов тоже считалось
+    /// отсутствующим, включите `blank_description_as_none` у читателя CSV или TXT.
+    pub fn description_opt(&self) -> Option<&str> {
+        Some(self.description.as_str()).filter(|description| !description.is_empty())
+    }
+
+    /// Ключ хронологической сортировки: метка времени, затем `id`.
+    ///
+    /// Используется вместо `Ord`, чтобы не вводить произвольный порядок на типах и статусах:
+    /// `records.sort_by_key(Transaction::sort_key)`.
+    pub fn sort_key(&self) -> (u64, u64) {
+        (self.timestamp, self.id)
+    }
+
+    /// Хеш содержимого транзакции, не зависящий от формата, из которого она прочитана.
+    ///
+    /// Поля хешируются в фиксированном порядке (FNV-1a); строковые поля — с префиксом длины,
+    /// а валюта — еще и с признаком наличия, поэтому разные значения полей не дают одинаковых байт.
+    /// Описание берется уже разобранным —
+    /// без кавычек и экранирования — и без пробелов по краям, поэтому одна и та же транзакция
+    /// из CSV и TXT дает одинаковый хеш. Хеш не криптографический: для обнаружения
+    /// злонамеренной подмены используйте цепочку `serialize_with_chain`.
+    pub fn content_hash(&self) -> u64 {
+        let mut bytes = Vec::with_capacity(64 + self.description.len());
+        for number in [self.id, self.from_user_id, self.to_user_id, self.amount as u64, self.timestamp] {
+            bytes.extend_from_slice(&number.to_le_bytes());
+        }
+        bytes.push(self.transaction_type as u8);
+        bytes.push(self.transaction_status as u8);
+        let description = self.description.trim();
+        bytes.extend_from_slice(&(description.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(description.as_bytes());
+        match &self.currency {
+            Some(currency) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&(currency.len() as u64).to_le_bytes());
+                bytes.extend_from_slice(currency.as_bytes());
+            }
+            None => bytes.push(0),
+        }
+
+        fnv1a(&bytes)
+    }
+
+    /// Сравнивает транзакции, допуская расхождение временных меток не более чем на `ts_tolerance_ms`.
+    ///
+    /// Все остальные поля должны совпадать точно. При нулевом допуске эквивалентно `==`.
+    pub fn approx_eq(&self, other: &Transaction, ts_tolerance_ms: u64) -> bool {
+        self.timestamp.abs_diff(other.timestamp) <= ts_tolerance_ms
+            && self.id == other.id
+            && self.transaction_type == other.transaction_type
+            && self.from_user_id == other.from_user_id
+            && self.to_user_id == other.to_user_id
+            && self.amount == other.amount
+            && self.transaction_status == other.transaction_status
+            && self.description == other.description
+            && self.currency == other.currency
+    }
+
+    /// Управляющие символы, допустимые в описании по умолчанию.
+    pub const DEFAULT_ALLOWED_CONTROL_CHARS: [char; 2] = ['\n', '\t'];
+
+    /// Проверяет, что описание не содержит управляющих ASCII-символов, кроме перечисленных в `allowed`.
+    ///
+    /// Для стандартного набора исключений используйте `Transaction::DEFAULT_ALLOWED_CONTROL_CHARS`.
+    pub fn description_is_printable(&self, allowed: &[char]) -> Result<(), NonPrintableDescription> {
+        let has_forbidden = self
+            .description
+            .chars()
+            .any(|c| c.is_ascii_control() && !allowed.contains(&c));
+
+        if has_forbidden {
+            return Err(NonPrintableDescription { id: self.id });
+        }
+
+        Ok(())
+    }
+
+    /// Форматирует сумму, хранящуюся в минимальных единицах валюты, как десятичное число.
+    ///
+    /// `minor_units` — число знаков после запятой (например, `2` для копеек: `50000` → `500.00`).
+    /// Если задан `thousands_separator`, он разделяет группы по три цифры в целой части.
+    /// Форматирование выполняется над цифрами числа без арифметики с плавающей точкой.
+    pub fn formatted_amount(&self, minor_units: u32, thousands_separator: Option<char>) -> String {
+        let digits = self.amount.unsigned_abs().to_string();
+        let minor_units = minor_units as usize;
+        let digits = format!("{:0>width$}", digits, width = minor_units + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - minor_units);
+
+        let mut formatted = String::with_capacity(digits.len() + digits.len() / 3 + 2);
+        if self.amount < 0 {
+            formatted.push('-');
+        }
+
+        for (i, digit) in integer.chars().enumerate() {
+            if let Some(separator) = thousands_separator
+                && i > 0
+                && (integer.len() - i) % 3 == 0
+            {
+                formatted.push(separator);
+            }
+            formatted.push(digit);
+        }
+
+        if !fraction.is_empty() {
+            formatted.push('.');
+            formatted.push_str(fraction);
+        }
+
+        formatted
+    }
+
+    /// Проверяет бизнес-правила транзакции со всеми включенными правилами.
+    ///
+    /// У `DEPOSIT` отправитель должен быть `0`, у `WITHDRAWAL` — получатель `0`;
+    /// у `TRANSFER` оба участника ненулевые и различны. Сумма любой транзакции положительна.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_with(&ValidationConfig::default())
+    }
+
+    /// Проверяет бизнес-правила транзакции, пропуская отключенные в `config`.
+    pub fn validate_with(&self, config: &ValidationConfig) -> Result<(), ValidationError> {
+        let id = self.id;
+
+        match self.transaction_type {
+            TransactionType::Deposit if config.deposit_sender && self.from_user_id != 0 => {
+                return Err(ValidationError::UnexpectedSender { id, from_user_id: self.from_user_id });
+            }
+            TransactionType::Withdrawal if config.withdrawal_receiver && self.to_user_id != 0 => {
+                return Err(ValidationError::UnexpectedReceiver { id, to_user_id: self.to_user_id });
+            }
+            TransactionType::Transfer if config.transfer_parties && (self.from_user_id == 0 || self.to_user_id == 0) => {
+                return Err(ValidationError::MissingTransferParty { id });
+            }
+            TransactionType::Transfer if config.no_self_transfer && self.from_user_id == self.to_user_id => {
+                return Err(ValidationError::SelfTransfer { id, user_id: self.from_user_id });
+            }
+            _ => {}
+        }
+
+        if config.positive_amount && self.amount <= 0 {
+            return Err(ValidationError::NonPositiveAmount { id, amount: self.amount });
+        }
+
+        Ok(())
+    }
+}
+
+/// Набор правил, проверяемых `Transaction::validate_with`.
+///
+/// По умолчанию включены все правила; отключение позволяет принимать данные систем
+/// с более мягкими соглашениями.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationConfig {
+    /// У `DEPOSIT` отправитель — внешний контрагент `0` (`ValidationError::UnexpectedSender`).
+    pub deposit_sender: bool,
+    /// У `WITHDRAWAL` получатель — внешний контрагент `0` (`ValidationError::UnexpectedReceiver`).
+    pub withdrawal_receiver: bool,
+    /// У `TRANSFER` оба участника ненулевые (`ValidationError::MissingTransferParty`).
+    pub transfer_parties: bool,
+    /// У `TRANSFER` отправитель и получатель различны (`ValidationError::SelfTransfer`).
+    pub no_self_transfer: bool,
+    /// Сумма положительна (`ValidationError::NonPositiveAmount`).
+    pub positive_amount: bool,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            deposit_sender: true,
+            withdrawal_receiver: true,
+            transfer_parties: true,
+            no_self_transfer: true,
+            positive_amount: true,
+        }
+    }
+}
+
+/// Построитель транзакции с именованными полями.
+///
+/// По умолчанию участники и сумма равны `0`, метка времени — `0`, статус — `PENDING`,
+/// описание пустое. `build` проверяет транзакцию через `Transaction::validate`.
+#[derive(Debug, Clone)]
+pub struct TransactionBuilder {
+    transaction: Transaction,
+}
+
+impl TransactionBuilder {
+    /// Начинает построение транзакции с идентификатором `id` и типом `transaction_type`.
+    pub fn new(id: u64, transaction_type: TransactionType) -> Self {
+        Self {
+            transaction: Transaction {
+                id,
+                transaction_type,
+                from_user_id: 0,
+                to_user_id: 0,
+                amount: 0,
+                timestamp: 0,
+                transaction_status: TransactionStatus::Pending,
+                description: String::new(),
+                currency: None,
+            },
+        }
+    }
+
+    /// ID пользователя, отправляющего средства.
+    pub fn from_user_id(mut self, from_user_id: u64) -> Self {
+        self.transaction.from_user_id = from_user_id;
+        self
+    }
+
+    /// ID пользователя, получающего средства.
+    pub fn to_user_id(mut self, to_user_id: u64) -> Self {
+        self.transaction.to_user_id = to_user_id;
+        self
+    }
+
+    /// Сумма операции.
+    pub fn amount(mut self, amount: i64) -> Self {
+        self.transaction.amount = amount;
+        self
+    }
+
+    /// Временная метка операции.
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.transaction.timestamp = timestamp;
+        self
+    }
+
+    /// Статус транзакции.
+    pub fn status(mut self, status: TransactionStatus) -> Self {
+        self.transaction.transaction_status = status;
+        self
+    }
+
+    /// Описание транзакции.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.transaction.description = description.into();
+        self
+    }
+
+    /// Код валюты ISO 4217.
+    pub fn currency(mut self, currency: impl Into<String>) -> Self {
+        self.transaction.currency = Some(currency.into());
+        self
+    }
+
+    /// Проверяет бизнес-правила и возвращает транзакцию.
+    pub fn build(self) -> Result<Transaction, ValidationError> {
+        self.transaction.validate()?;
+        Ok(self.transaction)
+    }
+
+    /// Возвращает транзакцию без проверки (для тестовых данных, в том числе заведомо неверных).
+    #[cfg(test)]
+    pub(crate) fn build_unchecked(self) -> Transaction {
+        self.transaction
+    }
+}
+
+/// Транзакция для тестов: пополнение пользователя `10` на `100` со статусом `SUCCESS`,
+/// меткой `1000 + id` и описанием `Desc {id}`.
+///
+/// Остальные значения задаются обновлением структуры: `Transaction { amount: 5, ..test_transaction(1) }`.
+#[cfg(test)]
+pub(crate) fn test_transaction(id: u64) -> Transaction {
+    TransactionBuilder::new(id, TransactionType::Deposit)
+        .to_user_id(10)
+        .amount(100)
+        .timestamp(1000 + id)
+        .status(TransactionStatus::Success)
+        .description(format!("Desc {}", id))
+        .build_unchecked()
+}
+
+#[cfg(feature = "chrono")]
+impl Transaction {
+    /// Возвращает временную метку как дату и время в UTC.
+    ///
+    /// Ошибка, если число миллисекунд не представимо в `chrono::DateTime`.
+    pub fn timestamp_datetime(&self) -> Result<chrono::DateTime<chrono::Utc>, TimestampOutOfRange> {
+        i64::try_from(self.timestamp)
+            .ok()
+            .and_then(chrono::DateTime::from_timestamp_millis)
+            .ok_or(TimestampOutOfRange { millis: self.timestamp.into() })
+    }
+
+    /// Задает временную метку по дате и времени в UTC.
+    ///
+    /// Ошибка для дат до эпохи Unix: они не представимы в `u64`.
+    pub fn with_timestamp_datetime(mut self, datetime: chrono::DateTime<chrono::Utc>) -> Result<Self, TimestampOutOfRange> {
+        let millis = datetime.timestamp_millis();
+        self.timestamp = u64::try_from(millis).map_err(|_| TimestampOutOfRange { millis: millis.into() })?;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufReader, Cursor};
+
+    fn read_lines(input: &[u8], capacity: usize) -> Vec<(usize, LineKind, String)> {
+        let mut source = LineSource::new(BufReader::with_capacity(capacity, Cursor::new(input.to_vec())), 1024);
+        let mut lines = Vec::new();
+        while let Some(kind) = source.next_line().unwrap() {
+            lines.push((source.line_number(), kind, source.text().to_owned()));
+        }
+        lines
+    }
+
+    #[test]
+    fn line_source_classifies_and_numbers_lines() {
+        let lines = read_lines(b"\xEF\xBB\xBF# header\n  KEY: value  \n\n   \n#no space\nlast", 64);
+
+        assert_eq!(
+            lines,
+            vec![
+                (1, LineKind::Comment, "header".to_owned()),
+                (2, LineKind::Content, "KEY: value".to_owned()),
+                (3, LineKind::Blank, String::new()),
+                (4, LineKind::Blank, String::new()),
+                (5, LineKind::Comment, "no space".to_owned()),
+                (6, LineKind::Content, "last".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn line_source_accepts_crlf_and_lone_cr() {
+        let expected = read_lines(b"A: 1\n\nB: 2\n", 64);
+
+        for capacity in [64, 1, 5] {
+            assert_eq!(read_lines(b"A: 1\r\n\r\nB: 2\r\n", capacity), expected);
+            assert_eq!(read_lines(b"A: 1\r\rB: 2\r", capacity), expected);
+        }
+        assert_eq!(expected.len(), 3);
+    }
+
+    #[test]
+    fn line_source_limits_line_length() {
+        let mut source = LineSource::new(Cursor::new(b"short\nmuch longer line\n".to_vec()), 8);
+
+        assert_eq!(source.next_line().unwrap(), Some(LineKind::Content));
+        assert!(matches!(source.next_line(), Err(LineError::TooLong { .. })));
+    }
+
+    #[test]
+    fn line_source_reports_offset_of_invalid_utf8() {
+        let mut source = LineSource::new(Cursor::new(b"\xEF\xBB\xBFfirst\r\nsecond \xC3\x28\n".to_vec()), 64);
+
+        assert_eq!(source.next_line().unwrap(), Some(LineKind::Content));
+        assert!(matches!(source.next_line(), Err(LineError::InvalidUtf8 { byte_offset: 17 })));
+    }
+
+    #[test]
+    fn line_source_peek_skips_bom() {
+        let mut source = LineSource::new(Cursor::new(b"\xEF\xBB\xBF#!x\n".to_vec()), 64);
+
+        assert_eq!(source.peek_byte().unwrap(), Some(b'#'));
+        assert_eq!(source.next_line().unwrap(), Some(LineKind::Comment));
+        assert_eq!(source.line(), "#!x");
+        assert_eq!(source.next_line().unwrap(), None);
+        assert_eq!(source.peek_byte().unwrap(), None);
+    }
+
+    fn sample_transaction() -> Transaction {
+        Transaction { transaction_type: TransactionType::Transfer, from_user_id: 10, to_user_id: 20, ..test_transaction(1) }
+    }
+
+    #[test]
+    fn approx_eq_accepts_timestamp_within_tolerance() {
+        let left = sample_transaction();
+        let mut right = sample_transaction();
+        right.timestamp -= 1000;
+
+        assert!(left.approx_eq(&right, 1000));
+        assert!(right.approx_eq(&left, 1000));
+        assert_ne!(left, right);
+    }
+
+    #[test]
+    fn approx_eq_rejects_timestamp_beyond_tolerance() {
+        let left = sample_transaction();
+        let mut right = sample_transaction();
+        right.timestamp += 1001;
+
+        assert!(!left.approx_eq(&right, 1000));
+    }
+
+    #[test]
+    fn approx_eq_requires_other_fields_to_match() {
+        let left = sample_transaction();
+        let mut right = sample_transaction();
+        right.amount += 1;
+
+        assert!(!left.approx_eq(&right, 1000));
+        assert!(left.approx_eq(&sample_transaction(), 0));
+    }
+
+    #[test]
+    fn description_with_bell_is_not_printable() {
+        let mut transaction = sample_transaction();
+        transaction.description = "Ring\x07bell".to_string();
+
+        assert_eq!(
+            transaction.description_is_printable(&Transaction::DEFAULT_ALLOWED_CONTROL_CHARS),
+            Err(NonPrintableDescription { id: 1 })
+        );
+    }
+
+    #[test]
+    fn allowed_control_chars_are_configurable() {
+        let mut transaction = sample_transaction();
+        transaction.description = "Line one\nline\ttwo".to_string();
+
+        assert!(transaction.description_is_printable(&Transaction::DEFAULT_ALLOWED_CONTROL_CHARS).is_ok());
+        assert!(transaction.description_is_printable(&['\t']).is_err());
+    }
+
+    #[test]
+    fn transaction_type_codes_round_trip() {
+        for (code, transaction_type) in [(0, TransactionType::Deposit), (1, TransactionType::Transfer), (2, TransactionType::Withdrawal)] {
+            assert_eq!(TransactionType::try_from(code), Ok(transaction_type));
+            assert_eq!(transaction_type.as_u8(), code);
+        }
+    }
+
+    #[test]
+    fn transaction_status_codes_round_trip() {
+        for (code, status) in [(0, TransactionStatus::Success), (1, TransactionStatus::Failure), (2, TransactionStatus::Pending)] {
+            assert_eq!(TransactionStatus::try_from(code), Ok(status));
+            assert_eq!(status.as_u8(), code);
+        }
+    }
+
+    #[test]
+    fn invalid_codes_are_reported() {
+        for code in [3, 5, u8::MAX] {
+            let error = TransactionType::try_from(code).unwrap_err();
+            assert_eq!(error.to_string(), format!("Invalid transaction type value: {}", code));
+
+            let error = TransactionStatus::try_from(code).unwrap_err();
+            assert_eq!(error.to_string(), format!("Invalid transaction status value: {}", code));
+        }
+    }
+
+    #[test]
+    fn unknown_enum_string_names_expected_values() {
+        let error = "FOO".parse::<TransactionType>().unwrap_err();
+        assert_eq!(error.input, "FOO");
+        assert_eq!(error.to_string(), "invalid TransactionType \"FOO\", expected one of DEPOSIT, TRANSFER, WITHDRAWAL");
+
+        let error = "DONE".parse::<TransactionStatus>().unwrap_err();
+        assert_eq!(error.to_string(), "invalid TransactionStatus \"DONE\", expected one of PENDING, SUCCESS, FAILURE");
+
+        assert_eq!("SUCCESS".parse(), Ok(TransactionStatus::Success));
+    }
+
+    #[test]
+    fn valid_transactions_pass_validation() {
+        let deposit = Transaction { transaction_type: TransactionType::Deposit, from_user_id: 0, ..sample_transaction() };
+        let withdrawal = Transaction { transaction_type: TransactionType::Withdrawal, to_user_id: 0, ..sample_transaction() };
+
+        assert_eq!(sample_transaction().validate(), Ok(()));
+        assert_eq!(deposit.validate(), Ok(()));
+        assert_eq!(withdrawal.validate(), Ok(()));
+    }
+
+    #[test]
+    fn each_validation_rule_is_reported() {
+        let cases = [
+            (
+                Transaction { transaction_type: TransactionType::Deposit, from_user_id: 5, ..sample_transaction() },
+                ValidationError::UnexpectedSender { id: 1, from_user_id: 5 },
+            ),
+            (
+                Transaction { transaction_type: TransactionType::Withdrawal, to_user_id: 7, ..sample_transaction() },
+                ValidationError::UnexpectedReceiver { id: 1, to_user_id: 7 },
+            ),
+            (
+                Transaction { to_user_id: 0, ..sample_transaction() },
+                ValidationError::MissingTransferParty { id: 1 },
+            ),
+            (
+                Transaction { to_user_id: 10, ..sample_transaction() },
+                ValidationError::SelfTransfer { id: 1, user_id: 10 },
+            ),
+            (
+                Transaction { amount: 0, ..sample_transaction() },
+                ValidationError::NonPositiveAmount { id: 1, amount: 0 },
+            ),
+        ];
+
+        for (transaction, expected) in cases {
+            assert_eq!(transaction.validate(), Err(expected));
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn timestamp_datetime_at_epoch_zero() {
+        let transaction = Transaction { timestamp: 0, ..sample_transaction() };
+        let datetime = transaction.timestamp_datetime().unwrap();
+
+        assert_eq!(datetime, chrono::DateTime::UNIX_EPOCH);
+        assert_eq!(transaction.clone().with_timestamp_datetime(datetime).unwrap(), transaction);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn timestamp_datetime_round_trips_far_future() {
+        let max_millis = chrono::DateTime::<chrono::Utc>::MAX_UTC.timestamp_millis() as u64;
+        let transaction = Transaction { timestamp: max_millis, ..sample_transaction() };
+        let datetime = transaction.timestamp_datetime().unwrap();
+
+        assert_eq!(transaction.clone().with_timestamp_datetime(datetime).unwrap(), transaction);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn timestamp_beyond_chrono_range_is_an_error() {
+        let max_millis = chrono::DateTime::<chrono::Utc>::MAX_UTC.timestamp_millis() as u64;
+
+        for timestamp in [max_millis + 1, u64::MAX] {
+            let transaction = Transaction { timestamp, ..sample_transaction() };
+            assert_eq!(
+                transaction.timestamp_datetime(),
+                Err(TimestampOutOfRange { millis: timestamp.into() })
+            );
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn datetime_before_epoch_is_an_error() {
+        let datetime = chrono::DateTime::from_timestamp_millis(-1).unwrap();
+
+        assert_eq!(
+            sample_transaction().with_timestamp_datetime(datetime),
+            Err(TimestampOutOfRange { millis: -1 })
+        );
+    }
+
+    #[test]
+    fn enums_round_trip_through_serde_json() {
+        for transaction_type in [TransactionType::Deposit, TransactionType::Transfer, TransactionType::Withdrawal] {
+            let json = serde_json::to_string(&transaction_type).unwrap();
+            assert_eq!(json, format!("\"{}\"", transaction_type));
+            assert_eq!(serde_json::from_str::<TransactionType>(&json).unwrap(), transaction_type);
+        }
+
+        for status in [TransactionStatus::Pending, TransactionStatus::Success, TransactionStatus::Failure] {
+            let json = serde_json::to_string(&status).unwrap();
+            assert_eq!(json, format!("\"{}\"", status));
+            assert_eq!(serde_json::from_str::<TransactionStatus>(&json).unwrap(), status);
+        }
+    }
+
+    #[test]
+    fn builder_builds_valid_transaction_with_defaults() {
+        let transaction = TransactionBuilder::new(1, TransactionType::Transfer)
+            .from_user_id(10)
+            .to_user_id(20)
+            .amount(100)
+            .timestamp(1_700_000_000_000)
+            .build()
+            .unwrap();
+
+        assert_eq!(transaction.transaction_status, TransactionStatus::Pending);
+        assert_eq!(transaction.description, "");
+        assert_eq!(
+            transaction,
+            Transaction {
+                transaction_status: TransactionStatus::Pending,
+                description: String::new(),
+                timestamp: 1_700_000_000_000,
+                ..sample_transaction()
+            }
+        );
+    }
+
+    #[test]
+    fn builder_rejects_self_transfer() {
+        let result = TransactionBuilder::new(1, TransactionType::Transfer)
+            .from_user_id(10)
+            .to_user_id(10)
+            .amount(500)
+            .status(TransactionStatus::Success)
+            .description("Self")
+            .build();
+
+        assert_eq!(result, Err(ValidationError::SelfTransfer { id: 1, user_id: 10 }));
+    }
+
+    #[test]
+    fn sort_key_orders_by_timestamp_then_id() {
+        let at = |id: u64, timestamp: u64| Transaction { id, timestamp, ..sample_transaction() };
+        let mut records = [at(4, 300), at(2, 100), at(5, 200), at(1, 100), at(3, 200)];
+
+        records.sort_by_key(Transaction::sort_key);
+
+        assert_eq!(records.iter().map(|t| t.id).collect::<Vec<_>>(), vec![1, 2, 3, 5, 4]);
+    }
+
+    #[test]
+    fn content_hash_matches_across_csv_and_txt() {
+        use crate::{Parser, YPBankCsvRecord, YPBankTextRecord};
+
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+7, TRANSFER ,10,20,500,1000,SUCCESS,\"Rent, \"\"May\"\"\"
+";
+        let txt_data = "\
+TX_ID: 7
+TX_TYPE: TRANSFER
+FROM_USER_ID: 10
+TO_USER_ID: 20
+AMOUNT: 500
+TIMESTAMP: 1000
+STATUS: SUCCESS
+DESCRIPTION: \"Rent, \"May\"  \"
+";
+        let from_csv: Transaction = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data)).next().unwrap().unwrap().into();
+        let from_txt: Transaction = Parser::<YPBankTextRecord, _>::new(Cursor::new(txt_data)).next().unwrap().unwrap().into();
+
+        assert_eq!(from_csv.content_hash(), from_txt.content_hash());
+        assert_ne!(from_csv.content_hash(), Transaction { amount: 501, ..from_csv.clone() }.content_hash());
+        assert_ne!(from_csv.content_hash(), Transaction { description: "Rent".to_string(), ..from_csv }.content_hash());
+    }
+
+    #[test]
+    fn content_hash_separates_description_from_currency() {
+        let base = Transaction { description: "Rent".to_string(), ..sample_transaction() };
+        let with_currency = Transaction { currency: Some("USD".to_string()), ..base.clone() };
+        let currency_in_description = Transaction { description: "Rent\0USD".to_string(), ..base.clone() };
+
+        assert_ne!(base.content_hash(), with_currency.content_hash());
+        assert_ne!(with_currency.content_hash(), currency_in_description.content_hash());
+    }
+
+    #[test]
+    fn self_transfer_rule_can_be_disabled() {
+        let transaction = Transaction { to_user_id: 10, ..sample_transaction() };
+        let config = ValidationConfig { no_self_transfer: false, ..ValidationConfig::default() };
+
+        assert_eq!(transaction.validate(), Err(ValidationError::SelfTransfer { id: 1, user_id: 10 }));
+        assert_eq!(transaction.validate_with(&config), Ok(()));
+        // прочие правила остаются включенными
+        let zero_amount = Transaction { amount: 0, ..transaction };
+        assert_eq!(zero_amount.validate_with(&config), Err(ValidationError::NonPositiveAmount { id: 1, amount: 0 }));
+    }
+
+    #[test]
+    fn sentinel_party_rules_can_be_disabled_individually() {
+        let deposit = Transaction { transaction_type: TransactionType::Deposit, from_user_id: 5, ..sample_transaction() };
+        let withdrawal = Transaction { transaction_type: TransactionType::Withdrawal, to_user_id: 7, ..sample_transaction() };
+        let lenient_deposits = ValidationConfig { deposit_sender: false, ..ValidationConfig::default() };
+        let lenient_withdrawals = ValidationConfig { withdrawal_receiver: false, ..ValidationConfig::default() };
+
+        assert_eq!(deposit.validate_with(&lenient_deposits), Ok(()));
+        assert_eq!(withdrawal.validate_with(&lenient_deposits), Err(ValidationError::UnexpectedReceiver { id: 1, to_user_id: 7 }));
+        assert_eq!(withdrawal.validate_with(&lenient_withdrawals), Ok(()));
+        assert_eq!(deposit.validate_with(&lenient_withdrawals), Err(ValidationError::UnexpectedSender { id: 1, from_user_id: 5 }));
+    }
+
+    #[test]
+    fn formatted_amount_handles_zero_and_exact_division() {
+        let with_amount = |amount| Transaction { amount, ..sample_transaction() };
+
+        assert_eq!(with_amount(0).formatted_amount(2, None), "0.00");
+        assert_eq!(with_amount(50000).formatted_amount(2, None), "500.00");
+        assert_eq!(with_amount(50000).formatted_amount(0, None), "50000");
+    }
+
+    #[test]
+    fn formatted_amount_handles_non_exact_division() {
+        let with_amount = |amount| Transaction { amount, ..sample_transaction() };
+
+        assert_eq!(with_amount(5).formatted_amount(2, None), "0.05");
+        assert_eq!(with_amount(123456).formatted_amount(3, None), "123.456");
+        assert_eq!(with_amount(-1050).formatted_amount(2, None), "-10.50");
+    }
+
+    #[test]
+    fn formatted_amount_groups_thousands() {
+        let transaction = Transaction { amount: 123456789, ..sample_transaction() };
+
+        assert_eq!(transaction.formatted_amount(2, Some(',')), "1,234,567.89");
+        assert_eq!(transaction.formatted_amount(6, Some(' ')), "123.456789");
+    }
+
+    #[test]
+    fn formatted_amount_handles_extreme_values() {
+        let with_amount = |amount| Transaction { amount, ..sample_transaction() };
+
+        assert_eq!(with_amount(i64::MAX).formatted_amount(2, Some(',')), "92,233,720,368,547,758.07");
+        assert_eq!(with_amount(i64::MIN).formatted_amount(2, None), "-92233720368547758.08");
+        assert_eq!(with_amount(1).formatted_amount(20, None), "0.00000000000000000001");
+    }
+}
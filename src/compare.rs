@@ -0,0 +1,204 @@
+use crate::common::Transaction;
+use std::collections::BTreeMap;
+
+/// Пара транзакций с одинаковым `id` и различающимися полями.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    /// Транзакция из левого набора.
+    pub left: Transaction,
+    /// Транзакция из правого набора.
+    pub right: Transaction,
+    /// Различающиеся поля в порядке полей записи.
+    pub fields: Vec<FieldDiff>,
+}
+
+/// Различие одного поля в паре сверяемых транзакций.
+///
+/// Значения приводятся в исходном виде, даже если поле сравнивалось после нормализации
+/// (описание без учета пробелов); отсутствующая валюта выводится пустой строкой.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    /// Имя поля, как в заголовке CSV.
+    pub field: &'static str,
+    /// Значение в левом наборе.
+    pub left: String,
+    /// Значение в правом наборе.
+    pub right: String,
+}
+
+/// Результат сверки двух наборов транзакций, сопоставленных по `id`.
+#[derive(Debug, Default, PartialEq)]
+pub struct TransactionDiff {
+    /// Транзакции, присутствующие только в левом наборе.
+    pub only_in_left: Vec<Transaction>,
+    /// Транзакции, присутствующие только в правом наборе.
+    pub only_in_right: Vec<Transaction>,
+    /// Транзакции, присутствующие в обоих наборах, но различающиеся.
+    pub mismatched: Vec<Mismatch>,
+}
+
+impl TransactionDiff {
+    /// Возвращает `true`, если наборы совпадают.
+    pub fn is_empty(&self) -> bool {
+        self.only_in_left.is_empty() && self.only_in_right.is_empty() && self.mismatched.is_empty()
+    }
+}
+
+/// Настройки сверки для `diff_with`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Описания должны совпадать побайтно; по умолчанию различия в пробельных символах не учитываются.
+    pub strict_description: bool,
+    /// Допустимое расхождение временных меток (мс), при котором поле `TIMESTAMP` считается совпадающим.
+    pub timestamp_tolerance_ms: u64,
+}
+
+impl DiffOptions {
+    /// Точное сравнение всех полей.
+    pub fn strict() -> Self {
+        Self { strict_description: true, timestamp_tolerance_ms: 0 }
+    }
+}
+
+/// Сверяет два набора транзакций по `id` с настройками по умолчанию.
+///
+/// Описания сравниваются без учета различий в пробельных символах. Результат упорядочен по `id`;
+/// при повторяющихся `id` внутри набора учитывается последняя запись.
+pub fn diff(left: &[Transaction], right: &[Transaction]) -> TransactionDiff {
+    diff_with(left, right, DiffOptions::default())
+}
+
+/// Сверяет два набора транзакций по `id` с настройками `options`.
+///
+/// Единственная реализация сверки в библиотеке: на ней построены `DiffReport` и утилита `comparer`.
+pub fn diff_with(left: &[Transaction], right: &[Transaction], options: DiffOptions) -> TransactionDiff {
+    let left_by_id: BTreeMap<u64, &Transaction> = left.iter().map(|t| (t.id, t)).collect();
+    let right_by_id: BTreeMap<u64, &Transaction> = right.iter().map(|t| (t.id, t)).collect();
+
+    let mut result = TransactionDiff::default();
+
+    for (id, left_transaction) in &left_by_id {
+        let Some(right_transaction) = right_by_id.get(id) else {
+            result.only_in_left.push((*left_transaction).clone());
+            continue;
+        };
+
+        let fields = differing_fields(left_transaction, right_transaction, &options);
+        if !fields.is_empty() {
+            result.mismatched.push(Mismatch {
+                left: (*left_transaction).clone(),
+                right: (*right_transaction).clone(),
+                fields,
+            });
+        }
+    }
+
+    for (id, right_transaction) in &right_by_id {
+        if !left_by_id.contains_key(id) {
+            result.only_in_right.push((*right_transaction).clone());
+        }
+    }
+
+    result
+}
+
+fn differing_fields(left: &Transaction, right: &Transaction, options: &DiffOptions) -> Vec<FieldDiff> {
+    let description_equal = match options.strict_description {
+        true => left.description == right.description,
+        false => left.description.split_whitespace().eq(right.description.split_whitespace()),
+    };
+
+    let mut fields = Vec::new();
+    let mut compare = |field: &'static str, equal: bool, render: &dyn Fn(&Transaction) -> String| {
+        if !equal {
+            fields.push(FieldDiff { field, left: render(left), right: render(right) });
+        }
+    };
+
+    compare("TX_TYPE", left.transaction_type == right.transaction_type, &|t| t.transaction_type.to_string());
+    compare("FROM_USER_ID", left.from_user_id == right.from_user_id, &|t| t.from_user_id.to_string());
+    compare("TO_USER_ID", left.to_user_id == right.to_user_id, &|t| t.to_user_id.to_string());
+    compare("AMOUNT", left.amount == right.amount, &|t| t.amount.to_string());
+    let timestamp_equal = left.timestamp.abs_diff(right.timestamp) <= options.timestamp_tolerance_ms;
+    compare("TIMESTAMP", timestamp_equal, &|t| t.timestamp.to_string());
+    compare("STATUS", left.transaction_status == right.transaction_status, &|t| t.transaction_status.to_string());
+    compare("DESCRIPTION", description_equal, &|t| t.description.clone());
+    compare("CURRENCY", left.currency == right.currency, &|t| t.currency.clone().unwrap_or_default());
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::test_transaction;
+    use crate::TransactionStatus;
+
+    #[test]
+    fn reports_added_removed_and_changed_records() {
+        let left = vec![
+            test_transaction(1),
+            Transaction { amount: 200, ..test_transaction(2) },
+            Transaction { amount: 400, ..test_transaction(4) },
+        ];
+        let right = vec![
+            test_transaction(1),
+            Transaction { amount: 250, ..test_transaction(2) },
+            Transaction { amount: 300, ..test_transaction(3) },
+        ];
+
+        let result = diff(&left, &right);
+
+        assert_eq!(result.only_in_left, vec![Transaction { amount: 400, ..test_transaction(4) }]);
+        assert_eq!(result.only_in_right, vec![Transaction { amount: 300, ..test_transaction(3) }]);
+        assert_eq!(
+            result.mismatched,
+            vec![Mismatch {
+                left: Transaction { amount: 200, ..test_transaction(2) },
+                right: Transaction { amount: 250, ..test_transaction(2) },
+                fields: vec![FieldDiff { field: "AMOUNT", left: "200".to_string(), right: "250".to_string() }],
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_each_differing_field_with_values() {
+        let left = vec![test_transaction(1)];
+        let right = vec![Transaction {
+            amount: 150,
+            transaction_status: TransactionStatus::Failure,
+            description: "Desc  1".to_string(),
+            ..test_transaction(1)
+        }];
+
+        assert_eq!(
+            diff(&left, &right).mismatched[0].fields,
+            vec![
+                FieldDiff { field: "AMOUNT", left: "100".to_string(), right: "150".to_string() },
+                FieldDiff { field: "STATUS", left: "SUCCESS".to_string(), right: "FAILURE".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn description_whitespace_is_ignored_unless_strict() {
+        let left = vec![test_transaction(1)];
+        let right = vec![Transaction { description: "  Desc   1 ".to_string(), ..test_transaction(1) }];
+
+        assert!(diff(&left, &right).is_empty());
+        assert_eq!(
+            diff_with(&left, &right, DiffOptions::strict()).mismatched[0].fields,
+            vec![FieldDiff { field: "DESCRIPTION", left: "Desc 1".to_string(), right: "  Desc   1 ".to_string() }]
+        );
+    }
+
+    #[test]
+    fn timestamp_within_tolerance_is_not_a_difference() {
+        let left = vec![test_transaction(1)];
+        let right = vec![Transaction { timestamp: 1901, ..test_transaction(1) }];
+        let options = DiffOptions { timestamp_tolerance_ms: 900, ..DiffOptions::default() };
+
+        assert!(diff_with(&left, &right, options).is_empty());
+        assert_eq!(diff(&left, &right).mismatched[0].fields[0].field, "TIMESTAMP");
+    }
+}
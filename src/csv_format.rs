@@ -1,16 +1,23 @@
-use crate::common::{Transaction, TransactionStatus, TransactionType};
-use crate::{Readable, Writable};
+use crate::common::{deserialize_currency, deserialize_user_id, hex_to_decimal, is_currency_code, is_overflowing, CURRENCY_FIELD, ENUM_FIELDS, FIELD_NAMES, NUMERIC_FIELDS, SentinelRendering, Transaction, TransactionStatus, TransactionType};
+use crate::errors::CsvRecordError;
+use crate::{Parser, RawBytesRecords, Readable, RecordWithRaw, Serializer, Writable, YPBankTextRecord};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
-use std::io::{Error, ErrorKind, Read, Write};
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::ops::RangeInclusive;
 
 //noinspection DuplicatedCode
 /// Запись транзакции в формате CSV (Comma-Separated Values).
 ///
 /// Ожидается наличие заголовка: `TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION`.
 /// Поля разделяются запятыми, описание заключается в двойные кавычки.
+/// Пробелы вокруг значений при чтении отбрасываются у всех полей, кроме `DESCRIPTION`:
+/// описание сохраняется в точности так, как оно записано в кавычках.
+/// Заголовок может завершаться необязательным столбцом `CURRENCY`: в строках без валюты
+/// это поле можно опустить. При записи столбец добавляется, если валюта указана у первой записи.
 #[serde_as]
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct YPBankCsvRecord {
     #[serde(rename = "TX_ID")]
@@ -20,12 +27,10 @@ pub struct YPBankCsvRecord {
     #[serde(rename = "TX_TYPE")]
     transaction_type: TransactionType,
 
-    #[serde(rename = "FROM_USER_ID")]
-    #[serde_as(as = "DisplayFromStr")]
+    #[serde(rename = "FROM_USER_ID", deserialize_with = "deserialize_user_id")]
     from_user_id: u64,
 
-    #[serde(rename = "TO_USER_ID")]
-    #[serde_as(as = "DisplayFromStr")]
+    #[serde(rename = "TO_USER_ID", deserialize_with = "deserialize_user_id")]
     to_user_id: u64,
 
     #[serde(rename = "AMOUNT")]
@@ -39,7 +44,10 @@ pub struct YPBankCsvRecord {
     #[serde(rename = "STATUS")]
     transaction_status: TransactionStatus,
     #[serde(rename = "DESCRIPTION")]
-    description: String
+    description: String,
+
+    #[serde(rename = "CURRENCY", default, deserialize_with = "deserialize_currency", skip_serializing_if = "Option::is_none")]
+    currency: Option<String>,
 }
 
 
@@ -56,6 +64,7 @@ impl From<YPBankCsvRecord> for Transaction {
             timestamp: value.timestamp,
             transaction_status: value.transaction_status,
             description: value.description,
+            currency: value.currency,
         }
     }
 }
@@ -71,60 +80,766 @@ impl From<Transaction> for YPBankCsvRecord {
             timestamp: value.timestamp,
             transaction_status: value.transaction_status,
             description: value.description,
+            currency: value.currency,
+        }
+    }
+}
+
+impl From<YPBankTextRecord> for YPBankCsvRecord {
+    fn from(value: YPBankTextRecord) -> Self {
+        Transaction::from(value).into()
+    }
+}
+
+//noinspection DuplicatedCode
+impl YPBankCsvRecord {
+    /// Уникальный идентификатор транзакции.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Тип операции.
+    pub fn transaction_type(&self) -> TransactionType {
+        self.transaction_type
+    }
+
+    /// ID отправителя.
+    pub fn from_user_id(&self) -> u64 {
+        self.from_user_id
+    }
+
+    /// ID получателя.
+    pub fn to_user_id(&self) -> u64 {
+        self.to_user_id
+    }
+
+    /// Сумма операции.
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    /// Временная метка операции.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Статус транзакции.
+    pub fn transaction_status(&self) -> TransactionStatus {
+        self.transaction_status
+    }
+
+    /// Описание транзакции.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Код валюты, если он указан.
+    pub fn currency(&self) -> Option<&str> {
+        self.currency.as_deref()
+    }
+}
+
+/// Наличие столбца `CURRENCY` в записываемом CSV (настройка `Serializer::with_options`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CurrencyColumn {
+    /// Определяется по первой записи потока при записи заголовка (по умолчанию).
+    #[default]
+    Auto,
+    /// Столбец есть; у записей без валюты он пустой.
+    Present,
+    /// Столбца нет; запись с валютой завершается ошибкой `InvalidInput`.
+    Absent,
+}
+
+/// Параметры разметки CSV-файла.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Разделитель полей.
+    pub delimiter: u8,
+    /// Содержит ли первая строка заголовок. Без заголовка столбцы сопоставляются полям
+    /// по порядку: `TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION`.
+    pub has_headers: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            has_headers: true,
+        }
+    }
+}
+
+/// Читатель CSV-формата с настраиваемыми режимами разбора.
+///
+/// Создается автоматически в `Parser::new`; для изменения настроек его можно собрать вручную
+/// и передать в `Parser::from_reader`.
+pub struct CsvReader<R> {
+    pub(crate) inner: csv::Reader<CsvSource<R>>,
+    has_headers: bool,
+    hex_numbers: bool,
+    ignore_unknown_columns: bool,
+    case_insensitive_enums: bool,
+    blank_description_as_none: bool,
+    skip_empty_rows: bool,
+    /// Исходные байты последней прочитанной записи, если включен `Parser::with_raw`.
+    raw: Vec<u8>,
+}
+
+impl<R: Read> CsvReader<R> {
+    /// Создает читатель с настройками по умолчанию.
+    pub fn new(source: R) -> Self {
+        Self::with_capacity(source, crate::DEFAULT_BUFFER_CAPACITY)
+    }
+
+    /// Создает читатель с буферами размером `capacity` байт.
+    pub fn with_capacity(source: R, capacity: usize) -> Self {
+        Self::build(source, capacity, CsvOptions::default())
+    }
+
+    /// Создает читатель с заданными разделителем и наличием заголовка.
+    pub fn with_options(source: R, options: CsvOptions) -> Self {
+        Self::build(source, crate::DEFAULT_BUFFER_CAPACITY, options)
+    }
+
+    fn build(source: R, capacity: usize, options: CsvOptions) -> Self {
+        let source = CsvSource {
+            inner: BufReader::with_capacity(capacity, source),
+            join_continuations: false,
+            in_quotes: false,
+            line: Vec::new(),
+            position: 0,
+            capture_raw: false,
+            captured: Vec::new(),
+            captured_from: 0,
+        };
+
+        Self {
+            inner: csv::ReaderBuilder::new()
+                .trim(csv::Trim::Headers)
+                // число полей проверяется в `read_row`, чтобы пустые строки можно было пропустить
+                .flexible(true)
+                .buffer_capacity(capacity)
+                .delimiter(options.delimiter)
+                .has_headers(options.has_headers)
+                .from_reader(source),
+            has_headers: options.has_headers,
+            hex_numbers: false,
+            ignore_unknown_columns: false,
+            case_insensitive_enums: false,
+            blank_description_as_none: false,
+            skip_empty_rows: true,
+            raw: Vec::new(),
+        }
+    }
+
+    /// Разрешает шестнадцатеричную запись числовых полей с префиксом `0x` (например, `0x1F4`).
+    ///
+    /// Значения без префикса по-прежнему разбираются как десятичные. По умолчанию выключено.
+    pub fn hex_numbers(mut self, enabled: bool) -> Self {
+        self.hex_numbers = enabled;
+        self
+    }
+
+    /// Разрешает запись `TX_TYPE` и `STATUS` в любом регистре (например, `deposit` или `Deposit`).
+    ///
+    /// По умолчанию выключено, чтобы опечатки в регистре не оставались незамеченными.
+    pub fn case_insensitive_enums(mut self, enabled: bool) -> Self {
+        self.case_insensitive_enums = enabled;
+        self
+    }
+
+    /// Считает описание из одних пробельных символов отсутствующим: оно читается как пустое,
+    /// и `Transaction::description_opt` возвращает `None`.
+    ///
+    /// По умолчанию выключено: пробелы сохраняются как часть описания.
+    pub fn blank_description_as_none(mut self, enabled: bool) -> Self {
+        self.blank_description_as_none = enabled;
+        self
+    }
+
+    /// Пропускает строки, все поля которых пусты или состоят из пробелов (например, `   ` или `,,,`).
+    ///
+    /// По умолчанию включено, как и пропуск пустых строк между блоками текстового формата.
+    /// В строгом режиме такая строка — ошибка разбора. Совсем пустые строки парсер CSV
+    /// пропускает в любом режиме.
+    pub fn skip_empty_rows(mut self, enabled: bool) -> Self {
+        self.skip_empty_rows = enabled;
+        self
+    }
+
+    /// Разрешает столбцы, не входящие в формат (например, добавленный партнером `BRANCH_ID`).
+    ///
+    /// Такие столбцы отбрасываются, известные поля проверяются как обычно.
+    /// По умолчанию выключено: неизвестный столбец — ошибка разбора.
+    pub fn ignore_unknown_columns(mut self, enabled: bool) -> Self {
+        self.ignore_unknown_columns = enabled;
+        self
+    }
+
+    /// Включает склейку строк-продолжений.
+    ///
+    /// Строка, оканчивающаяся обратным слешем вне кавычек, объединяется со следующей
+    /// физической строкой: слеш и перевод строки удаляются.
+    pub fn join_continuations(mut self, enabled: bool) -> Self {
+        self.inner.get_mut().join_continuations = enabled;
+        self
+    }
+}
+
+impl<R: Read> CsvReader<R> {
+    /// Читает следующую строку данных, пропуская пустые при `skip_empty_rows`.
+    ///
+    /// Проверяет, что число полей строки входит в `expected_fields`. Возвращает `Ok(false)`, если данные закончились.
+    pub(crate) fn read_row(&mut self, record: &mut csv::ByteRecord, expected_fields: RangeInclusive<usize>) -> Result<bool, CsvRecordError> {
+        loop {
+            if !self.inner.read_byte_record(record)? {
+                return Ok(false);
+            }
+
+            if !(self.skip_empty_rows && record.iter().all(|field| field.trim_ascii().is_empty())) {
+                break;
+            }
+        }
+
+        if !expected_fields.contains(&record.len()) {
+            let message = format!("found record with {} fields, but the header has {} fields", record.len(), expected_fields.end());
+            return Err(CsvRecordError::InvalidData {
+                line: record.position().map_or(0, csv::Position::line),
+                record: record.position().map(csv::Position::record),
+                field: None,
+                source: message.into(),
+            });
+        }
+
+        Ok(true)
+    }
+
+    /// Исходный источник под буферами читателя.
+    #[cfg(feature = "async")]
+    pub(crate) fn source_mut(&mut self) -> &mut R {
+        self.inner.get_mut().inner.get_mut()
+    }
+
+    /// Допустимое число полей в строке данных: по заголовку или по формату, если заголовка нет.
+    ///
+    /// Завершающий столбец `CURRENCY` необязателен, поэтому строка может быть на поле короче заголовка.
+    pub(crate) fn expected_fields(&mut self) -> Result<RangeInclusive<usize>, CsvRecordError> {
+        if !self.has_headers {
+            return Ok(FIELD_NAMES.len()..=FIELD_NAMES.len());
+        }
+
+        let headers = self.inner.byte_headers()?;
+        match headers.iter().next_back() == Some(CURRENCY_FIELD.as_bytes()) {
+            true => Ok(headers.len() - 1..=headers.len()),
+            false => Ok(headers.len()..=headers.len()),
+        }
+    }
+
+    /// Ошибка, которой завершается чтение: `Empty`, если из источника не прочитано ни байта.
+    fn end_of_data(&self) -> CsvRecordError {
+        match self.inner.position().byte() {
+            0 => CsvRecordError::Empty,
+            _ => CsvRecordError::Eof,
+        }
+    }
+}
+
+impl<R: Read + Seek> CsvReader<R> {
+    /// Возвращает читатель к началу источника; строка заголовка пропускается заново.
+    pub(crate) fn rewind(&mut self) -> Result<(), CsvRecordError> {
+        self.inner.seek(csv::Position::new())?;
+
+        // после seek csv::Reader считает заголовок обычной строкой, поэтому пропускаем его сами
+        if self.has_headers {
+            self.inner.read_byte_record(&mut csv::ByteRecord::new())?;
+        }
+
+        Ok(())
+    }
+
+    /// Переводит читатель на начало записи по смещению `byte_offset` от начала источника.
+    fn seek_to(&mut self, byte_offset: u64) -> Result<(), CsvRecordError> {
+        let mut position = csv::Position::new();
+        position.set_byte(byte_offset);
+        // заголовок читается до перехода, поэтому сопоставление столбцов сохраняется
+        self.inner.seek(position)?;
+
+        Ok(())
+    }
+}
+
+/// Источник байтов для `csv::Reader`, при необходимости склеивающий строки-продолжения.
+pub(crate) struct CsvSource<R> {
+    inner: BufReader<R>,
+    join_continuations: bool,
+    /// Находится ли конец уже прочитанных данных внутри поля в кавычках.
+    in_quotes: bool,
+    line: Vec<u8>,
+    position: usize,
+    /// Сохранять ли выданные байты для `Parser::with_raw`.
+    capture_raw: bool,
+    /// Выданные, но еще не отнесенные к записи байты.
+    captured: Vec<u8>,
+    /// Смещение первого байта `captured` от начала источника.
+    captured_from: u64,
+}
+
+impl<R: Read> CsvSource<R> {
+    /// Забирает сохраненные байты в диапазоне `[start, end)` смещений от начала источника
+    /// и отбрасывает все, что лежит до `end`.
+    fn take_captured(&mut self, start: u64, end: u64) -> std::io::Result<Vec<u8>> {
+        let mut start = (start.saturating_sub(self.captured_from) as usize).min(self.captured.len());
+        let end = (end.saturating_sub(self.captured_from) as usize).clamp(start, self.captured.len());
+
+        // csv завершает запись на `\r`, а `\n` из пары `\r\n` относит к следующей записи
+        if start > 0 && self.captured[start - 1] == b'\r' && self.captured.get(start) == Some(&b'\n') {
+            start += 1;
+        }
+
+        let mut raw = self.captured[start..end.max(start)].to_vec();
+        if raw.last() == Some(&b'\r') && self.next_byte(end)? == Some(b'\n') {
+            raw.push(b'\n');
+        }
+
+        // `\r` остается в буфере, чтобы следующая запись распознала свой начальный `\n`
+        let keep = usize::from(end > 0 && self.captured[end - 1] == b'\r');
+        self.captured.drain(..end - keep);
+        self.captured_from += (end - keep) as u64;
+
+        Ok(raw)
+    }
+
+    /// Байт источника с индексом `index` в `captured`, в том числе еще не выданный csv.
+    fn next_byte(&mut self, index: usize) -> std::io::Result<Option<u8>> {
+        if let Some(&byte) = self.captured.get(index) {
+            return Ok(Some(byte));
+        }
+
+        if self.join_continuations && self.position < self.line.len() {
+            return Ok(Some(self.line[self.position]));
         }
+
+        Ok(self.inner.fill_buf()?.first().copied())
+    }
+}
+
+impl<R: Read> Read for CsvSource<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let count = self.read_joined(buf)?;
+        if self.capture_raw {
+            self.captured.extend_from_slice(&buf[..count]);
+        }
+
+        Ok(count)
+    }
+}
+
+impl<R: Read> CsvSource<R> {
+    fn read_joined(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if !self.join_continuations {
+            return self.inner.read(buf);
+        }
+
+        if self.position >= self.line.len() {
+            self.line.clear();
+            self.position = 0;
+
+            if self.inner.read_until(b'\n', &mut self.line)? == 0 {
+                return Ok(0);
+            }
+
+            // удвоенная кавычка внутри поля переключает состояние дважды, поэтому подсчета четности достаточно
+            for &byte in &self.line {
+                if byte == b'"' {
+                    self.in_quotes = !self.in_quotes;
+                }
+            }
+
+            let content = self.line.strip_suffix(b"\n").unwrap_or(&self.line);
+            let content = content.strip_suffix(b"\r").unwrap_or(content);
+
+            if !self.in_quotes && content.ends_with(b"\\") {
+                let continuation_len = content.len() - 1;
+                self.line.truncate(continuation_len);
+            }
+        }
+
+        let available = &self.line[self.position..];
+        let count = available.len().min(buf.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.position += count;
+
+        Ok(count)
+    }
+}
+
+impl<R: Seek> Seek for CsvSource<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.in_quotes = false;
+        self.line.clear();
+        self.position = 0;
+
+        let offset = self.inner.seek(pos)?;
+        self.captured.clear();
+        self.captured_from = offset;
+
+        Ok(offset)
     }
 }
 
 impl<R: Read> Readable<R> for YPBankCsvRecord {
-    type Reader = csv::Reader<R>;
-    type Error = Error;
+    type Reader = CsvReader<R>;
+    type Error = CsvRecordError;
+
+    fn build_reader(source: R) -> Self::Reader {
+        CsvReader::new(source)
+    }
+
+    fn build_reader_with_capacity(source: R, capacity: usize) -> Self::Reader {
+        CsvReader::with_capacity(source, capacity)
+    }
+
+    fn read(reader: &mut Self::Reader) -> Result<Self, CsvRecordError> {
+        read_converted(reader)
+    }
+
+    fn read_id(reader: &mut Self::Reader) -> Result<u64, CsvRecordError> {
+        let id_column = match reader.has_headers {
+            true => reader.inner.byte_headers()?.iter().position(|header| header == b"TX_ID"),
+            false => Some(0),
+        };
+
+        let expected_fields = reader.expected_fields()?;
+        let mut record = csv::ByteRecord::new();
+        if !reader.read_row(&mut record, expected_fields)? {
+            return Err(reader.end_of_data());
+        }
+
+        let line = record.position().map_or(0, csv::Position::line);
+        let record_number = record.position().map(csv::Position::record);
+        let invalid = |message: String| CsvRecordError::InvalidData {
+            line,
+            record: record_number,
+            field: Some("TX_ID".to_owned()),
+            source: message.into(),
+        };
+        let Some(id_column) = id_column else {
+            return Err(invalid("Missing TX_ID column".to_owned()));
+        };
+
+        let field = record
+            .get(id_column)
+            .and_then(|field| std::str::from_utf8(field).ok())
+            .ok_or_else(|| invalid("Invalid TX_ID value".to_owned()))?
+            .trim();
+        // числа разбираются так же, как в `read_converted`
+        let field = match reader.hex_numbers {
+            true => hex_to_decimal(field).map_err(invalid)?,
+            false => field.into(),
+        };
+
+        field.parse().map_err(|_| invalid("Invalid TX_ID value".to_owned()))
+    }
+
+    fn rewind(reader: &mut Self::Reader) -> Result<(), CsvRecordError>
+    where
+        R: Seek
+    {
+        reader.rewind()
+    }
+
+    fn is_recoverable(error: &CsvRecordError) -> bool {
+        // ошибка разбора строки оставляет читатель на начале следующей строки; ошибку ввода-вывода не обойти
+        matches!(error, CsvRecordError::InvalidData { .. } | CsvRecordError::FieldOverflow { .. })
+    }
+}
+
+/// Маркер CSV-записи произвольной структуры, читаемой через serde.
+///
+/// Для типа с этим маркером `Readable` реализуется автоматически тем же кодом, что и для
+/// `YPBankCsvRecord` (см. `read_converted`): столбцы сопоставляются полям по заголовку согласно
+/// атрибутам serde, ошибка разбора строки не прерывает чтение. Настройки `CsvReader`
+/// (шестнадцатеричные числа, регистр перечислений и т. п.) действуют на столбцы с именами полей формата.
+pub trait CsvReadable: DeserializeOwned {}
+
+impl<T: CsvReadable, R: Read> Readable<R> for T {
+    type Reader = CsvReader<R>;
+    type Error = CsvRecordError;
 
     fn build_reader(source: R) -> Self::Reader {
-        csv::ReaderBuilder::new()
-            .trim(csv::Trim::All)
-            .from_reader(source)
+        CsvReader::new(source)
+    }
+
+    fn build_reader_with_capacity(source: R, capacity: usize) -> Self::Reader {
+        CsvReader::with_capacity(source, capacity)
+    }
+
+    fn read(reader: &mut Self::Reader) -> Result<Self, CsvRecordError> {
+        read_converted(reader)
+    }
+
+    fn is_recoverable(error: &CsvRecordError) -> bool {
+        <YPBankCsvRecord as Readable<R>>::is_recoverable(error)
+    }
+}
+
+/// Убирает пробелы вокруг значения поля `header`.
+///
+/// `DESCRIPTION` возвращается как есть: пробелы внутри кавычек — часть описания.
+pub(crate) fn trim_field<'a>(header: &str, value: &'a str) -> &'a str {
+    match header {
+        "DESCRIPTION" => value,
+        _ => value.trim(),
+    }
+}
+
+/// Читает запись через промежуточную строку, которая готовится согласно настройкам читателя:
+/// значения полей очищаются от окружающих пробелов (см. `trim_field`), шестнадцатеричные
+/// числовые поля переводятся в десятичные, перечисления приводятся к верхнему регистру,
+/// неизвестные столбцы отбрасываются. Общая для `YPBankCsvRecord` и типов с `CsvReadable`.
+fn read_converted<T: DeserializeOwned, R: Read>(reader: &mut CsvReader<R>) -> Result<T, CsvRecordError> {
+    let headers = match reader.has_headers {
+        true => reader.inner.headers()?.clone(),
+        false => csv::StringRecord::from(FIELD_NAMES.to_vec()),
+    };
+
+    let expected_fields = reader.expected_fields()?;
+    let mut record = csv::ByteRecord::new();
+    if !reader.read_row(&mut record, expected_fields)? {
+        return Err(reader.end_of_data());
     }
+    let position = record.position().cloned();
+    let record = csv::StringRecord::from_byte_record(record).map_err(|error| CsvRecordError::InvalidData {
+        line: position.as_ref().map_or(0, csv::Position::line),
+        record: position.as_ref().map(csv::Position::record),
+        field: headers.get(error.utf8_error().field()).map(str::to_owned),
+        source: Box::new(error.utf8_error().clone()),
+    })?;
 
-    fn read(reader: &mut Self::Reader) -> Result<Self, Error> {
-        let mut iter = reader.deserialize();
+    if reader.inner.get_ref().capture_raw {
+        let start = record.position().map_or(0, csv::Position::byte);
+        let end = reader.inner.position().byte();
+        reader.raw = reader.inner.get_mut().take_captured(start, end)?;
+    }
 
-        match iter.next() {
-            Some(Ok(record)) => Ok(record),
-            Some(Err(e)) => Err(Error::new(ErrorKind::InvalidData, e)),
-            None => Err(Error::new(ErrorKind::UnexpectedEof, "End of CSV")),
+    let line = record.position().map_or(0, csv::Position::line);
+    let record_number = record.position().map(csv::Position::record);
+    let mut kept_headers = csv::StringRecord::with_capacity(headers.as_slice().len(), headers.len());
+    let mut converted = csv::StringRecord::with_capacity(record.as_slice().len(), record.len());
+    for (header, value) in headers.iter().zip(record.iter()) {
+        if reader.ignore_unknown_columns && !FIELD_NAMES.contains(&header) && header != CURRENCY_FIELD {
+            continue;
         }
+
+        let value = trim_field(header, value);
+        if reader.hex_numbers && NUMERIC_FIELDS.contains(&header) {
+            let value = hex_to_decimal(value).map_err(|e| CsvRecordError::InvalidData {
+                line,
+                record: record_number,
+                field: Some(header.to_owned()),
+                source: e.into(),
+            })?;
+            converted.push_field(&value);
+        } else if reader.case_insensitive_enums && ENUM_FIELDS.contains(&header) {
+            converted.push_field(&value.to_ascii_uppercase());
+        } else if reader.blank_description_as_none && header == "DESCRIPTION" && value.trim().is_empty() {
+            converted.push_field("");
+        } else {
+            converted.push_field(value);
+        }
+        kept_headers.push_field(header);
+
+        if let Some(&field) = NUMERIC_FIELDS.iter().find(|&&field| field == header)
+            && is_overflowing(field, &converted[converted.len() - 1])
+        {
+            return Err(CsvRecordError::FieldOverflow { line, record: record_number, field, value: value.to_owned() });
+        }
+    }
+
+    // позиция нужна, чтобы ошибка десериализации указывала на исходную строку
+    converted.set_position(record.position().cloned());
+    converted.deserialize(Some(&kept_headers)).map_err(|error| {
+        let field = match error.kind() {
+            csv::ErrorKind::Deserialize { err, .. } => match err.field() {
+                Some(i) => kept_headers.get(i as usize).map(str::to_owned),
+                None => invalid_field(&kept_headers, &converted),
+            },
+            _ => None,
+        };
+
+        match CsvRecordError::from(error) {
+            CsvRecordError::InvalidData { line, record, source, .. } => CsvRecordError::InvalidData { line, record, field, source },
+            other => other,
+        }
+    })
+}
+
+/// Имя первого поля, значение которого не разбирается.
+///
+/// Ошибки `FromStr` и перечислений приходят из serde без номера поля, поэтому поле
+/// находится повторным разбором значений по отдельности.
+fn invalid_field(headers: &csv::StringRecord, record: &csv::StringRecord) -> Option<String> {
+    use serde::de::value::{Error as DeError, StrDeserializer};
+
+    headers
+        .iter()
+        .zip(record.iter())
+        .find(|&(header, value)| {
+            let deserializer = StrDeserializer::<DeError>::new(value);
+            let valid = match header {
+                "TX_ID" | "AMOUNT" | "TIMESTAMP" => value.parse::<u64>().is_ok(),
+                "FROM_USER_ID" | "TO_USER_ID" => deserialize_user_id(deserializer).is_ok(),
+                "TX_TYPE" => TransactionType::deserialize(deserializer).is_ok(),
+                "STATUS" => TransactionStatus::deserialize(deserializer).is_ok(),
+                CURRENCY_FIELD => value.trim().is_empty() || is_currency_code(value.trim()),
+                _ => true,
+            };
+            !valid
+        })
+        .map(|(header, _)| header.to_owned())
+}
+
+impl<R: Read> Parser<YPBankCsvRecord, R> {
+    /// Создает парсер CSV с заданными разделителем и наличием заголовка.
+    pub fn with_csv_options(source: R, options: CsvOptions) -> Self {
+        Self::from_reader(CsvReader::with_options(source, options))
+    }
+}
+
+impl<R: Read> Iterator for RawBytesRecords<YPBankCsvRecord, R> {
+    type Item = Result<RecordWithRaw, CsvRecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.parser.next()?;
+        let raw = std::mem::take(&mut self.parser.reader.raw);
+
+        Some(record.map(|record| RecordWithRaw { parsed: record.into(), raw }))
+    }
+}
+
+impl<R: Read> Parser<YPBankCsvRecord, R> {
+    /// Переключает парсер в режим, в котором вместе с каждой транзакцией возвращаются
+    /// исходные байты её строки (или строк, если описание многострочное) вместе с переводом строки.
+    ///
+    /// Вызывается до начала чтения. При склейке строк-продолжений байты возвращаются уже склеенными.
+    pub fn with_raw(mut self) -> RawBytesRecords<YPBankCsvRecord, R> {
+        self.reader.inner.get_mut().capture_raw = true;
+
+        RawBytesRecords { parser: self }
+    }
+}
+
+impl<R: Read + Seek> Parser<YPBankCsvRecord, R> {
+    /// Читает одну запись, начинающуюся со смещения `byte_offset` байт от начала источника.
+    ///
+    /// Смещение должно указывать на начало строки записи (например, взятое из внешнего индекса);
+    /// смещение внутри строки даст ошибку разбора или чужие значения. Номера строк в ошибках
+    /// отсчитываются от смещения. После вызова итерация продолжается со следующей записи.
+    pub fn read_at(&mut self, byte_offset: u64) -> Result<Transaction, CsvRecordError> {
+        self.reader.seek_to(byte_offset)?;
+        self.pending_error = None;
+        self.peeked = None;
+        self.finished = false;
+
+        read_converted::<YPBankCsvRecord, _>(&mut self.reader).map(Transaction::from)
     }
 }
 
 impl Writable for YPBankCsvRecord {
     type Error = Error;
+    type Options = CurrencyColumn;
 
     fn write_header<W: Write>(writer: &mut W) -> Result<(), Error> {
-        writer.write_all(b"TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n")
+        writeln!(writer, "{}", Self::field_names().join(","))
+    }
+
+    fn write_header_for<W: Write>(writer: &mut W, first: &Self, options: &mut CurrencyColumn) -> Result<(), Error> {
+        if *options == CurrencyColumn::Auto {
+            *options = match first.currency {
+                Some(_) => CurrencyColumn::Present,
+                None => CurrencyColumn::Absent,
+            };
+        }
+
+        match options {
+            CurrencyColumn::Present => writeln!(writer, "{},{}", Self::field_names().join(","), CURRENCY_FIELD),
+            _ => Self::write_header(writer),
+        }
     }
 
     fn write<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
-        writeln!(
+        self.write_rendered(writer, &SentinelRendering::Zero, &CurrencyColumn::Auto)
+    }
+
+    fn write_rendered<W: Write>(&self, writer: &mut W, sentinel: &SentinelRendering, options: &CurrencyColumn) -> Result<(), Error> {
+        if *options == CurrencyColumn::Absent && self.currency.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Record {} has CURRENCY, but the CSV header has no CURRENCY column", self.id),
+            ));
+        }
+
+        // числовые поля и перечисления никогда не требуют кавычек, поэтому пишутся напрямую
+        write!(
             writer,
-            "{},{},{},{},{},{},{},\"{}\"",
+            "{},{},{},{},{},{},{},",
             self.id,
             self.transaction_type,
-            self.from_user_id,
-            self.to_user_id,
+            sentinel.render(self.from_user_id),
+            sentinel.render(self.to_user_id),
             self.amount,
             self.timestamp,
             self.transaction_status,
-            self.description.replace('"', "\"\"") // экранирование кавычек внутри description для CSV формата
         )?;
 
+        // описание по спецификации всегда в кавычках; экранирование кавычек и переводов строк выполняет csv::Writer
+        let mut description_writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .quote_style(csv::QuoteStyle::Always)
+            .buffer_capacity(2 * self.description.len() + 9)
+            .from_writer(writer);
+        match (&self.currency, options) {
+            (Some(currency), _) => description_writer.write_record([self.description.as_str(), currency])?,
+            (None, CurrencyColumn::Present) => description_writer.write_record([self.description.as_str(), ""])?,
+            (None, _) => description_writer.serialize(&self.description)?,
+        }
+        description_writer.flush()?;
+
         Ok(())
     }
 }
 
+/// Метка строки-итога, которую пишет `Serializer::serialize_with_trailer`.
+const TRAILER_MARKER: &str = "TRAILER";
+
+impl<Target: Write> Serializer<YPBankCsvRecord, Target> {
+    /// Сериализует записи, как `serialize`, и дописывает после них строку-итог
+    /// `TRAILER,<число записей>,<сумма AMOUNT>`.
+    ///
+    /// Сумма накапливается в `u128` и не переполняется даже при суммах, близких к `u64::MAX`.
+    /// Итог не входит в формат CSV: `Parser` его не понимает и прочитает как ошибочную запись.
+    pub fn serialize_with_trailer<I>(&mut self, records: I) -> Result<(), Error>
+    where I: IntoIterator<Item = YPBankCsvRecord>,
+    {
+        let mut count: u64 = 0;
+        let mut total: u128 = 0;
+        let records = records.into_iter().inspect(|record| {
+            count += 1;
+            total += u128::from(record.amount);
+        });
+
+        self.serialize(records)?;
+
+        writeln!(self.target, "{},{},{}", TRAILER_MARKER, count, total)?;
+        self.target.flush()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Parser, Serializer};
+    use crate::serialize_to_vec;
     use std::io::Cursor;
 
     fn sample_record() -> YPBankCsvRecord {
@@ -137,6 +852,7 @@ mod tests {
             timestamp: 1672531200000,
             transaction_status: TransactionStatus::Success,
             description: "Initial account funding".to_string(),
+            currency: None,
         }
     }
 
@@ -149,10 +865,10 @@ TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
         let cursor = Cursor::new(csv_data);
         let mut parser = Parser::<YPBankCsvRecord, _>::new(cursor);
 
-        let record = parser.next().expect("Should have a record");
+        let record = parser.next().expect("Should have a record").unwrap();
 
         assert_eq!(record, sample_record());
-        assert!(parser.next().is_none(), "Read error: {}", parser.read_error.unwrap());
+        assert!(parser.next().is_none());
     }
 
     #[test]
@@ -165,7 +881,7 @@ TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
         let cursor = Cursor::new(csv_data);
         let mut parser = Parser::<YPBankCsvRecord, _>::new(cursor);
 
-        let r1 = parser.next().unwrap();
+        let r1 = parser.next().unwrap().unwrap();
         assert_eq!(r1.id, 1);
         assert_eq!(r1.transaction_type, TransactionType::Deposit);
         assert_eq!(r1.from_user_id, 0);
@@ -175,7 +891,7 @@ TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
         assert_eq!(r1.transaction_status, TransactionStatus::Success);
         assert_eq!(r1.description, "Desc 1");
 
-        let r2 = parser.next().unwrap();
+        let r2 = parser.next().unwrap().unwrap();
         assert_eq!(r2.id, 2);
         assert_eq!(r2.transaction_type, TransactionType::Withdrawal);
         assert_eq!(r2.from_user_id, 10);
@@ -197,11 +913,25 @@ NOT_A_NUMBER,DEPOSIT,0,501,50000,1672531200000,SUCCESS,\"Bad ID\"
         let cursor = Cursor::new(csv_data);
         let mut parser = Parser::<YPBankCsvRecord, _>::new(cursor);
 
-        // Parser returns None on error, and stores error in read_error
+        let err = parser.next().expect("Should have an item").unwrap_err();
+        assert!(matches!(err, CsvRecordError::InvalidData { .. }));
         assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn bad_middle_row_does_not_discard_later_rows() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc 1\"
+2,DEPOSIT,0,10,not_a_number,2000,SUCCESS,\"Desc 2\"
+3,DEPOSIT,0,10,300,3000,SUCCESS,\"Desc 3\"
+";
+        let mut parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data));
 
-        let err = parser.read_error.expect("Should have read_error");
-        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(parser.next().unwrap().unwrap().id, 1);
+        assert!(matches!(parser.next().unwrap().unwrap_err(), CsvRecordError::InvalidData { .. }));
+        assert_eq!(parser.next().unwrap().unwrap().id, 3);
+        assert!(parser.next().is_none());
     }
 
     #[test]
@@ -219,6 +949,7 @@ NOT_A_NUMBER,DEPOSIT,0,501,50000,1672531200000,SUCCESS,\"Bad ID\"
             timestamp: 1672531300000,
             transaction_status: TransactionStatus::Pending,
             description: "Payment".to_string(),
+            currency: None,
         };
 
         let writer = Cursor::new(Vec::<u8>::new());
@@ -235,4 +966,633 @@ TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
 ";
         assert_eq!(output, expected);
     }
+
+    #[test]
+    fn serialize_to_vec_returns_csv_bytes() {
+        let bytes = serialize_to_vec(&[sample_record()]).unwrap();
+
+        let expected = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1001,DEPOSIT,0,501,50000,1672531200000,SUCCESS,\"Initial account funding\"
+";
+        assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+    }
+
+    #[test]
+    fn read_joins_continuation_lines() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1001,DEPOSIT,0,501,50000,1672531200000,SUCCESS,Initial account \\
+funding
+1002,WITHDRAWAL,501,0,100,1672531300000,PENDING,\"Quoted \\\"
+";
+        let reader = CsvReader::new(Cursor::new(csv_data)).join_continuations(true);
+        let mut parser = Parser::<YPBankCsvRecord, _>::from_reader(reader);
+
+        let record = parser.next().expect("Should have a record").unwrap();
+        assert_eq!(record, sample_record());
+
+        // обратный слеш внутри кавычек не является признаком продолжения
+        let record = parser.next().expect("Should have a second record").unwrap();
+        assert_eq!(record.description, "Quoted \\");
+
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn read_without_continuation_mode_keeps_lines_separate() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1001,DEPOSIT,0,501,50000,1672531200000,SUCCESS,Initial account \\
+funding
+";
+        let mut parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data));
+
+        let record = parser.next().expect("Should have a record").unwrap();
+        assert_eq!(record.description, "Initial account \\");
+
+        assert!(parser.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn sentinel_renderings_are_written_and_read_back_as_zero() {
+        let cases = [
+            (SentinelRendering::Zero, "1001,DEPOSIT,0,501,"),
+            (SentinelRendering::Empty, "1001,DEPOSIT,,501,"),
+            (SentinelRendering::Literal(crate::EXTERNAL_USER_LITERAL.to_string()), "1001,DEPOSIT,EXTERNAL,501,"),
+        ];
+
+        for (sentinel, expected_prefix) in cases {
+            let mut serializer = Serializer::<YPBankCsvRecord, _>::new(Vec::new()).with_sentinel(sentinel);
+            serializer.serialize(vec![sample_record()]).unwrap();
+            let bytes = serializer.into_inner().into_inner().unwrap();
+
+            let output = String::from_utf8(bytes.clone()).unwrap();
+            assert!(output.lines().nth(1).unwrap().starts_with(expected_prefix), "Output: {}", output);
+
+            let mut parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(bytes));
+            assert_eq!(parser.next().expect("Should have a record").unwrap(), sample_record());
+        }
+    }
+
+    #[test]
+    fn read_with_small_buffer_capacity() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1001,DEPOSIT,0,501,50000,1672531200000,SUCCESS,\"Initial account funding\"
+";
+        let mut parser = Parser::<YPBankCsvRecord, _>::with_buffer_capacity(Cursor::new(csv_data), 16);
+
+        assert_eq!(parser.next().expect("Should have a record").unwrap(), sample_record());
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn leading_bom_is_ignored() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1001,DEPOSIT,0,501,50000,1672531200000,SUCCESS,\"Initial account funding\"
+";
+        let parse = |data: String| {
+            Parser::<YPBankCsvRecord, _>::new(Cursor::new(data)).collect::<Result<Vec<_>, _>>().unwrap()
+        };
+
+        assert_eq!(parse(format!("\u{feff}{}", csv_data)), parse(csv_data.to_string()));
+        assert_eq!(parse(format!("\u{feff}{}", csv_data)), vec![sample_record()]);
+
+        // csv::Reader сам снимает метку и в файлах без заголовка
+        let headerless = format!("\u{feff}{}", csv_data.lines().nth(1).unwrap());
+        let options = CsvOptions { has_headers: false, ..CsvOptions::default() };
+        let mut parser = Parser::<YPBankCsvRecord, _>::with_csv_options(Cursor::new(headerless), options);
+        assert_eq!(parser.next().unwrap().unwrap(), sample_record());
+    }
+
+    #[test]
+    fn blank_description_can_be_read_as_none() {
+        let read = |description: &str, enabled: bool| -> Transaction {
+            let csv_data = format!(
+                "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n1,DEPOSIT,0,10,5,1,SUCCESS,{}\n",
+                description
+            );
+            let reader = CsvReader::new(Cursor::new(csv_data)).blank_description_as_none(enabled);
+            Parser::<YPBankCsvRecord, _>::from_reader(reader).next().unwrap().unwrap().into()
+        };
+
+        assert_eq!(read("", true).description_opt(), None);
+        assert_eq!(read("\"\"", true).description_opt(), None);
+        assert_eq!(read("\"   \"", true).description_opt(), None);
+        assert_eq!(read("\"   \"", false).description_opt(), Some("   "));
+        assert_eq!(read("\" Rent \"", true).description_opt(), Some(" Rent "));
+
+        let mut output = Vec::new();
+        YPBankCsvRecord::from(read("", true)).write(&mut output).unwrap();
+        assert!(String::from_utf8(output).unwrap().ends_with(",SUCCESS,\"\"\n"));
+    }
+
+    #[test]
+    fn header_matches_field_names() {
+        let mut header = Vec::new();
+        YPBankCsvRecord::write_header(&mut header).unwrap();
+
+        let header = String::from_utf8(header).unwrap();
+        assert_eq!(header.trim_end().split(',').collect::<Vec<_>>(), YPBankCsvRecord::field_names());
+    }
+
+    #[test]
+    fn empty_source_and_header_only_source_end_differently() {
+        let mut empty = CsvReader::new(Cursor::new(""));
+        assert!(matches!(YPBankCsvRecord::read(&mut empty), Err(CsvRecordError::Empty)));
+
+        let mut header_only = CsvReader::new(Cursor::new("TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n"));
+        assert!(matches!(YPBankCsvRecord::read(&mut header_only), Err(CsvRecordError::Eof)));
+
+        assert!(Parser::<YPBankCsvRecord, _>::new(Cursor::new("")).next().is_none());
+    }
+
+    #[test]
+    fn mid_stream_error_reports_line_and_parsing_continues() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"\"
+2,DEPOSIT,0,10,oops,2000,SUCCESS,\"\"
+3,DEPOSIT,0,10,300,3000,SUCCESS,\"\"
+";
+        let mut parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data));
+
+        assert_eq!(parser.next().unwrap().unwrap().id, 1);
+        match parser.next() {
+            Some(Err(error @ CsvRecordError::InvalidData { line: 3, .. })) => {
+                assert!(error.to_string().starts_with("invalid CSV record at line 3, field AMOUNT: "));
+            }
+            other => panic!("Expected InvalidData at line 3, got: {:?}", other),
+        }
+        assert_eq!(parser.next().unwrap().unwrap().id, 3);
+        assert!(parser.next().is_none());
+    }
+
+    fn read_amount(amount: &str, hex_numbers: bool) -> Result<u64, CsvRecordError> {
+        let csv_data = format!(
+            "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n1,DEPOSIT,0,10,{},0x10,SUCCESS,\"Hex\"\n",
+            amount
+        );
+        let reader = CsvReader::new(Cursor::new(csv_data)).hex_numbers(hex_numbers);
+        let mut parser = Parser::<YPBankCsvRecord, _>::from_reader(reader);
+
+        let record = parser.next().expect("Should have an item")?;
+        assert_eq!(record.timestamp, 16);
+
+        Ok(record.amount)
+    }
+
+    #[test]
+    fn hex_mode_accepts_hex_and_decimal_numbers() {
+        assert_eq!(read_amount("0x1F4", true).unwrap(), 500);
+        assert_eq!(read_amount("500", true).unwrap(), 500);
+    }
+
+    #[test]
+    fn hex_mode_rejects_malformed_hex() {
+        assert!(matches!(read_amount("0xZZ", true).unwrap_err(), CsvRecordError::InvalidData { .. }));
+    }
+
+    #[test]
+    fn hex_is_rejected_by_default() {
+        assert!(read_amount("0x1F4", false).is_err());
+    }
+
+    fn read_type(transaction_type: &str, case_insensitive: bool) -> Result<TransactionType, CsvRecordError> {
+        let csv_data = format!(
+            "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n1,{},0,10,5,1,Success,\"\"\n",
+            transaction_type
+        );
+        let reader = CsvReader::new(Cursor::new(csv_data)).case_insensitive_enums(case_insensitive);
+        let mut parser = Parser::<YPBankCsvRecord, _>::from_reader(reader);
+
+        let record = parser.next().expect("Should have an item")?;
+        assert_eq!(record.transaction_status, TransactionStatus::Success);
+
+        Ok(record.transaction_type)
+    }
+
+    #[test]
+    fn case_insensitive_mode_accepts_any_case() {
+        assert_eq!(read_type("deposit", true).unwrap(), TransactionType::Deposit);
+        assert_eq!(read_type("Deposit", true).unwrap(), TransactionType::Deposit);
+        assert!(matches!(read_type("depsit", true).unwrap_err(), CsvRecordError::InvalidData { .. }));
+    }
+
+    #[test]
+    fn enum_case_is_strict_by_default() {
+        assert!(read_type("deposit", false).is_err());
+    }
+
+    #[test]
+    fn semicolon_delimited_file_is_read_with_options() {
+        let csv_data = "\
+TX_ID;TX_TYPE;FROM_USER_ID;TO_USER_ID;AMOUNT;TIMESTAMP;STATUS;DESCRIPTION
+1001;DEPOSIT;0;501;50000;1672531200000;SUCCESS;\"Initial account funding\"
+";
+        let options = CsvOptions { delimiter: b';', ..CsvOptions::default() };
+        let records = Parser::<YPBankCsvRecord, _>::with_csv_options(Cursor::new(csv_data), options)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(records, vec![sample_record()]);
+    }
+
+    #[test]
+    fn headerless_file_maps_columns_by_position() {
+        let csv_data = "\
+1001,DEPOSIT,0,501,50000,1672531200000,SUCCESS,\"Initial account funding\"
+1002,DEPOSIT,0,501,50000,1672531200000,SUCCESS,\"Initial account funding\"
+";
+        let options = CsvOptions { has_headers: false, ..CsvOptions::default() };
+        let records = Parser::<YPBankCsvRecord, _>::with_csv_options(Cursor::new(csv_data), options)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0], sample_record());
+        assert_eq!(records[1].id, 1002);
+    }
+
+    #[test]
+    fn headerless_file_supports_hex_mode_and_id_lookup() {
+        let csv_data = "1001,DEPOSIT,0,501,0x1F4,1672531200000,SUCCESS,\"x\"\n";
+        let options = CsvOptions { has_headers: false, ..CsvOptions::default() };
+
+        let mut reader = CsvReader::with_options(Cursor::new(csv_data), options).hex_numbers(true);
+        assert_eq!(YPBankCsvRecord::read(&mut reader).unwrap().amount, 500);
+
+        let mut reader = CsvReader::with_options(Cursor::new(csv_data), options);
+        assert_eq!(YPBankCsvRecord::read_id(&mut reader).unwrap(), 1001);
+    }
+
+    #[test]
+    fn hex_id_is_read_the_same_by_read_id_and_read() {
+        let csv_data = "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n0x3E9,DEPOSIT,0,501,500,1672531200000,SUCCESS,\"x\"\n";
+
+        let mut reader = CsvReader::new(Cursor::new(csv_data)).hex_numbers(true);
+        assert_eq!(YPBankCsvRecord::read_id(&mut reader).unwrap(), 1001);
+
+        let mut reader = CsvReader::new(Cursor::new(csv_data)).hex_numbers(true);
+        assert_eq!(YPBankCsvRecord::read(&mut reader).unwrap().id, 1001);
+
+        let mut reader = CsvReader::new(Cursor::new(csv_data));
+        assert!(matches!(
+            YPBankCsvRecord::read_id(&mut reader),
+            Err(CsvRecordError::InvalidData { field: Some(field), .. }) if field == "TX_ID"
+        ));
+    }
+
+    #[test]
+    fn unknown_trailing_column_is_ignored_only_in_lenient_mode() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION,BRANCH_ID
+1001,DEPOSIT,0,501,50000,1672531200000,SUCCESS,\"Initial account funding\",77
+";
+
+        let mut strict = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data));
+        assert!(matches!(strict.next().unwrap().unwrap_err(), CsvRecordError::InvalidData { .. }));
+
+        let reader = CsvReader::new(Cursor::new(csv_data)).ignore_unknown_columns(true);
+        let records = Parser::<YPBankCsvRecord, _>::from_reader(reader)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records, vec![sample_record()]);
+    }
+
+    #[test]
+    fn lenient_mode_still_validates_known_columns() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION,BRANCH_ID
+1001,DEPOSIT,0,501,abc,1672531200000,SUCCESS,\"Initial account funding\",77
+";
+
+        let reader = CsvReader::new(Cursor::new(csv_data)).ignore_unknown_columns(true);
+        let mut parser = Parser::<YPBankCsvRecord, _>::from_reader(reader);
+
+        assert!(matches!(parser.next().unwrap().unwrap_err(), CsvRecordError::InvalidData { .. }));
+    }
+
+    #[test]
+    fn special_characters_in_description_round_trip() {
+        let descriptions = [
+            "Line one\nline two",
+            "He said \"hi\"",
+            "Payment, invoice #123",
+            "Mixed: \"quoted, text\"\r\nand more",
+        ];
+
+        for description in descriptions {
+            let record = YPBankCsvRecord { description: description.to_string(), ..sample_record() };
+            let bytes = serialize_to_vec(std::slice::from_ref(&record)).unwrap();
+
+            let parsed = Parser::<YPBankCsvRecord, _>::new(Cursor::new(bytes))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            assert_eq!(parsed, vec![record], "description {:?}", description);
+        }
+    }
+
+    fn read_description(row_tail: &str) -> String {
+        let csv_data = format!(
+            "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n1001,DEPOSIT,0,501,50000,1672531200000,SUCCESS,{}\n",
+            row_tail
+        );
+
+        Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data)).next().unwrap().unwrap().description
+    }
+
+    #[test]
+    fn read_keeps_commas_quotes_and_spaces_inside_quoted_description() {
+        assert_eq!(read_description("\"Payment, invoice #123\""), "Payment, invoice #123");
+        assert_eq!(read_description("\"He said \"\"hi\"\"\""), "He said \"hi\"");
+        assert_eq!(read_description("\"  spaced  \""), "  spaced  ");
+    }
+
+    #[test]
+    fn read_trims_spaces_around_unquoted_fields() {
+        let csv_data = "\
+TX_ID, TX_TYPE ,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+ 1001 , DEPOSIT ,0, 501 ,50000,1672531200000, SUCCESS ,\"Initial account funding\"
+";
+        let mut parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data));
+
+        assert_eq!(parser.next().unwrap().unwrap(), sample_record());
+    }
+
+    #[test]
+    fn spaced_description_round_trips() {
+        let record = YPBankCsvRecord { description: "  spaced  ".to_string(), ..sample_record() };
+        let bytes = serialize_to_vec(std::slice::from_ref(&record)).unwrap();
+
+        let parsed = Parser::<YPBankCsvRecord, _>::new(Cursor::new(bytes)).next().unwrap().unwrap();
+
+        assert_eq!(parsed.description, "  spaced  ");
+    }
+
+    #[test]
+    fn rewind_reads_all_records_again() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1001,DEPOSIT,0,501,50000,1672531200000,SUCCESS,\"Initial account funding\"
+1002,TRANSFER,501,502,15000,1672534800000,FAILURE,\"Payment\"
+";
+        let mut parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data));
+
+        let first_pass = parser.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        parser.rewind().unwrap();
+        let second_pass = parser.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(first_pass.len(), 2);
+        assert_eq!(second_pass, first_pass);
+    }
+
+    #[test]
+    fn getters_expose_record_fields() {
+        let record = sample_record();
+
+        assert_eq!(record.id(), 1001);
+        assert_eq!(record.transaction_type(), TransactionType::Deposit);
+        assert_eq!(record.from_user_id(), 0);
+        assert_eq!(record.to_user_id(), 501);
+        assert_eq!(record.amount(), 50000);
+        assert_eq!(record.timestamp(), 1672531200000);
+        assert_eq!(record.transaction_status(), TransactionStatus::Success);
+        assert_eq!(record.description(), "Initial account funding");
+    }
+
+    #[test]
+    fn trailer_reports_count_and_total_beyond_u64() {
+        let records = vec![
+            YPBankCsvRecord { amount: u64::MAX, ..sample_record() },
+            YPBankCsvRecord { id: 1002, amount: u64::MAX, ..sample_record() },
+            YPBankCsvRecord { id: 1003, amount: 2, ..sample_record() },
+        ];
+        let mut output = Vec::new();
+
+        Serializer::<YPBankCsvRecord, _>::new(&mut output).serialize_with_trailer(records).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let total = 2 * u128::from(u64::MAX) + 2;
+        assert_eq!(output.lines().count(), 5);
+        assert_eq!(output.lines().last().unwrap(), format!("TRAILER,3,{}", total));
+    }
+
+    #[test]
+    fn read_at_reads_records_out_of_order() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1001,DEPOSIT,0,501,50000,1672531200000,SUCCESS,\"Initial account funding\"
+1002,TRANSFER,501,502,15000,1672534800000,FAILURE,\"Payment\"
+1003,WITHDRAWAL,502,0,1000,1672538400000,PENDING,\"Cash\"
+";
+        // первый проход: смещения начала строк записей
+        let offsets: Vec<u64> = csv_data.match_indices('\n').map(|(i, _)| i as u64 + 1).take(3).collect();
+        let mut parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data));
+        let all: Vec<Transaction> = parser.by_ref().map(|r| r.map(Transaction::from)).collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(parser.read_at(offsets[2]).unwrap(), all[2]);
+        assert_eq!(parser.read_at(offsets[0]).unwrap(), all[0]);
+        assert_eq!(parser.next().unwrap().map(Transaction::from).unwrap(), all[1]);
+    }
+
+    #[test]
+    fn invalid_data_reports_record_and_field() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"\"
+2,DEPOSIT,0,10,200,2000,SUCCESS,\"Two
+lines\"
+3,DEPOSIT,0,10,12x,3000,SUCCESS,\"\"
+";
+        let error = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data)).nth(2).unwrap().unwrap_err();
+
+        match error {
+            CsvRecordError::InvalidData { line, record, field, .. } => {
+                assert_eq!(line, 5);
+                assert_eq!(record, Some(3));
+                assert_eq!(field.as_deref(), Some("AMOUNT"));
+            }
+            other => panic!("Expected InvalidData, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn with_raw_returns_exact_record_bytes() {
+        let first = "1001,DEPOSIT, 0 ,501,50000,1672531200000,SUCCESS,\"Initial, \"\"quoted\"\"\nfunding\"\r\n";
+        let second = "1002,TRANSFER,501,502,15000,1672534800000,FAILURE,\"Payment\"";
+        let input = format!("TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n{}{}", first, second);
+
+        // малые буферы разрывают запись и пару `\r\n` между порциями чтения
+        for capacity in [crate::DEFAULT_BUFFER_CAPACITY, 1, 16, 86, 87] {
+            let records: Vec<RecordWithRaw> = Parser::<YPBankCsvRecord, _>::with_buffer_capacity(Cursor::new(input.clone()), capacity)
+                .with_raw()
+                .collect::<Result<_, _>>()
+                .unwrap();
+
+            assert_eq!(records.len(), 2);
+            assert_eq!(records[0].raw, first.as_bytes(), "capacity {}", capacity);
+            assert_eq!(records[0].parsed.description, "Initial, \"quoted\"\nfunding");
+            assert_eq!(records[1].raw, second.as_bytes(), "capacity {}", capacity);
+        }
+    }
+
+    #[test]
+    fn blank_rows_between_and_after_records_are_skipped() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc 1\"
+
+   \r
+2,WITHDRAWAL,10,0,50,2000,PENDING,\"Desc 2\"
+, , ,,,,,
+3,DEPOSIT,0,10,100,3000,SUCCESS,\"Desc 3\"
+
+";
+        let ids: Vec<u64> = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data))
+            .map(|record| record.map(|record| record.id))
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn strict_mode_reports_blank_rows() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc 1\"
+\x20\x20\x20
+2,WITHDRAWAL,10,0,50,2000,PENDING,\"Desc 2\"
+";
+        let reader = CsvReader::new(Cursor::new(csv_data)).skip_empty_rows(false);
+        let results: Vec<_> = Parser::<YPBankCsvRecord, _>::from_reader(reader).collect();
+
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[1], Err(CsvRecordError::InvalidData { line: 3, .. })));
+        assert_eq!(results[2].as_ref().unwrap().id, 2);
+    }
+
+    #[test]
+    fn overflowing_amount_is_reported_separately_from_invalid_value() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,99999999999999999999,1000,SUCCESS,\"Desc 1\"
+2,DEPOSIT,0,10,9223372036854775808,2000,SUCCESS,\"Desc 2\"
+3,DEPOSIT,0,10,1x0,3000,SUCCESS,\"Desc 3\"
+4,DEPOSIT,0,10,9223372036854775807,4000,SUCCESS,\"Desc 4\"
+";
+        let results: Vec<_> = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data)).collect();
+
+        assert!(matches!(&results[0], Err(CsvRecordError::FieldOverflow { line: 2, field: "AMOUNT", value, .. }) if value == "99999999999999999999"));
+        assert!(matches!(&results[1], Err(CsvRecordError::FieldOverflow { line: 3, field: "AMOUNT", .. })));
+        assert!(matches!(&results[2], Err(CsvRecordError::InvalidData { line: 4, .. })));
+        assert_eq!(results[3].as_ref().unwrap().amount(), i64::MAX as u64);
+    }
+
+    #[test]
+    fn currency_column_is_optional() {
+        let with_column = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION,CURRENCY
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc 1\",EUR
+2,DEPOSIT,0,10,100,2000,SUCCESS,\"Desc 2\",
+3,DEPOSIT,0,10,100,3000,SUCCESS,\"Desc 3\"
+";
+        let records: Vec<YPBankCsvRecord> = Parser::new(Cursor::new(with_column)).collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.iter().map(YPBankCsvRecord::currency).collect::<Vec<_>>(), vec![Some("EUR"), None, None]);
+
+        let without_column = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc 1\"
+";
+        let record = Parser::<YPBankCsvRecord, _>::new(Cursor::new(without_column)).next().unwrap().unwrap();
+        assert_eq!(record.currency(), None);
+    }
+
+    #[test]
+    fn malformed_currency_is_reported_as_field_error() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION,CURRENCY
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc 1\",usd
+";
+        let error = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data)).next().unwrap().unwrap_err();
+
+        assert!(matches!(error, CsvRecordError::InvalidData { line: 2, field: Some(ref field), .. } if field == "CURRENCY"));
+    }
+
+    #[test]
+    fn currency_is_written_only_when_present() {
+        let mut with_currency = sample_record();
+        with_currency.currency = Some("USD".to_string());
+
+        let output = String::from_utf8(serialize_to_vec(&[with_currency]).unwrap()).unwrap();
+        assert!(output.starts_with("TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION,CURRENCY\n"));
+        let read_back = Parser::<YPBankCsvRecord, _>::new(Cursor::new(output)).next().unwrap().unwrap();
+        assert_eq!(read_back.currency(), Some("USD"));
+
+        let output = String::from_utf8(serialize_to_vec(&[sample_record()]).unwrap()).unwrap();
+        assert!(!output.contains("CURRENCY"));
+    }
+
+    #[test]
+    fn currency_column_is_settled_by_header() {
+        let with_currency = |id, currency: &str| YPBankCsvRecord { id, currency: Some(currency.to_string()), ..sample_record() };
+
+        // заголовок без CURRENCY: запись с валютой отклоняется, а не пишется лишним столбцом
+        let mut output = Vec::new();
+        let mut serializer = Serializer::<YPBankCsvRecord, _>::new(&mut output);
+        serializer.push(&sample_record()).unwrap();
+        assert_eq!(serializer.push(&with_currency(2, "USD")).unwrap_err().kind(), ErrorKind::InvalidInput);
+
+        // заголовок с CURRENCY: записи без валюты получают пустой столбец
+        let records = vec![sample_record(), with_currency(2, "USD"), YPBankCsvRecord { id: 3, ..sample_record() }];
+        let mut output = Vec::new();
+        let mut serializer = Serializer::<YPBankCsvRecord, _>::new(&mut output).with_options(CurrencyColumn::Present);
+        serializer.serialize(records.iter().cloned()).unwrap();
+        drop(serializer);
+
+        let text = String::from_utf8(output.clone()).unwrap();
+        assert!(text.starts_with("TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION,CURRENCY\n"));
+        assert!(text.contains(",\"Initial account funding\",\"\"\n"));
+        assert_eq!(Parser::<YPBankCsvRecord, _>::new(Cursor::new(output)).into_vec().unwrap(), records);
+
+        // первая запись с валютой включает столбец для всего потока
+        let records = vec![with_currency(1, "EUR"), YPBankCsvRecord { id: 2, ..sample_record() }];
+        let bytes = serialize_to_vec(&records).unwrap();
+        assert_eq!(Parser::<YPBankCsvRecord, _>::new(Cursor::new(bytes)).into_vec().unwrap(), records);
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Payment {
+        #[serde(rename = "ID")]
+        id: u64,
+        #[serde(rename = "AMOUNT")]
+        amount: i64,
+    }
+
+    impl CsvReadable for Payment {}
+
+    #[test]
+    fn csv_readable_type_is_parsed_without_reader_code() {
+        let input = "ID,AMOUNT\n1,100\n2,x\n3,-5\n";
+
+        let results: Vec<_> = Parser::<Payment, _>::new(Cursor::new(input)).collect();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &Payment { id: 1, amount: 100 });
+        assert!(matches!(&results[1], Err(CsvRecordError::InvalidData { line: 3, field: Some(field), .. }) if field == "AMOUNT"));
+        assert_eq!(results[2].as_ref().unwrap(), &Payment { id: 3, amount: -5 });
+    }
+
+    #[test]
+    fn csv_readable_type_uses_reader_settings() {
+        let reader = CsvReader::new(Cursor::new("ID, AMOUNT\n1, 0x10\n")).hex_numbers(true);
+
+        let payments = Parser::<Payment, _>::from_reader(reader).into_vec().unwrap();
+
+        assert_eq!(payments, vec![Payment { id: 1, amount: 16 }]);
+    }
 }
@@ -0,0 +1,140 @@
+use crate::common::Transaction;
+use crate::{Parser, Readable};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+
+/// Какое из повторений транзакции с одинаковым `id` сохранять.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepPolicy {
+    /// Первое вхождение.
+    First,
+    /// Последнее вхождение.
+    Last,
+}
+
+/// Итератор транзакций, пропускающий записи с уже встречавшимся `id`.
+///
+/// Хранит только множество увиденных идентификаторов, сами записи не накапливаются.
+/// Ошибки чтения передаются без изменений.
+pub struct DedupParser<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read
+{
+    parser: Parser<TRecord, Source>,
+    seen: HashSet<u64>,
+}
+
+impl<TRecord, Source> Iterator for DedupParser<TRecord, Source>
+where
+    TRecord: Readable<Source> + Into<Transaction>,
+    Source: Read
+{
+    type Item = Result<Transaction, TRecord::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let transaction: Transaction = match self.parser.next()? {
+                Ok(record) => record.into(),
+                Err(e) => return Some(Err(e)),
+            };
+
+            if self.seen.insert(transaction.id) {
+                return Some(Ok(transaction));
+            }
+        }
+    }
+}
+
+impl<TRecord, Source> Parser<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read
+{
+    /// Оборачивает парсер в итератор транзакций без повторов по `id`; сохраняется первое вхождение.
+    pub fn dedup_by_id(self) -> DedupParser<TRecord, Source> {
+        DedupParser { parser: self, seen: HashSet::new() }
+    }
+}
+
+/// Удаляет из `records` транзакции с повторяющимся `id`, сохраняя порядок оставшихся.
+pub fn dedup(records: &mut Vec<Transaction>, keep: KeepPolicy) {
+    match keep {
+        KeepPolicy::First => {
+            let mut seen = HashSet::new();
+            records.retain(|transaction| seen.insert(transaction.id));
+        }
+        KeepPolicy::Last => {
+            let last_index: HashMap<u64, usize> = records
+                .iter()
+                .enumerate()
+                .map(|(index, transaction)| (transaction.id, index))
+                .collect();
+
+            let mut index = 0;
+            records.retain(|transaction| {
+                let keep = last_index[&transaction.id] == index;
+                index += 1;
+                keep
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::test_transaction;
+    use crate::YPBankCsvRecord;
+    use std::io::Cursor;
+
+    #[test]
+    fn dedup_keeps_first_occurrence() {
+        let mut records = vec![
+            Transaction { amount: 10, ..test_transaction(1) },
+            Transaction { amount: 20, ..test_transaction(2) },
+            Transaction { amount: 30, ..test_transaction(1) },
+            Transaction { amount: 40, ..test_transaction(3) },
+        ];
+
+        dedup(&mut records, KeepPolicy::First);
+
+        assert_eq!(records, vec![
+            Transaction { amount: 10, ..test_transaction(1) },
+            Transaction { amount: 20, ..test_transaction(2) },
+            Transaction { amount: 40, ..test_transaction(3) },
+        ]);
+    }
+
+    #[test]
+    fn dedup_keeps_last_occurrence() {
+        let mut records = vec![
+            Transaction { amount: 10, ..test_transaction(1) },
+            Transaction { amount: 20, ..test_transaction(2) },
+            Transaction { amount: 30, ..test_transaction(1) },
+            Transaction { amount: 40, ..test_transaction(3) },
+        ];
+
+        dedup(&mut records, KeepPolicy::Last);
+
+        assert_eq!(records, vec![
+            Transaction { amount: 20, ..test_transaction(2) },
+            Transaction { amount: 30, ..test_transaction(1) },
+            Transaction { amount: 40, ..test_transaction(3) },
+        ]);
+    }
+
+    #[test]
+    fn streaming_dedup_stores_only_seen_ids() {
+        let mut csv_data = String::from("TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n");
+        for i in 0..1000 {
+            csv_data.push_str(&format!("{},DEPOSIT,0,10,{},1000,SUCCESS,\"\"\n", i % 5, i + 1));
+        }
+
+        let mut parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data)).dedup_by_id();
+        let amounts: Vec<i64> = parser.by_ref().map(|r| r.unwrap().amount).collect();
+
+        assert_eq!(amounts, vec![1, 2, 3, 4, 5]);
+        assert_eq!(parser.seen.len(), 5);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::common::Transaction;
+use crate::compare::{diff_with, DiffOptions, Mismatch, TransactionDiff};
+use std::collections::BTreeMap;
+
+/// Структурированная разница между двумя наборами транзакций, сопоставленными по `id`.
+#[derive(Debug, Default, PartialEq)]
+pub struct DiffReport {
+    /// Транзакции, присутствующие только в новом наборе.
+    pub added: Vec<Transaction>,
+    /// Транзакции, присутствующие только в старом наборе.
+    pub removed: Vec<Transaction>,
+    /// Транзакции с одинаковым `id` и различающимися полями: старая (`left`), новая (`right`)
+    /// и значения каждого различающегося поля.
+    pub changed: Vec<Mismatch>,
+}
+
+impl DiffReport {
+    /// Сравнивает старый и новый наборы транзакций по `id`.
+    ///
+    /// Поля сравниваются точно (`DiffOptions::strict`) через `compare::diff_with`.
+    /// При повторяющихся `id` внутри набора учитывается последняя запись.
+    pub fn between(old: &[Transaction], new: &[Transaction]) -> Self {
+        diff_with(old, new, DiffOptions::strict()).into()
+    }
+
+    /// Возвращает `true`, если наборы совпадают.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Выводит разницу в стиле `git diff`, упорядочив строки по `id`.
+    ///
+    /// Добавленные записи помечаются `+`, удаленные — `-`, измененные выводятся парой строк
+    /// `-старая` / `+новая`. Каждая запись выводится одной строкой в порядке полей CSV.
+    pub fn to_unified_text(&self) -> String {
+        let mut lines: BTreeMap<u64, Vec<String>> = BTreeMap::new();
+
+        for transaction in &self.removed {
+            lines.entry(transaction.id).or_default().push(format!("-{}", format_line(transaction)));
+        }
+
+        for Mismatch { left, right, .. } in &self.changed {
+            let entry = lines.entry(left.id).or_default();
+            entry.push(format!("-{}", format_line(left)));
+            entry.push(format!("+{}", format_line(right)));
+        }
+
+        for transaction in &self.added {
+            lines.entry(transaction.id).or_default().push(format!("+{}", format_line(transaction)));
+        }
+
+        lines.into_values().flatten().map(|line| line + "\n").collect()
+    }
+}
+
+impl From<TransactionDiff> for DiffReport {
+    fn from(diff: TransactionDiff) -> Self {
+        Self {
+            added: diff.only_in_right,
+            removed: diff.only_in_left,
+            changed: diff.mismatched,
+        }
+    }
+}
+
+fn format_line(transaction: &Transaction) -> String {
+    format!(
+        "{},{},{},{},{},{},{},\"{}\"",
+        transaction.id,
+        transaction.transaction_type,
+        transaction.from_user_id,
+        transaction.to_user_id,
+        transaction.amount,
+        transaction.timestamp,
+        transaction.transaction_status,
+        transaction.description.replace('"', "\"\"")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::test_transaction;
+    use crate::FieldDiff;
+
+    #[test]
+    fn unified_text_groups_lines_by_id() {
+        let old = vec![
+            test_transaction(1),
+            Transaction { amount: 200, ..test_transaction(2) },
+            Transaction { amount: 400, ..test_transaction(4) },
+        ];
+        let new = vec![
+            test_transaction(1),
+            Transaction { amount: 250, ..test_transaction(2) },
+            Transaction { amount: 300, ..test_transaction(3) },
+        ];
+
+        let report = DiffReport::between(&old, &new);
+
+        assert_eq!(report.added, vec![Transaction { amount: 300, ..test_transaction(3) }]);
+        assert_eq!(report.removed, vec![Transaction { amount: 400, ..test_transaction(4) }]);
+        assert_eq!(report.changed.len(), 1);
+        assert_eq!((&report.changed[0].left, &report.changed[0].right), (&Transaction { amount: 200, ..test_transaction(2) }, &Transaction { amount: 250, ..test_transaction(2) }));
+        assert_eq!(
+            report.changed[0].fields,
+            vec![FieldDiff { field: "AMOUNT", left: "200".to_string(), right: "250".to_string() }]
+        );
+
+        let expected = "\
+-2,DEPOSIT,0,10,200,1002,SUCCESS,\"Desc 2\"
++2,DEPOSIT,0,10,250,1002,SUCCESS,\"Desc 2\"
++3,DEPOSIT,0,10,300,1003,SUCCESS,\"Desc 3\"
+-4,DEPOSIT,0,10,400,1004,SUCCESS,\"Desc 4\"
+";
+        assert_eq!(report.to_unified_text(), expected);
+    }
+
+    #[test]
+    fn identical_sets_produce_empty_text() {
+        let records = vec![test_transaction(1)];
+
+        let report = DiffReport::between(&records, &records);
+
+        assert!(report.is_empty());
+        assert_eq!(report.to_unified_text(), "");
+    }
+}
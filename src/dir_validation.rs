@@ -0,0 +1,223 @@
+use crate::common::Transaction;
+use crate::errors::ParseError;
+use crate::format::Format;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+/// Режим обработки ошибок при проверке каталога.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Остановиться на первой ошибке и вернуть её с указанием файла и номера записи.
+    Strict,
+    /// Проверить все файлы и собрать ошибки в отчет.
+    Lenient,
+}
+
+/// Результат проверки одного файла.
+#[derive(Debug)]
+pub struct FileReport {
+    /// Путь к файлу.
+    pub path: PathBuf,
+    /// Формат, по которому проверялся файл.
+    pub format: Format,
+    /// Количество успешно прочитанных записей.
+    pub records: usize,
+    /// Ошибки в виде (номер записи с нуля, ошибка).
+    pub failures: Vec<(usize, ParseError)>,
+}
+
+/// Сводный отчет о проверке каталога.
+#[derive(Debug, Default)]
+pub struct DirReport {
+    /// Отчеты по проверенным файлам в порядке обхода.
+    pub files: Vec<FileReport>,
+    /// Файлы, формат которых не удалось определить; они не открывались.
+    pub skipped: Vec<PathBuf>,
+}
+
+impl DirReport {
+    /// Возвращает `true`, если ни в одном файле не найдено ошибок.
+    pub fn is_ok(&self) -> bool {
+        self.files.iter().all(|file| file.failures.is_empty())
+    }
+}
+
+/// Рекурсивно проверяет все файлы с транзакциями в каталоге.
+///
+/// Формат определяется по расширению (`Format::from_path`); файлы с другим расширением не
+/// открываются и перечисляются в `DirReport::skipped`. Файлы обходятся в порядке имен.
+/// В режиме `Strict` чтение прекращается на первой ошибке, которая возвращается в виде
+/// (путь, номер записи с нуля, ошибка); в режиме `Lenient` — отчет по всем файлам.
+/// Ошибки доступа к каталогам и проверяемым файлам возвращаются как ошибка записи `0` в любом режиме.
+pub fn validate_dir(path: impl AsRef<Path>, policy: FailurePolicy) -> Result<DirReport, (PathBuf, usize, ParseError)> {
+    let mut files = Vec::new();
+    collect_files(path.as_ref(), &mut files)?;
+
+    let mut report = DirReport::default();
+
+    for file_path in files {
+        let Some(format) = Format::from_path(&file_path) else {
+            report.skipped.push(file_path);
+            continue;
+        };
+
+        let file = File::open(&file_path).map_err(|e| (file_path.clone(), 0, e.into()))?;
+        let file_report = validate_file(format.parser(file), file_path, format, policy)?;
+        report.files.push(file_report);
+    }
+
+    Ok(report)
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), (PathBuf, usize, ParseError)> {
+    let mut entries = fs::read_dir(dir)
+        .and_then(|entries| entries.map(|entry| entry.map(|e| e.path())).collect::<Result<Vec<_>, _>>())
+        .map_err(|e| (dir.to_path_buf(), 0, e.into()))?;
+
+    entries.sort();
+
+    for entry in entries {
+        if entry.is_dir() {
+            collect_files(&entry, files)?;
+        } else {
+            files.push(entry);
+        }
+    }
+
+    Ok(())
+}
+
+/// Читает записи файла; в режиме `Strict` возвращает первую ошибку, не читая дальше.
+fn validate_file<I>(parser: I, path: PathBuf, format: Format, policy: FailurePolicy) -> Result<FileReport, (PathBuf, usize, ParseError)>
+where
+    I: Iterator<Item = Result<Transaction, ParseError>>,
+{
+    let mut records = 0;
+    let mut failures = Vec::new();
+
+    for (index, record) in parser.enumerate() {
+        match record {
+            Ok(_) => records += 1,
+            Err(e) if policy == FailurePolicy::Strict => return Err((path, index, e)),
+            Err(e) => failures.push((index, e)),
+        }
+    }
+
+    Ok(FileReport { path, format, records, failures })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::test_transaction;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("ypbank_{}_{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn prepare_dir(name: &str) -> TempDir {
+        let dir = TempDir::new(name);
+
+        fs::write(dir.0.join("a_good.csv"), "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc 1\"
+2,WITHDRAWAL,10,0,50,2000,PENDING,\"Desc 2\"
+").unwrap();
+
+        fs::write(dir.0.join("b_bad.txt"), "\
+TX_ID: 1
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 100
+TIMESTAMP: 1
+STATUS: SUCCESS
+DESCRIPTION: \"Good\"
+
+TX_ID: 2
+AMOUNT: not_a_number
+").unwrap();
+
+        fs::write(dir.0.join("c_good.jsonl"), "\
+{\"TX_ID\":3,\"TX_TYPE\":\"DEPOSIT\",\"FROM_USER_ID\":0,\"TO_USER_ID\":10,\"AMOUNT\":100,\"TIMESTAMP\":3,\"STATUS\":\"SUCCESS\",\"DESCRIPTION\":\"Desc 3\"}
+").unwrap();
+
+        fs::write(dir.0.join("notes.md"), "ignored").unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn strict_policy_returns_first_failure_locator() {
+        let dir = prepare_dir("validate_dir_strict");
+
+        let (path, index, error) = validate_dir(&dir.0, FailurePolicy::Strict).unwrap_err();
+
+        assert_eq!(path, dir.0.join("b_bad.txt"));
+        assert_eq!(index, 1);
+        assert!(matches!(error, ParseError::Text(_)));
+    }
+
+    #[test]
+    fn lenient_policy_aggregates_failures_per_file() {
+        let dir = prepare_dir("validate_dir_lenient");
+
+        let report = validate_dir(&dir.0, FailurePolicy::Lenient).unwrap();
+
+        assert!(!report.is_ok());
+        assert_eq!(report.files.len(), 3);
+        assert_eq!(report.skipped, vec![dir.0.join("notes.md")]);
+
+        assert_eq!(report.files[0].path, dir.0.join("a_good.csv"));
+        assert_eq!(report.files[0].records, 2);
+        assert!(report.files[0].failures.is_empty());
+
+        assert_eq!(report.files[1].path, dir.0.join("b_bad.txt"));
+        assert_eq!(report.files[1].records, 1);
+        assert_eq!(report.files[1].failures.len(), 1);
+        assert_eq!(report.files[1].failures[0].0, 1);
+
+        assert_eq!(report.files[2].format, Format::Json);
+        assert_eq!(report.files[2].records, 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unreadable_file_with_unknown_extension_is_not_opened() {
+        let dir = TempDir::new("validate_dir_unknown");
+        // ссылка в никуда: открыть такой файл нельзя
+        std::os::unix::fs::symlink(dir.0.join("missing"), dir.0.join("README")).unwrap();
+        fs::write(dir.0.join("data.xml"), "<tx/>").unwrap();
+
+        let report = validate_dir(&dir.0, FailurePolicy::Strict).unwrap();
+
+        assert!(report.files.is_empty());
+        assert_eq!(report.skipped, vec![dir.0.join("README"), dir.0.join("data.xml")]);
+    }
+
+    #[test]
+    fn strict_policy_stops_reading_at_first_failure() {
+        let mut read = 0;
+        let good = || Ok(test_transaction(1));
+        let records = vec![Err(ParseError::from(std::io::Error::other("bad record"))), good(), good()];
+        let parser = records.into_iter().inspect(|_| read += 1);
+
+        let (path, index, _) = validate_file(parser, PathBuf::from("x.csv"), Format::Csv, FailurePolicy::Strict).unwrap_err();
+
+        assert_eq!((path, index), (PathBuf::from("x.csv"), 0));
+        assert_eq!(read, 1);
+    }
+}
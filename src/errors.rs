@@ -7,22 +7,73 @@ use crate::IsEofError;
 #[derive(Debug)]
 pub enum TextRecordError {
     /// Отсутствует двоеточие, разделяющее ключ и значение.
-    MissingColonAfterKey,
+    ///
+    /// `line` — номер строки с единицы, `record_index` — номер блока записи с нуля.
+    MissingColonAfterKey { line: usize, record_index: usize },
+    /// Перед двоеточием нет ключа (строка вида `: значение`).
+    EmptyKey { line: usize, record_index: usize },
+    /// Ключ `key` встречается в блоке повторно; `line` — строка повтора.
+    DuplicateKey { key: String, line: usize, record_index: usize },
     /// Ошибка ввода-вывода при чтении строки.
     ReadLineError(std::io::Error),
     /// Ошибка парсинга полей (например, неверный формат числа или даты).
-    ParseError { error: String },
+    ///
+    /// `line` указывает на строку поля с ошибкой, а если поле определить нельзя
+    /// (например, оно отсутствует) — на первую строку блока.
+    ParseError { error: serde::de::value::Error, line: usize, record_index: usize },
+    /// Числовое поле `field` записано цифрами, но его значение `value` не помещается в тип поля.
+    FieldOverflow { field: &'static str, value: String, line: usize, record_index: usize },
+    /// В блоке нет обязательного поля `field`; `line` — первая строка блока.
+    MissingField { field: &'static str, line: usize, record_index: usize },
+    /// Файл оборвался посреди блока: в последнем блоке нет обязательных полей `missing`;
+    /// `line` — первая строка блока.
+    IncompleteRecord { missing: Vec<String>, line: usize, record_index: usize },
+    /// Строка длиннее допустимого лимита; `bytes` — сколько байт строки уже прочитано.
+    LineTooLong { bytes: usize },
+    /// Строка не в UTF-8; `byte_offset` — смещение первого неверного байта от начала источника.
+    InvalidUtf8 { byte_offset: usize },
+    /// В блоке больше полей, чем допускает лимит; `line` — строка первого лишнего поля.
+    RecordTooLarge { fields: usize, line: usize, record_index: usize },
+    /// Отсутствует заголовок версии схемы `#!ypbank-txt vN`.
+    MissingVersion,
+    /// Версия схемы из заголовка не входит в список допустимых.
+    UnsupportedVersion { version: u32 },
     /// Достигнут конец файла.
     EndOfFile,
 }
 
 impl Display for TextRecordError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            TextRecordError::MissingColonAfterKey { line, .. } => write!(f, "missing colon after key at line {}", line),
+            TextRecordError::EmptyKey { line, .. } => write!(f, "empty key at line {}", line),
+            TextRecordError::DuplicateKey { key, line, .. } => write!(f, "duplicate key {} at line {}", key, line),
+            TextRecordError::ParseError { error, line, .. } => write!(f, "parse error at line {}: {}", line, error),
+            TextRecordError::FieldOverflow { field, value, line, .. } => {
+                write!(f, "value {} of field {} at line {} is out of range", value, field, line)
+            }
+            TextRecordError::MissingField { field, line, .. } => write!(f, "missing field {} in record at line {}", field, line),
+            TextRecordError::IncompleteRecord { missing, line, .. } => {
+                write!(f, "incomplete record at line {} at end of file, missing {}", line, missing.join(", "))
+            }
+            TextRecordError::RecordTooLarge { fields, line, .. } => {
+                write!(f, "record exceeds {} fields at line {}", fields, line)
+            }
+            TextRecordError::InvalidUtf8 { byte_offset } => write!(f, "invalid UTF-8 at byte {}", byte_offset),
+            _ => write!(f, "{:?}", self),
+        }
     }
 }
 
-impl Error for TextRecordError {}
+impl Error for TextRecordError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TextRecordError::ReadLineError(e) => Some(e),
+            TextRecordError::ParseError { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
 
 impl IsEofError for TextRecordError {
     fn is_eof(&self) -> bool {
@@ -30,15 +81,36 @@ impl IsEofError for TextRecordError {
     }
 }
 
-impl From<std::io::Error> for TextRecordError {
+/// Ошибки чтения строк построчных форматов.
+#[derive(Debug)]
+pub(crate) enum LineError {
+    /// Строка длиннее допустимого лимита; `bytes` — сколько байт строки уже прочитано.
+    TooLong { bytes: usize },
+    /// Строка не в UTF-8; `byte_offset` — смещение первого неверного байта от начала источника.
+    InvalidUtf8 { byte_offset: usize },
+    /// Ошибка ввода-вывода.
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for LineError {
     fn from(value: std::io::Error) -> Self {
-        TextRecordError::ReadLineError(value)
+        LineError::Io(value)
     }
 }
 
-impl From<serde::de::value::Error> for TextRecordError {
-    fn from(value: serde::de::value::Error) -> Self {
-        TextRecordError::ParseError { error: value.to_string() }
+impl From<LineError> for TextRecordError {
+    fn from(value: LineError) -> Self {
+        match value {
+            LineError::TooLong { bytes } => TextRecordError::LineTooLong { bytes },
+            LineError::InvalidUtf8 { byte_offset } => TextRecordError::InvalidUtf8 { byte_offset },
+            LineError::Io(e) => TextRecordError::ReadLineError(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for TextRecordError {
+    fn from(value: std::io::Error) -> Self {
+        TextRecordError::ReadLineError(value)
     }
 }
 
@@ -52,4 +124,558 @@ impl IsEofError for std::io::Error {
     fn is_eof(&self) -> bool {
         matches!(self.kind(), ErrorKind::UnexpectedEof)
     }
-}
\ No newline at end of file
+}
+
+/// Ошибки, возникающие при чтении CSV-записей.
+#[derive(Debug)]
+pub enum CsvRecordError {
+    /// Источник пуст: в нем нет ни заголовка, ни записей.
+    Empty,
+    /// Записи закончились.
+    Eof,
+    /// Строку не удалось разобрать.
+    ///
+    /// `line` — номер строки с единицы, `record` — номер записи (строка заголовка имеет номер 0),
+    /// `field` — имя поля, значение которого не удалось разобрать, если оно известно.
+    InvalidData { line: u64, record: Option<u64>, field: Option<String>, source: Box<dyn Error + Send + Sync> },
+    /// Числовое поле `field` записано цифрами, но его значение `value` не помещается в тип поля.
+    FieldOverflow { line: u64, record: Option<u64>, field: &'static str, value: String },
+    /// Ошибка ввода-вывода при чтении источника.
+    Io(std::io::Error),
+}
+
+impl Display for CsvRecordError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsvRecordError::Empty => write!(f, "CSV source is empty"),
+            CsvRecordError::Eof => write!(f, "end of CSV"),
+            CsvRecordError::InvalidData { line, field: Some(field), source, .. } => {
+                write!(f, "invalid CSV record at line {}, field {}: {}", line, field, source)
+            }
+            CsvRecordError::InvalidData { line, field: None, source, .. } => write!(f, "invalid CSV record at line {}: {}", line, source),
+            CsvRecordError::FieldOverflow { line, field, value, .. } => {
+                write!(f, "invalid CSV record at line {}, field {}: value {} is out of range", line, field, value)
+            }
+            CsvRecordError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for CsvRecordError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CsvRecordError::InvalidData { source, .. } => Some(source.as_ref()),
+            CsvRecordError::Io(e) => Some(e),
+            CsvRecordError::Empty | CsvRecordError::Eof | CsvRecordError::FieldOverflow { .. } => None,
+        }
+    }
+}
+
+impl IsEofError for CsvRecordError {
+    fn is_eof(&self) -> bool {
+        matches!(self, CsvRecordError::Empty | CsvRecordError::Eof)
+    }
+}
+
+impl From<std::io::Error> for CsvRecordError {
+    fn from(value: std::io::Error) -> Self {
+        CsvRecordError::Io(value)
+    }
+}
+
+impl From<csv::Error> for CsvRecordError {
+    fn from(value: csv::Error) -> Self {
+        if value.is_io_error() {
+            return match value.into_kind() {
+                csv::ErrorKind::Io(e) => CsvRecordError::Io(e),
+                _ => unreachable!("is_io_error guarantees an I/O error kind"),
+            };
+        }
+
+        let line = value.position().map_or(0, csv::Position::line);
+        let record = value.position().map(csv::Position::record);
+        CsvRecordError::InvalidData { line, record, field: None, source: Box::new(value) }
+    }
+}
+
+impl From<CsvRecordError> for std::io::Error {
+    fn from(value: CsvRecordError) -> Self {
+        match value {
+            CsvRecordError::Io(e) => e,
+            CsvRecordError::Empty | CsvRecordError::Eof => std::io::Error::new(ErrorKind::UnexpectedEof, value),
+            CsvRecordError::InvalidData { .. } | CsvRecordError::FieldOverflow { .. } => std::io::Error::new(ErrorKind::InvalidData, value),
+        }
+    }
+}
+
+/// Переполнение при суммировании сумм транзакций.
+#[derive(Debug, PartialEq)]
+pub struct AmountOverflowError {
+    /// Идентификатор транзакции, на которой произошло переполнение.
+    pub id: u64,
+}
+
+impl Display for AmountOverflowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Amount sum overflow at transaction {}", self.id)
+    }
+}
+
+impl Error for AmountOverflowError {}
+
+/// Временная метка вне диапазона, представимого `chrono::DateTime<Utc>` или `u64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampOutOfRange {
+    /// Значение метки в миллисекундах от эпохи Unix (для дат до эпохи — отрицательное).
+    pub millis: i128,
+}
+
+impl Display for TimestampOutOfRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Timestamp {} ms is out of range", self.millis)
+    }
+}
+
+impl Error for TimestampOutOfRange {}
+
+/// Ошибка с контекстной меткой источника (например, именем файла).
+#[derive(Debug)]
+pub struct LabeledError<E> {
+    /// Метка источника, в котором произошла ошибка.
+    pub label: String,
+    /// Исходная ошибка.
+    pub source: E,
+}
+
+impl<E: Display> Display for LabeledError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.label, self.source)
+    }
+}
+
+impl<E: Error + 'static> Error for LabeledError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl<E: Into<std::io::Error>> From<LabeledError<E>> for std::io::Error {
+    fn from(value: LabeledError<E>) -> Self {
+        let source: std::io::Error = value.source.into();
+        std::io::Error::new(source.kind(), format!("{}: {}", value.label, source))
+    }
+}
+
+/// Ошибка проверки цепочки контрольных сумм.
+#[derive(Debug)]
+pub enum ChainError {
+    /// Ошибка ввода-вывода или разбора CSV.
+    Io(std::io::Error),
+    /// Звено цепочки не совпало; `index` — номер записи с нуля.
+    Broken { index: usize },
+}
+
+impl Display for ChainError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChainError::Io(e) => write!(f, "{}", e),
+            ChainError::Broken { index } => write!(f, "Checksum chain is broken at record {}", index),
+        }
+    }
+}
+
+impl Error for ChainError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ChainError::Io(e) => Some(e),
+            ChainError::Broken { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ChainError {
+    fn from(value: std::io::Error) -> Self {
+        ChainError::Io(value)
+    }
+}
+
+/// Ошибка потокового преобразования между форматами.
+#[derive(Debug)]
+pub enum ConvertError {
+    /// Не удалось прочитать запись; `index` — номер записи с нуля.
+    Read { index: usize, source: std::io::Error },
+    /// Не удалось записать заголовок целевого формата.
+    Header(std::io::Error),
+    /// Не удалось записать запись; `index` — номер записи с нуля.
+    Write { index: usize, source: std::io::Error },
+}
+
+impl Display for ConvertError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConvertError::Read { index, source } => write!(f, "Failed to read record {}: {}", index, source),
+            ConvertError::Header(e) => write!(f, "Failed to write header: {}", e),
+            ConvertError::Write { index, source } => write!(f, "Failed to write record {}: {}", index, source),
+        }
+    }
+}
+
+impl Error for ConvertError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConvertError::Read { source, .. } | ConvertError::Write { source, .. } => Some(source),
+            ConvertError::Header(e) => Some(e),
+        }
+    }
+}
+
+/// Ошибка слияния отсортированных по `timestamp` потоков.
+#[derive(Debug)]
+pub enum MergeError<E> {
+    /// Ошибка чтения источника.
+    Source(E),
+    /// Источник `source_index` не отсортирован: метка транзакции `id` меньше предыдущей.
+    OutOfOrder { source_index: usize, id: u64, timestamp: u64, previous: u64 },
+}
+
+impl<E: Display> Display for MergeError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeError::Source(e) => write!(f, "{}", e),
+            MergeError::OutOfOrder { source_index, id, timestamp, previous } => write!(
+                f,
+                "Source {} is not sorted by timestamp: transaction {} has {} after {}",
+                source_index, id, timestamp, previous
+            ),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for MergeError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MergeError::Source(e) => Some(e),
+            MergeError::OutOfOrder { .. } => None,
+        }
+    }
+}
+
+/// Ошибка проверки порядка временных меток в файле.
+#[derive(Debug)]
+pub enum OrderError<E> {
+    /// Не удалось прочитать запись; `index` — номер записи с нуля.
+    Read { index: usize, source: E },
+    /// Метка записи `index` меньше метки предыдущей записи.
+    OutOfOrder { index: usize, previous: u64, timestamp: u64 },
+}
+
+impl<E: Display> Display for OrderError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderError::Read { index, source } => write!(f, "Failed to read record {}: {}", index, source),
+            OrderError::OutOfOrder { index, previous, timestamp } => {
+                write!(f, "Record {} has timestamp {} earlier than previous {}", index, timestamp, previous)
+            }
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for OrderError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            OrderError::Read { source, .. } => Some(source),
+            OrderError::OutOfOrder { .. } => None,
+        }
+    }
+}
+
+/// Ошибка чтения с одновременной записью копии (`Parser::tee`).
+#[derive(Debug)]
+pub enum TeeError<R, W> {
+    /// Не удалось прочитать запись.
+    Read(R),
+    /// Не удалось записать копию прочитанной записи.
+    Write(W),
+}
+
+impl<R: Display, W: Display> Display for TeeError<R, W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TeeError::Read(e) => write!(f, "Failed to read record: {}", e),
+            TeeError::Write(e) => write!(f, "Failed to write record copy: {}", e),
+        }
+    }
+}
+
+impl<R: Error + 'static, W: Error + 'static> Error for TeeError<R, W> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TeeError::Read(e) => Some(e),
+            TeeError::Write(e) => Some(e),
+        }
+    }
+}
+
+/// Ошибка ограниченного чтения (`Parser::take_limited`).
+#[derive(Debug)]
+pub enum LimitError<E> {
+    /// В источнике больше записей, чем допускает ограничение.
+    Exceeded { limit: usize },
+    /// Не удалось прочитать запись.
+    Read(E),
+}
+
+impl<E: Display> Display for LimitError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitError::Exceeded { limit } => write!(f, "Source has more than {} records", limit),
+            LimitError::Read(e) => write!(f, "Failed to read record: {}", e),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for LimitError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LimitError::Exceeded { .. } => None,
+            LimitError::Read(e) => Some(e),
+        }
+    }
+}
+
+/// Идентификаторы транзакций нарушают порядок по возрастанию.
+#[derive(Debug, PartialEq)]
+pub struct UnsortedIdError {
+    /// Идентификатор предыдущей записи.
+    pub previous: u64,
+    /// Идентификатор, оказавшийся меньше предыдущего.
+    pub id: u64,
+}
+
+impl Display for UnsortedIdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Transaction id {} follows greater id {}", self.id, self.previous)
+    }
+}
+
+impl Error for UnsortedIdError {}
+
+/// Сумма сумм транзакций не совпала с ожидаемой контрольной суммой.
+#[derive(Debug, PartialEq)]
+pub struct ControlTotalMismatch {
+    /// Ожидаемая контрольная сумма.
+    pub expected: u64,
+    /// Фактическая сумма по потоку.
+    pub actual: i128,
+}
+
+impl Display for ControlTotalMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Control total mismatch: expected {}, actual {}", self.expected, self.actual)
+    }
+}
+
+impl Error for ControlTotalMismatch {}
+
+/// Описание транзакции содержит недопустимые управляющие символы.
+#[derive(Debug, PartialEq)]
+pub struct NonPrintableDescription {
+    /// Идентификатор транзакции с некорректным описанием.
+    pub id: u64,
+}
+
+impl Display for NonPrintableDescription {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Description of transaction {} contains control characters", self.id)
+    }
+}
+
+impl Error for NonPrintableDescription {}
+
+/// Числовой код не соответствует ни одному варианту перечисления.
+#[derive(Debug, PartialEq)]
+pub struct InvalidEnumValue {
+    /// Название перечисления в сообщении об ошибке (например, `transaction type`).
+    pub enum_name: &'static str,
+    /// Недопустимый код.
+    pub value: u8,
+}
+
+impl Display for InvalidEnumValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid {} value: {}", self.enum_name, self.value)
+    }
+}
+
+impl Error for InvalidEnumValue {}
+
+impl From<InvalidEnumValue> for std::io::Error {
+    fn from(value: InvalidEnumValue) -> Self {
+        std::io::Error::new(ErrorKind::InvalidData, value)
+    }
+}
+
+/// Строка не соответствует ни одному варианту перечисления.
+#[derive(Debug, PartialEq)]
+pub struct ParseEnumError {
+    /// Имя перечисления (например, `TransactionType`).
+    pub enum_name: &'static str,
+    /// Исходная строка.
+    pub input: String,
+    /// Допустимые значения.
+    pub expected: &'static [&'static str],
+}
+
+impl Display for ParseEnumError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid {} \"{}\", expected one of {}", self.enum_name, self.input, self.expected.join(", "))
+    }
+}
+
+impl Error for ParseEnumError {}
+
+/// Вид ограничения `ResourceBudget`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetLimit {
+    /// Превышен объем прочитанных байтов.
+    Bytes,
+    /// Превышено время разбора.
+    Duration,
+}
+
+/// Превышение бюджета ресурсов при разборе.
+#[derive(Debug, PartialEq)]
+pub struct BudgetExceeded {
+    /// Ограничение, которое было превышено.
+    pub which: BudgetLimit,
+}
+
+impl Display for BudgetExceeded {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.which {
+            BudgetLimit::Bytes => write!(f, "Byte budget exceeded"),
+            BudgetLimit::Duration => write!(f, "Time budget exceeded"),
+        }
+    }
+}
+
+impl Error for BudgetExceeded {}
+
+/// Ошибка чтения с бюджетом ресурсов (`BudgetedParser`).
+#[derive(Debug)]
+pub enum BudgetError<E> {
+    /// Превышен бюджет; разбор прерван.
+    Exceeded(BudgetExceeded),
+    /// Не удалось прочитать запись.
+    Read(E),
+}
+
+impl<E: Display> Display for BudgetError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetError::Exceeded(e) => write!(f, "{}", e),
+            BudgetError::Read(e) => write!(f, "Failed to read record: {}", e),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for BudgetError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BudgetError::Exceeded(e) => Some(e),
+            BudgetError::Read(e) => Some(e),
+        }
+    }
+}
+
+/// Нарушение бизнес-правила транзакции.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// У пополнения (`DEPOSIT`) указан отправитель.
+    UnexpectedSender { id: u64, from_user_id: u64 },
+    /// У снятия (`WITHDRAWAL`) указан получатель.
+    UnexpectedReceiver { id: u64, to_user_id: u64 },
+    /// У перевода (`TRANSFER`) не указан отправитель или получатель.
+    MissingTransferParty { id: u64 },
+    /// Перевод (`TRANSFER`) самому себе.
+    SelfTransfer { id: u64, user_id: u64 },
+    /// Сумма не положительна.
+    NonPositiveAmount { id: u64, amount: i64 },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::UnexpectedSender { id, from_user_id } => {
+                write!(f, "Deposit {} has sender {}, expected 0", id, from_user_id)
+            }
+            ValidationError::UnexpectedReceiver { id, to_user_id } => {
+                write!(f, "Withdrawal {} has receiver {}, expected 0", id, to_user_id)
+            }
+            ValidationError::MissingTransferParty { id } => write!(f, "Transfer {} has no sender or receiver", id),
+            ValidationError::SelfTransfer { id, user_id } => write!(f, "Transfer {} sends user {} money to itself", id, user_id),
+            ValidationError::NonPositiveAmount { id, amount } => write!(f, "Transaction {} has non-positive amount {}", id, amount),
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Ошибка разбора записи любого из поддерживаемых форматов.
+#[derive(Debug)]
+pub enum ParseError {
+    /// Ошибка ввода-вывода или разбора бинарного формата.
+    Io(std::io::Error),
+    /// Ошибка разбора CSV-формата.
+    Csv(CsvRecordError),
+    /// Ошибка разбора текстового формата.
+    Text(TextRecordError),
+    /// Запись разобрана, но нарушает бизнес-правила.
+    Validation(ValidationError),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "{}", e),
+            ParseError::Csv(e) => write!(f, "{}", e),
+            ParseError::Text(e) => write!(f, "{}", e),
+            ParseError::Validation(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Io(e) => Some(e),
+            ParseError::Csv(e) => Some(e),
+            ParseError::Text(e) => Some(e),
+            ParseError::Validation(e) => Some(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for ParseError {
+    fn from(value: std::io::Error) -> Self {
+        ParseError::Io(value)
+    }
+}
+
+impl From<CsvRecordError> for ParseError {
+    fn from(value: CsvRecordError) -> Self {
+        ParseError::Csv(value)
+    }
+}
+
+impl From<TextRecordError> for ParseError {
+    fn from(value: TextRecordError) -> Self {
+        ParseError::Text(value)
+    }
+}
+
+impl From<ValidationError> for ParseError {
+    fn from(value: ValidationError) -> Self {
+        ParseError::Validation(value)
+    }
+}
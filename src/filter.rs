@@ -0,0 +1,142 @@
+use crate::common::{Transaction, TransactionStatus, TransactionType};
+use crate::{Parser, Readable};
+use std::io::Read;
+
+/// Условие отбора транзакций.
+#[derive(Debug, Clone, Copy)]
+enum TransactionFilter {
+    Type(TransactionType),
+    Status(TransactionStatus),
+    User(u64),
+}
+
+impl TransactionFilter {
+    fn matches(&self, transaction: &Transaction) -> bool {
+        match *self {
+            TransactionFilter::Type(transaction_type) => transaction.transaction_type == transaction_type,
+            TransactionFilter::Status(status) => transaction.transaction_status == status,
+            TransactionFilter::User(user_id) => transaction.from_user_id == user_id || transaction.to_user_id == user_id,
+        }
+    }
+}
+
+/// Итератор транзакций, пропускающий записи, не подходящие под условие.
+///
+/// Ошибки чтения передаются без изменений. Фильтры можно сочетать:
+/// `parser.filter_status(TransactionStatus::Failure).filter_user(42)`.
+pub struct Filtered<I> {
+    inner: I,
+    filter: TransactionFilter,
+}
+
+impl<I, T, E> Iterator for Filtered<I>
+where
+    I: Iterator<Item = Result<T, E>>,
+    T: Into<Transaction>,
+{
+    type Item = Result<Transaction, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.inner.next()? {
+                Ok(record) => {
+                    let transaction = record.into();
+                    if self.filter.matches(&transaction) {
+                        return Some(Ok(transaction));
+                    }
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+impl<I> Filtered<I> {
+    /// Оставляет только транзакции типа `transaction_type`.
+    pub fn filter_type(self, transaction_type: TransactionType) -> Filtered<Self> {
+        Filtered { inner: self, filter: TransactionFilter::Type(transaction_type) }
+    }
+
+    /// Оставляет только транзакции со статусом `status`.
+    pub fn filter_status(self, status: TransactionStatus) -> Filtered<Self> {
+        Filtered { inner: self, filter: TransactionFilter::Status(status) }
+    }
+
+    /// Оставляет только транзакции, в которых `user_id` — отправитель или получатель.
+    pub fn filter_user(self, user_id: u64) -> Filtered<Self> {
+        Filtered { inner: self, filter: TransactionFilter::User(user_id) }
+    }
+}
+
+impl<TRecord, Source> Parser<TRecord, Source>
+where
+    TRecord: Readable<Source> + Into<Transaction>,
+    Source: Read
+{
+    /// Оставляет только транзакции типа `transaction_type`.
+    pub fn filter_type(self, transaction_type: TransactionType) -> Filtered<Self> {
+        Filtered { inner: self, filter: TransactionFilter::Type(transaction_type) }
+    }
+
+    /// Оставляет только транзакции со статусом `status`.
+    pub fn filter_status(self, status: TransactionStatus) -> Filtered<Self> {
+        Filtered { inner: self, filter: TransactionFilter::Status(status) }
+    }
+
+    /// Оставляет только транзакции, в которых `user_id` — отправитель или получатель.
+    pub fn filter_user(self, user_id: u64) -> Filtered<Self> {
+        Filtered { inner: self, filter: TransactionFilter::User(user_id) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::YPBankCsvRecord;
+    use std::io::Cursor;
+
+    const CSV_DATA: &str = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,TRANSFER,42,10,100,1000,FAILURE,\"\"
+2,TRANSFER,10,42,100,2000,SUCCESS,\"\"
+3,BROKEN,42,10,100,3000,FAILURE,\"\"
+4,DEPOSIT,0,42,100,4000,FAILURE,\"\"
+5,TRANSFER,10,20,100,5000,FAILURE,\"\"
+";
+
+    #[test]
+    fn filters_compose_and_pass_errors_through() {
+        let mut parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(CSV_DATA))
+            .filter_status(TransactionStatus::Failure)
+            .filter_user(42)
+            .filter_type(TransactionType::Transfer);
+
+        assert_eq!(parser.next().unwrap().unwrap().id, 1);
+        assert!(parser.next().unwrap().is_err());
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn filtering_is_lazy() {
+        let mut pulled = 0;
+        let source = std::iter::from_fn(|| {
+            pulled += 1;
+            Some(Ok::<_, ()>(Transaction {
+                id: pulled,
+                transaction_type: TransactionType::Deposit,
+                from_user_id: pulled,
+                to_user_id: 0,
+                amount: 1,
+                timestamp: 0,
+                transaction_status: TransactionStatus::Success,
+                description: String::new(),
+                currency: None,
+            }))
+        });
+
+        let first = Filtered { inner: source, filter: TransactionFilter::User(3) }.next();
+
+        assert_eq!(first.unwrap().unwrap().id, 3);
+        assert_eq!(pulled, 3);
+    }
+}
@@ -0,0 +1,446 @@
+use crate::common::{SentinelRendering, Transaction};
+use crate::{Readable, Writable};
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::str::FromStr;
+
+/// Положение поля в строке фиксированной ширины: смещение и ширина в байтах.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedColumn {
+    /// Смещение первого байта столбца от начала строки.
+    pub offset: usize,
+    /// Ширина столбца в байтах.
+    pub width: usize,
+}
+
+impl FixedColumn {
+    /// Создает столбец с заданными смещением и шириной.
+    pub const fn new(offset: usize, width: usize) -> Self {
+        Self { offset, width }
+    }
+
+    fn end(&self) -> usize {
+        self.offset + self.width
+    }
+}
+
+/// Раскладка столбцов формата фиксированной ширины.
+///
+/// Столбцы могут идти в любом порядке и с промежутками, но не должны пересекаться и не могут
+/// быть нулевой ширины; раскладку с такой проверкой создает `FixedLayout::new`. `Default` задает раскладку шириной 180 байт: числа по 20 знаков, тип и статус по 10,
+/// описание — последние 60. Для чтения раскладка передается в `FixedReader::with_layout`,
+/// для записи — в `Serializer::with_options`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedLayout {
+    /// Столбец `TX_ID`.
+    pub id: FixedColumn,
+    /// Столбец `TX_TYPE`.
+    pub transaction_type: FixedColumn,
+    /// Столбец `FROM_USER_ID`.
+    pub from_user_id: FixedColumn,
+    /// Столбец `TO_USER_ID`.
+    pub to_user_id: FixedColumn,
+    /// Столбец `AMOUNT`.
+    pub amount: FixedColumn,
+    /// Столбец `TIMESTAMP`.
+    pub timestamp: FixedColumn,
+    /// Столбец `STATUS`.
+    pub transaction_status: FixedColumn,
+    /// Столбец `DESCRIPTION`; при чтении может быть обрезан концом строки.
+    pub description: FixedColumn,
+}
+
+impl Default for FixedLayout {
+    fn default() -> Self {
+        Self {
+            id: FixedColumn::new(0, 20),
+            transaction_type: FixedColumn::new(20, 10),
+            from_user_id: FixedColumn::new(30, 20),
+            to_user_id: FixedColumn::new(50, 20),
+            amount: FixedColumn::new(70, 20),
+            timestamp: FixedColumn::new(90, 20),
+            transaction_status: FixedColumn::new(110, 10),
+            description: FixedColumn::new(120, 60),
+        }
+    }
+}
+
+impl FixedLayout {
+    /// Создает раскладку из столбцов в порядке `TX_ID`, `TX_TYPE`, `FROM_USER_ID`, `TO_USER_ID`,
+    /// `AMOUNT`, `TIMESTAMP`, `STATUS`, `DESCRIPTION`.
+    ///
+    /// Столбец нулевой ширины или пересечение двух столбцов — ошибка `InvalidInput`.
+    pub fn new(columns: [FixedColumn; 8]) -> Result<Self, Error> {
+        let [id, transaction_type, from_user_id, to_user_id, amount, timestamp, transaction_status, description] = columns;
+        let layout = Self { id, transaction_type, from_user_id, to_user_id, amount, timestamp, transaction_status, description };
+
+        let columns = layout.columns();
+        for (i, (name, column)) in columns.iter().enumerate() {
+            if column.width == 0 {
+                return Err(Error::new(ErrorKind::InvalidInput, format!("Field {} has zero width", name)));
+            }
+
+            for (other_name, other) in &columns[..i] {
+                if column.offset < other.end() && other.offset < column.end() {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "Field {} at bytes {}..{} overlaps field {} at bytes {}..{}",
+                            name, column.offset, column.end(), other_name, other.offset, other.end()
+                        ),
+                    ));
+                }
+            }
+        }
+
+        Ok(layout)
+    }
+
+    /// Ширина строки: конец самого правого столбца.
+    pub fn line_width(&self) -> usize {
+        self.columns().iter().map(|(_, column)| column.end()).max().unwrap_or(0)
+    }
+
+    fn columns(&self) -> [(&'static str, FixedColumn); 8] {
+        [
+            ("TX_ID", self.id),
+            ("TX_TYPE", self.transaction_type),
+            ("FROM_USER_ID", self.from_user_id),
+            ("TO_USER_ID", self.to_user_id),
+            ("AMOUNT", self.amount),
+            ("TIMESTAMP", self.timestamp),
+            ("STATUS", self.transaction_status),
+            ("DESCRIPTION", self.description),
+        ]
+    }
+}
+
+/// Запись транзакции в формате фиксированной ширины: одна строка на запись,
+/// каждое поле занимает столбец, заданный `FixedLayout`.
+///
+/// Числа выравниваются по правому краю, тип, статус и описание — по левому; при чтении
+/// отступы отбрасываются. Строка, в которой конец описания обрезан вместе с пробелами,
+/// допустима, но остальные столбцы должны присутствовать целиком. Пустые строки пропускаются.
+/// Валюта в раскладке не предусмотрена и при записи отбрасывается.
+#[derive(Debug, Clone, PartialEq)]
+pub struct YPBankFixedRecord(Transaction);
+
+impl From<Transaction> for YPBankFixedRecord {
+    fn from(value: Transaction) -> Self {
+        Self(value)
+    }
+}
+
+impl From<YPBankFixedRecord> for Transaction {
+    fn from(value: YPBankFixedRecord) -> Self {
+        value.0
+    }
+}
+
+impl YPBankFixedRecord {
+    /// Разбирает одну строку (без перевода строки) по раскладке `layout`.
+    pub fn parse_line(line: &str, layout: &FixedLayout) -> Result<Self, Error> {
+        let field = |name: &str, column: FixedColumn| -> Result<&str, Error> {
+            line.get(column.offset..column.end()).map(str::trim).ok_or_else(|| {
+                invalid_data(format!("Field {} at bytes {}..{} is out of line of length {}", name, column.offset, column.end(), line.len()))
+            })
+        };
+
+        // концевые пробелы описания часто срезаются редакторами, поэтому короткий столбец допустим
+        let description = line
+            .get(layout.description.offset.min(line.len())..layout.description.end().min(line.len()))
+            .ok_or_else(|| invalid_data("DESCRIPTION does not start at a character boundary".to_string()))?
+            .trim_end();
+
+        Ok(Self(Transaction {
+            id: parse_field("TX_ID", field("TX_ID", layout.id)?)?,
+            transaction_type: parse_field("TX_TYPE", field("TX_TYPE", layout.transaction_type)?)?,
+            from_user_id: parse_field("FROM_USER_ID", field("FROM_USER_ID", layout.from_user_id)?)?,
+            to_user_id: parse_field("TO_USER_ID", field("TO_USER_ID", layout.to_user_id)?)?,
+            amount: parse_field("AMOUNT", field("AMOUNT", layout.amount)?)?,
+            timestamp: parse_field("TIMESTAMP", field("TIMESTAMP", layout.timestamp)?)?,
+            transaction_status: parse_field("STATUS", field("STATUS", layout.transaction_status)?)?,
+            description: description.to_string(),
+            currency: None,
+        }))
+    }
+}
+
+fn invalid_data(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn parse_field<T: FromStr>(name: &str, value: &str) -> Result<T, Error> {
+    value.parse().map_err(|_| invalid_data(format!("Invalid {} value {:?}", name, value)))
+}
+
+/// Вписывает `value` в столбец `column`: числа прижимаются вправо, текст — влево.
+fn place(line: &mut [u8], name: &str, column: FixedColumn, value: &str, align_right: bool) -> Result<(), Error> {
+    if value.len() > column.width || value.contains(['\n', '\r']) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} value {:?} does not fit into {} bytes", name, value, column.width),
+        ));
+    }
+
+    let start = if align_right { column.end() - value.len() } else { column.offset };
+    line[start..start + value.len()].copy_from_slice(value.as_bytes());
+
+    Ok(())
+}
+
+/// Читатель формата фиксированной ширины.
+///
+/// Создается автоматически в `Parser::new` с раскладкой по умолчанию; для другой раскладки
+/// его можно собрать через `with_layout` и передать в `Parser::from_reader`.
+pub struct FixedReader<R> {
+    inner: BufReader<R>,
+    layout: FixedLayout,
+    line_number: usize,
+}
+
+impl<R: Read> FixedReader<R> {
+    /// Создает читатель с раскладкой по умолчанию.
+    pub fn new(source: R) -> Self {
+        Self::with_capacity(source, crate::DEFAULT_BUFFER_CAPACITY)
+    }
+
+    /// Создает читатель с раскладкой по умолчанию и буфером размером `capacity` байт.
+    pub fn with_capacity(source: R, capacity: usize) -> Self {
+        Self::build(source, capacity, FixedLayout::default())
+    }
+
+    /// Создает читатель с раскладкой `layout`.
+    pub fn with_layout(source: R, layout: FixedLayout) -> Self {
+        Self::build(source, crate::DEFAULT_BUFFER_CAPACITY, layout)
+    }
+
+    fn build(source: R, capacity: usize, layout: FixedLayout) -> Self {
+        Self {
+            inner: BufReader::with_capacity(capacity, source),
+            layout,
+            line_number: 0,
+        }
+    }
+}
+
+impl<R: Read> Readable<R> for YPBankFixedRecord {
+    type Reader = FixedReader<R>;
+    type Error = Error;
+
+    fn build_reader(source: R) -> Self::Reader {
+        FixedReader::new(source)
+    }
+
+    fn build_reader_with_capacity(source: R, capacity: usize) -> Self::Reader {
+        FixedReader::with_capacity(source, capacity)
+    }
+
+    fn rewind(reader: &mut Self::Reader) -> Result<(), Error>
+    where
+        R: Seek
+    {
+        reader.inner.seek(SeekFrom::Start(0))?;
+        reader.line_number = 0;
+        Ok(())
+    }
+
+    fn read(reader: &mut Self::Reader) -> Result<Self, Error> {
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            if reader.inner.read_line(&mut line)? == 0 {
+                return Err(Error::new(ErrorKind::UnexpectedEof, "End of fixed-width lines"));
+            }
+            reader.line_number += 1;
+
+            if !line.trim().is_empty() {
+                break;
+            }
+        }
+
+        let line = line.trim_end_matches(['\n', '\r']);
+        Self::parse_line(line, &reader.layout)
+            .map_err(|e| Error::new(e.kind(), format!("Line {}: {}", reader.line_number, e)))
+    }
+
+    fn is_recoverable(error: &Error) -> bool {
+        // строка с ошибкой уже прочитана целиком
+        error.kind() == ErrorKind::InvalidData
+    }
+}
+
+impl Writable for YPBankFixedRecord {
+    type Error = Error;
+    type Options = FixedLayout;
+
+    fn write_header<W: Write>(_: &mut W) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.write_rendered(writer, &SentinelRendering::Zero, &FixedLayout::default())
+    }
+
+    /// Записывает запись строкой по раскладке `layout`.
+    ///
+    /// Значение, не помещающееся в свой столбец (в том числе слишком длинное описание),
+    /// приводит к ошибке `InvalidInput`; в поток при этом ничего не пишется.
+    fn write_rendered<W: Write>(&self, writer: &mut W, _sentinel: &SentinelRendering, layout: &FixedLayout) -> Result<(), Error> {
+        let transaction = &self.0;
+        let mut line = vec![b' '; layout.line_width()];
+
+        place(&mut line, "TX_ID", layout.id, &transaction.id.to_string(), true)?;
+        place(&mut line, "TX_TYPE", layout.transaction_type, &transaction.transaction_type.to_string(), false)?;
+        place(&mut line, "FROM_USER_ID", layout.from_user_id, &transaction.from_user_id.to_string(), true)?;
+        place(&mut line, "TO_USER_ID", layout.to_user_id, &transaction.to_user_id.to_string(), true)?;
+        place(&mut line, "AMOUNT", layout.amount, &transaction.amount.to_string(), true)?;
+        place(&mut line, "TIMESTAMP", layout.timestamp, &transaction.timestamp.to_string(), true)?;
+        place(&mut line, "STATUS", layout.transaction_status, &transaction.transaction_status.to_string(), false)?;
+        place(&mut line, "DESCRIPTION", layout.description, &transaction.description, false)?;
+
+        line.push(b'\n');
+        writer.write_all(&line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::test_transaction;
+    use crate::{Parser, Serializer, TransactionType};
+    use std::io::Cursor;
+
+    #[test]
+    fn write_pads_fields_to_default_layout() {
+        let mut output = Vec::new();
+        let transaction = Transaction {
+            transaction_type: TransactionType::Withdrawal,
+            from_user_id: 10,
+            to_user_id: 0,
+            amount: -1500,
+            description: "ATM, \"Main st\"".to_owned(),
+            ..test_transaction(1)
+        };
+        YPBankFixedRecord::from(transaction).write(&mut output).unwrap();
+
+        let line = String::from_utf8(output).unwrap();
+        assert_eq!(line.len(), FixedLayout::default().line_width() + 1);
+        assert_eq!(&line[..30], "                   1WITHDRAWAL");
+        assert_eq!(&line[70..90], "               -1500");
+        assert_eq!(&line[110..134], "SUCCESS   ATM, \"Main st\"");
+    }
+
+    #[test]
+    fn round_trip_through_serializer_and_parser() {
+        let transactions = vec![test_transaction(1), test_transaction(2)];
+
+        let mut serializer = Serializer::<YPBankFixedRecord, _>::new(Vec::new());
+        serializer.serialize(transactions.iter().cloned().map(YPBankFixedRecord::from)).unwrap();
+        let bytes = serializer.into_inner().into_inner().unwrap();
+
+        let parser = Parser::<YPBankFixedRecord, _>::new(Cursor::new(bytes));
+        let read_back: Vec<Transaction> = parser.map(|record| record.map(Into::into)).collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(read_back, transactions);
+    }
+
+    #[test]
+    fn custom_layout_is_used_for_reading_and_writing() {
+        let layout = FixedLayout::new([
+            FixedColumn::new(0, 4),
+            FixedColumn::new(4, 10),
+            FixedColumn::new(14, 4),
+            FixedColumn::new(18, 4),
+            FixedColumn::new(22, 6),
+            FixedColumn::new(28, 14),
+            FixedColumn::new(42, 8),
+            FixedColumn::new(50, 20),
+        ])
+        .unwrap();
+
+        let mut serializer = Serializer::<YPBankFixedRecord, _>::new(Vec::new()).with_options(layout);
+        serializer.push(&YPBankFixedRecord::from(test_transaction(7))).unwrap();
+        let output = serializer.finish().unwrap();
+        assert_eq!(output.len(), 71);
+
+        let reader = FixedReader::with_layout(Cursor::new(output), layout);
+        let mut parser = Parser::<YPBankFixedRecord, _>::from_reader(reader);
+
+        assert_eq!(parser.next().unwrap().unwrap(), YPBankFixedRecord::from(test_transaction(7)));
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn default_layout_passes_validation() {
+        let default = FixedLayout::default();
+
+        assert_eq!(FixedLayout::new(default.columns().map(|(_, column)| column)).unwrap(), default);
+    }
+
+    #[test]
+    fn overlapping_or_empty_columns_are_rejected() {
+        let mut columns = FixedLayout::default().columns().map(|(_, column)| column);
+        columns[4] = FixedColumn::new(65, 20);
+        let error = FixedLayout::new(columns).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert_eq!(error.to_string(), "Field AMOUNT at bytes 65..85 overlaps field TO_USER_ID at bytes 50..70");
+
+        let mut columns = FixedLayout::default().columns().map(|(_, column)| column);
+        columns[1] = FixedColumn::new(20, 0);
+        let error = FixedLayout::new(columns).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert_eq!(error.to_string(), "Field TX_TYPE has zero width");
+    }
+
+    #[test]
+    fn short_line_is_reported_and_stream_continues() {
+        let mut input = Vec::new();
+        YPBankFixedRecord::from(test_transaction(1)).write(&mut input).unwrap();
+        input.extend_from_slice(b"                   2DEPOSIT   \n\n");
+        YPBankFixedRecord::from(test_transaction(3)).write(&mut input).unwrap();
+
+        let mut parser = Parser::<YPBankFixedRecord, _>::new(Cursor::new(input));
+
+        assert_eq!(Transaction::from(parser.next().unwrap().unwrap()).id, 1);
+        let error = parser.next().unwrap().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "Line 2: Field FROM_USER_ID at bytes 30..50 is out of line of length 30");
+        assert_eq!(Transaction::from(parser.next().unwrap().unwrap()).id, 3);
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn description_with_stripped_padding_is_accepted() {
+        let mut output = Vec::new();
+        YPBankFixedRecord::from(test_transaction(1)).write(&mut output).unwrap();
+        let line = String::from_utf8(output).unwrap();
+
+        let record = YPBankFixedRecord::parse_line(line.trim_end(), &FixedLayout::default()).unwrap();
+
+        assert_eq!(record, YPBankFixedRecord::from(test_transaction(1)));
+    }
+
+    #[test]
+    fn invalid_codes_and_numbers_are_reported() {
+        let mut output = Vec::new();
+        YPBankFixedRecord::from(test_transaction(1)).write(&mut output).unwrap();
+        let line = String::from_utf8(output).unwrap().replacen("DEPOSIT   ", "REFUND    ", 1);
+
+        let error = YPBankFixedRecord::parse_line(&line, &FixedLayout::default()).unwrap_err();
+
+        assert_eq!(error.to_string(), "Invalid TX_TYPE value \"REFUND\"");
+    }
+
+    #[test]
+    fn too_long_description_is_rejected_without_output() {
+        let mut transaction = test_transaction(1);
+        transaction.description = "x".repeat(61);
+
+        let mut output = Vec::new();
+        let error = YPBankFixedRecord::from(transaction).write(&mut output).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert!(output.is_empty());
+    }
+}
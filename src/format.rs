@@ -0,0 +1,159 @@
+use crate::common::Transaction;
+use crate::errors::{ParseEnumError, ParseError};
+use crate::{Parser, Readable, Serializer, Writable, YPBankBinRecord, YPBankCsvRecord, YPBankJsonRecord, YPBankTextRecord};
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Формат записей, выбираемый во время выполнения (например, по флагу `--format`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// CSV с заголовком (`YPBankCsvRecord`).
+    Csv,
+    /// Текстовый формат «ключ: значение» (`YPBankTextRecord`).
+    Txt,
+    /// JSON Lines (`YPBankJsonRecord`).
+    Json,
+    /// Бинарный формат (`YPBankBinRecord`).
+    Bin,
+}
+
+impl Format {
+    /// Определяет формат по расширению файла: `csv`, `txt`, `json` или `jsonl` (JSON Lines), `bin`.
+    ///
+    /// Для прочих расширений и файлов без расширения возвращает `None`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "csv" => Some(Format::Csv),
+            "txt" => Some(Format::Txt),
+            "json" | "jsonl" => Some(Format::Json),
+            "bin" => Some(Format::Bin),
+            _ => None,
+        }
+    }
+
+    /// Создает парсер транзакций в этом формате поверх источника `source`.
+    pub fn parser<'a, R: Read + 'a>(self, source: R) -> Box<dyn Iterator<Item = Result<Transaction, ParseError>> + 'a> {
+        match self {
+            Format::Csv => boxed_parser::<YPBankCsvRecord, _>(source),
+            Format::Txt => boxed_parser::<YPBankTextRecord, _>(source),
+            Format::Json => boxed_parser::<YPBankJsonRecord, _>(source),
+            Format::Bin => boxed_parser::<YPBankBinRecord, _>(source),
+        }
+    }
+
+    /// Создает сериализатор транзакций в этом формате, пишущий в `target`.
+    pub fn serializer<'a, W: Write + 'a>(self, target: W) -> Box<dyn RecordSink + 'a> {
+        match self {
+            Format::Csv => Box::new(Serializer::<YPBankCsvRecord, _>::new(target)),
+            Format::Txt => Box::new(Serializer::<YPBankTextRecord, _>::new(target)),
+            Format::Json => Box::new(Serializer::<YPBankJsonRecord, _>::new(target)),
+            Format::Bin => Box::new(Serializer::<YPBankBinRecord, _>::new(target)),
+        }
+    }
+}
+
+fn boxed_parser<'a, TRecord, R>(source: R) -> Box<dyn Iterator<Item = Result<Transaction, ParseError>> + 'a>
+where
+    TRecord: Readable<R> + Into<Transaction> + 'a,
+    TRecord::Reader: 'a,
+    TRecord::Error: Into<ParseError>,
+    R: Read + 'a,
+{
+    Box::new(Parser::<TRecord, R>::new(source).map(|record| record.map(Into::into).map_err(Into::into)))
+}
+
+impl FromStr for Format {
+    type Err = ParseEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Format::Csv),
+            "txt" => Ok(Format::Txt),
+            "json" => Ok(Format::Json),
+            "bin" => Ok(Format::Bin),
+
+            _ => Err(ParseEnumError {
+                enum_name: "Format",
+                input: s.to_string(),
+                expected: &["csv", "txt", "json", "bin"],
+            }),
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Format::Csv => write!(f, "csv"),
+            Format::Txt => write!(f, "txt"),
+            Format::Json => write!(f, "json"),
+            Format::Bin => write!(f, "bin"),
+        }
+    }
+}
+
+/// Приемник транзакций, формат которого выбран во время выполнения (`Format::serializer`).
+pub trait RecordSink {
+    /// Дописывает транзакцию; данные остаются в буфере до вызова `flush`.
+    fn write(&mut self, transaction: Transaction) -> std::io::Result<()>;
+
+    /// Записывает заголовок формата, если записей не было, и сбрасывает буфер в целевой поток.
+    fn flush(&mut self) -> std::io::Result<()>;
+}
+
+impl<TRecord, Target> RecordSink for Serializer<TRecord, Target>
+where
+    TRecord: Writable,
+    Target: Write,
+{
+    fn write(&mut self, transaction: Transaction) -> std::io::Result<()> {
+        self.push(&TRecord::from(transaction)).map_err(Into::into)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.write_header_once().map_err(Into::<std::io::Error>::into)?;
+        self.target.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const CSV: &str = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Salary\"
+2,TRANSFER,10,20,50,2000,PENDING,\"Rent, \"\"May\"\"\"
+";
+
+    #[test]
+    fn parses_csv_and_reserializes_in_format_selected_by_name() {
+        let transactions: Vec<Transaction> = Format::Csv.parser(Cursor::new(CSV)).collect::<Result<_, _>>().unwrap();
+
+        for name in ["csv", "txt", "json", "bin"] {
+            let format: Format = name.parse().unwrap();
+            assert_eq!(format.to_string(), name);
+
+            let mut output = Vec::new();
+            let mut sink = format.serializer(&mut output);
+            for transaction in transactions.clone() {
+                sink.write(transaction).unwrap();
+            }
+            sink.flush().unwrap();
+            drop(sink);
+
+            let read_back: Vec<Transaction> = format.parser(Cursor::new(output)).collect::<Result<_, _>>().unwrap();
+            assert_eq!(read_back, transactions, "format {}", name);
+        }
+    }
+
+    #[test]
+    fn unknown_format_name_is_rejected() {
+        let error = "xml".parse::<Format>().unwrap_err();
+
+        assert_eq!(error.to_string(), "invalid Format \"xml\", expected one of csv, txt, json, bin");
+    }
+}
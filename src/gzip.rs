@@ -0,0 +1,120 @@
+use crate::{Parser, Readable};
+use flate2::read::GzDecoder;
+use std::io::{Chain, Cursor, Read};
+
+/// Сигнатура начала gzip-потока.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Источник, прочитанные для распознавания байты которого возвращены в начало потока.
+type Peeked<R> = Chain<Cursor<Vec<u8>>, R>;
+
+/// Источник, который распаковывается, если начинается с сигнатуры gzip, и читается как есть иначе.
+pub enum MaybeGzip<R> {
+    /// Несжатый поток.
+    Plain(Peeked<R>),
+    /// Поток в формате gzip.
+    Gzip(GzDecoder<Peeked<R>>),
+}
+
+impl<R: Read> MaybeGzip<R> {
+    /// Читает первые байты источника и выбирает способ чтения.
+    ///
+    /// Прочитанные байты не теряются: они возвращаются в начало потока.
+    pub fn detect(mut source: R) -> std::io::Result<Self> {
+        let mut prefix = Vec::with_capacity(GZIP_MAGIC.len());
+        source.by_ref().take(GZIP_MAGIC.len() as u64).read_to_end(&mut prefix)?;
+
+        let is_gzip = prefix == GZIP_MAGIC;
+        let peeked = Cursor::new(prefix).chain(source);
+
+        Ok(match is_gzip {
+            true => MaybeGzip::Gzip(GzDecoder::new(peeked)),
+            false => MaybeGzip::Plain(peeked),
+        })
+    }
+}
+
+impl<R: Read> Read for MaybeGzip<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            MaybeGzip::Plain(inner) => inner.read(buf),
+            MaybeGzip::Gzip(inner) => inner.read(buf),
+        }
+    }
+}
+
+impl<TRecord, R> Parser<TRecord, GzDecoder<R>>
+where
+    TRecord: Readable<GzDecoder<R>>,
+    R: Read
+{
+    /// Создает парсер, распаковывающий gzip-поток `source`.
+    pub fn from_gzip(source: R) -> Self {
+        Self::new(GzDecoder::new(source))
+    }
+}
+
+impl<TRecord, R> Parser<TRecord, MaybeGzip<R>>
+where
+    TRecord: Readable<MaybeGzip<R>>,
+    R: Read
+{
+    /// Создает парсер, который сам определяет, сжат ли `source` gzip.
+    ///
+    /// Ошибка возвращается, только если не удалось прочитать первые байты источника.
+    pub fn from_maybe_gzip(source: R) -> std::io::Result<Self> {
+        Ok(Self::new(MaybeGzip::detect(source)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::YPBankCsvRecord;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    const CSV_DATA: &str = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1001,DEPOSIT,0,501,50000,1672531200000,SUCCESS,\"Initial account funding\"
+1002,TRANSFER,501,502,15000,1672534800000,FAILURE,\"Payment\"
+";
+
+    fn gzipped(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn read_ids(source: Vec<u8>) -> Vec<u64> {
+        Parser::<YPBankCsvRecord, _>::from_maybe_gzip(Cursor::new(source))
+            .unwrap()
+            .map(|record| record.unwrap().id())
+            .collect()
+    }
+
+    #[test]
+    fn plain_and_gzipped_inputs_are_read_the_same() {
+        assert_eq!(read_ids(CSV_DATA.as_bytes().to_vec()), vec![1001, 1002]);
+        assert_eq!(read_ids(gzipped(CSV_DATA.as_bytes())), vec![1001, 1002]);
+    }
+
+    #[test]
+    fn from_gzip_reads_compressed_stream() {
+        let records = Parser::<YPBankCsvRecord, _>::from_gzip(Cursor::new(gzipped(CSV_DATA.as_bytes())))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(records.len(), 2);
+    }
+
+    #[test]
+    fn short_plain_input_is_not_lost() {
+        let mut detected = MaybeGzip::detect(Cursor::new(vec![0x1f])).unwrap();
+        let mut content = Vec::new();
+        detected.read_to_end(&mut content).unwrap();
+
+        assert_eq!(content, vec![0x1f]);
+    }
+}
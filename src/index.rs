@@ -0,0 +1,127 @@
+use crate::common::Transaction;
+use std::collections::HashMap;
+
+/// Индекс транзакций по участникам для быстрого поиска после одного прохода разбора.
+///
+/// ID `0` обозначает внешнего контрагента (отправителя пополнения и получателя снятия),
+/// поэтому в индекс не попадает: запросы для пользователя `0` ничего не возвращают.
+#[derive(Debug, Default)]
+pub struct TransactionIndex {
+    transactions: Vec<Transaction>,
+    by_sender: HashMap<u64, Vec<usize>>,
+    by_receiver: HashMap<u64, Vec<usize>>,
+}
+
+impl TransactionIndex {
+    /// Строит индекс по набору транзакций; порядок транзакций сохраняется.
+    pub fn build(records: impl IntoIterator<Item = Transaction>) -> Self {
+        let mut index = Self::default();
+
+        for (position, transaction) in records.into_iter().enumerate() {
+            if transaction.from_user_id != 0 {
+                index.by_sender.entry(transaction.from_user_id).or_default().push(position);
+            }
+            if transaction.to_user_id != 0 {
+                index.by_receiver.entry(transaction.to_user_id).or_default().push(position);
+            }
+            index.transactions.push(transaction);
+        }
+
+        index
+    }
+
+    /// Все транзакции в исходном порядке.
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    /// Транзакции, в которых `user_id` — отправитель.
+    pub fn sent_by(&self, user_id: u64) -> impl Iterator<Item = &Transaction> {
+        self.lookup(&self.by_sender, user_id).iter().map(|&i| &self.transactions[i])
+    }
+
+    /// Транзакции, в которых `user_id` — получатель.
+    pub fn received_by(&self, user_id: u64) -> impl Iterator<Item = &Transaction> {
+        self.lookup(&self.by_receiver, user_id).iter().map(|&i| &self.transactions[i])
+    }
+
+    /// Транзакции, в которых `user_id` — отправитель или получатель, в исходном порядке и без повторов.
+    pub fn involving(&self, user_id: u64) -> impl Iterator<Item = &Transaction> {
+        let mut sent = self.lookup(&self.by_sender, user_id).iter().peekable();
+        let mut received = self.lookup(&self.by_receiver, user_id).iter().peekable();
+
+        // оба списка упорядочены по позиции, поэтому достаточно слияния
+        std::iter::from_fn(move || {
+            let position = match (sent.peek(), received.peek()) {
+                (Some(&&s), Some(&&r)) if s == r => {
+                    received.next();
+                    sent.next()
+                }
+                (Some(&&s), Some(&&r)) if s < r => sent.next(),
+                (Some(_), Some(_)) | (None, Some(_)) => received.next(),
+                (Some(_), None) => sent.next(),
+                (None, None) => None,
+            }?;
+            Some(&self.transactions[*position])
+        })
+    }
+
+    fn lookup<'a>(&self, map: &'a HashMap<u64, Vec<usize>>, user_id: u64) -> &'a [usize] {
+        map.get(&user_id).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::test_transaction;
+    use crate::TransactionType;
+
+    fn sample_index() -> TransactionIndex {
+        TransactionIndex::build(vec![
+            test_transaction(1),
+            Transaction { transaction_type: TransactionType::Transfer, from_user_id: 10, to_user_id: 20, ..test_transaction(2) },
+            Transaction { transaction_type: TransactionType::Transfer, from_user_id: 20, ..test_transaction(3) },
+            Transaction { transaction_type: TransactionType::Withdrawal, from_user_id: 10, to_user_id: 0, ..test_transaction(4) },
+            Transaction { transaction_type: TransactionType::Transfer, from_user_id: 30, to_user_id: 20, ..test_transaction(5) },
+        ])
+    }
+
+    fn ids<'a>(transactions: impl Iterator<Item = &'a Transaction>) -> Vec<u64> {
+        transactions.map(|t| t.id).collect()
+    }
+
+    #[test]
+    fn sent_by_returns_outgoing_transactions() {
+        let index = sample_index();
+
+        assert_eq!(ids(index.sent_by(10)), vec![2, 4]);
+        assert_eq!(ids(index.sent_by(99)), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn received_by_returns_incoming_transactions() {
+        let index = sample_index();
+
+        assert_eq!(ids(index.received_by(10)), vec![1, 3]);
+        assert_eq!(ids(index.received_by(20)), vec![2, 5]);
+    }
+
+    #[test]
+    fn involving_merges_both_sides_in_order() {
+        let index = sample_index();
+
+        assert_eq!(ids(index.involving(10)), vec![1, 2, 3, 4]);
+        assert_eq!(ids(index.involving(30)), vec![5]);
+    }
+
+    #[test]
+    fn external_sentinel_is_not_a_participant() {
+        let index = sample_index();
+
+        assert_eq!(ids(index.sent_by(0)), Vec::<u64>::new());
+        assert_eq!(ids(index.received_by(0)), Vec::<u64>::new());
+        assert_eq!(ids(index.involving(0)), Vec::<u64>::new());
+        assert_eq!(index.transactions().len(), 5);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::common::{deserialize_currency, Transaction, TransactionStatus, TransactionType};
+use crate::{Readable, Writable};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Seek, SeekFrom, Write};
+
+//noinspection DuplicatedCode
+/// Запись транзакции в формате JSON Lines: один JSON-объект на строку.
+///
+/// Имена полей совпадают с заголовками CSV (`TX_ID`, `TX_TYPE`, ...), числовые поля
+/// записываются числами. Пустые строки между объектами пропускаются.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct YPBankJsonRecord {
+    #[serde(rename = "TX_ID")]
+    id: u64,
+
+    #[serde(rename = "TX_TYPE")]
+    transaction_type: TransactionType,
+
+    #[serde(rename = "FROM_USER_ID")]
+    from_user_id: u64,
+
+    #[serde(rename = "TO_USER_ID")]
+    to_user_id: u64,
+
+    #[serde(rename = "AMOUNT")]
+    amount: i64,
+
+    #[serde(rename = "TIMESTAMP")]
+    timestamp: u64,
+
+    #[serde(rename = "STATUS")]
+    transaction_status: TransactionStatus,
+
+    #[serde(rename = "DESCRIPTION")]
+    description: String,
+
+    #[serde(rename = "CURRENCY", default, deserialize_with = "deserialize_currency", skip_serializing_if = "Option::is_none")]
+    currency: Option<String>,
+}
+
+//noinspection DuplicatedCode
+impl From<YPBankJsonRecord> for Transaction {
+    fn from(value: YPBankJsonRecord) -> Self {
+        Transaction {
+            id: value.id,
+            transaction_type: value.transaction_type,
+            from_user_id: value.from_user_id,
+            to_user_id: value.to_user_id,
+            amount: value.amount,
+            timestamp: value.timestamp,
+            transaction_status: value.transaction_status,
+            description: value.description,
+            currency: value.currency,
+        }
+    }
+}
+
+impl From<Transaction> for YPBankJsonRecord {
+    fn from(value: Transaction) -> Self {
+        YPBankJsonRecord {
+            id: value.id,
+            transaction_type: value.transaction_type,
+            from_user_id: value.from_user_id,
+            to_user_id: value.to_user_id,
+            amount: value.amount,
+            timestamp: value.timestamp,
+            transaction_status: value.transaction_status,
+            description: value.description,
+            currency: value.currency,
+        }
+    }
+}
+
+//noinspection DuplicatedCode
+impl YPBankJsonRecord {
+    /// Уникальный идентификатор транзакции.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Тип операции.
+    pub fn transaction_type(&self) -> TransactionType {
+        self.transaction_type
+    }
+
+    /// ID отправителя.
+    pub fn from_user_id(&self) -> u64 {
+        self.from_user_id
+    }
+
+    /// ID получателя.
+    pub fn to_user_id(&self) -> u64 {
+        self.to_user_id
+    }
+
+    /// Сумма операции.
+    pub fn amount(&self) -> i64 {
+        self.amount
+    }
+
+    /// Временная метка операции.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Статус транзакции.
+    pub fn transaction_status(&self) -> TransactionStatus {
+        self.transaction_status
+    }
+
+    /// Описание транзакции.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+impl<R: Read> Readable<R> for YPBankJsonRecord {
+    type Reader = BufReader<R>;
+    type Error = Error;
+
+    fn build_reader(source: R) -> Self::Reader {
+        BufReader::new(source)
+    }
+
+    fn build_reader_with_capacity(source: R, capacity: usize) -> Self::Reader {
+        BufReader::with_capacity(capacity, source)
+    }
+
+    fn rewind(reader: &mut Self::Reader) -> Result<(), Error>
+    where
+        R: Seek
+    {
+        reader.seek(SeekFrom::Start(0)).map(|_| ())
+    }
+
+    fn read(reader: &mut Self::Reader) -> Result<Self, Error> {
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(Error::new(ErrorKind::UnexpectedEof, "End of JSON lines"));
+            }
+
+            if !line.trim().is_empty() {
+                break;
+            }
+        }
+
+        serde_json::from_str(&line).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    fn is_recoverable(error: &Error) -> bool {
+        // строка с ошибкой уже прочитана целиком
+        error.kind() == ErrorKind::InvalidData
+    }
+}
+
+impl Writable for YPBankJsonRecord {
+    type Error = Error;
+    type Options = ();
+
+    fn write_header<W: Write>(_: &mut W) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        serde_json::to_writer(&mut *writer, self)?;
+        writer.write_all(b"\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Parser, Serializer};
+    use std::io::Cursor;
+
+    fn sample_record(id: u64) -> YPBankJsonRecord {
+        YPBankJsonRecord {
+            id,
+            transaction_type: TransactionType::Transfer,
+            from_user_id: 10,
+            to_user_id: 20,
+            amount: 1500,
+            timestamp: 1_700_000_000_000,
+            transaction_status: TransactionStatus::Pending,
+            description: "Rent, \"March\"".to_string(),
+            currency: None,
+        }
+    }
+
+    #[test]
+    fn write_emits_one_compact_object_per_line() {
+        let mut output = Vec::new();
+        sample_record(1).write(&mut output).unwrap();
+
+        let expected = "{\"TX_ID\":1,\"TX_TYPE\":\"TRANSFER\",\"FROM_USER_ID\":10,\"TO_USER_ID\":20,\"AMOUNT\":1500,\
+\"TIMESTAMP\":1700000000000,\"STATUS\":\"PENDING\",\"DESCRIPTION\":\"Rent, \\\"March\\\"\"}\n";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn round_trip_through_serializer_and_parser() {
+        let mut serializer = Serializer::<YPBankJsonRecord, _>::new(Vec::new());
+        serializer.serialize(vec![sample_record(1), sample_record(2)]).unwrap();
+        let bytes = serializer.into_inner().into_inner().unwrap();
+
+        let parser = Parser::<YPBankJsonRecord, _>::new(Cursor::new(bytes));
+        let records: Vec<_> = parser.collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(records, vec![sample_record(1), sample_record(2)]);
+    }
+
+    #[test]
+    fn malformed_line_does_not_abort_stream() {
+        let mut input = Vec::new();
+        sample_record(1).write(&mut input).unwrap();
+        input.extend_from_slice(b"{\"TX_ID\": 2, \"TX_TYPE\": \n\n");
+        sample_record(3).write(&mut input).unwrap();
+
+        let mut parser = Parser::<YPBankJsonRecord, _>::new(Cursor::new(input));
+
+        assert_eq!(parser.next().unwrap().unwrap().id, 1);
+        assert_eq!(parser.next().unwrap().unwrap_err().kind(), ErrorKind::InvalidData);
+        assert_eq!(parser.next().unwrap().unwrap().id, 3);
+        assert!(parser.next().is_none());
+    }
+}
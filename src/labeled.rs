@@ -0,0 +1,73 @@
+use crate::errors::LabeledError;
+use crate::{Parser, Readable};
+use std::io::Read;
+
+/// Парсер, помечающий ошибки чтения меткой источника.
+pub struct LabeledParser<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read
+{
+    parser: Parser<TRecord, Source>,
+    label: String,
+}
+
+impl<TRecord, Source> Iterator for LabeledParser<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read
+{
+    type Item = Result<TRecord, LabeledError<TRecord::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.parser.next()?;
+
+        Some(record.map_err(|source| LabeledError {
+            label: self.label.clone(),
+            source,
+        }))
+    }
+}
+
+impl<TRecord, Source> Parser<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read
+{
+    /// Добавляет к ошибкам чтения метку источника, например имя файла.
+    ///
+    /// Ошибки выдаются как `LabeledError` с исходной ошибкой в поле `source`.
+    pub fn labeled(self, label: impl Into<String>) -> LabeledParser<TRecord, Source> {
+        LabeledParser {
+            parser: self,
+            label: label.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, YPBankCsvRecord};
+    use std::io::{Cursor, ErrorKind};
+
+    #[test]
+    fn read_error_carries_label() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc 1\"
+2,DEPOSIT,0,10,not_a_number,2000,SUCCESS,\"Desc 2\"
+";
+        let mut parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data)).labeled("daily_2024-01-01.csv");
+
+        assert!(parser.next().unwrap().is_ok());
+
+        let error = parser.next().unwrap().expect_err("Should have an error");
+        assert!(parser.next().is_none());
+        assert_eq!(error.label, "daily_2024-01-01.csv");
+        assert!(error.to_string().starts_with("daily_2024-01-01.csv: "));
+
+        let io_error: std::io::Error = error.into();
+        assert_eq!(io_error.kind(), ErrorKind::InvalidData);
+        assert!(io_error.to_string().contains("daily_2024-01-01.csv"));
+    }
+}
@@ -2,22 +2,101 @@ mod common;
 mod txt_format;
 mod csv_format;
 mod bin_format;
+mod json_format;
+mod fixed_format;
 mod errors;
+mod ordering;
+mod channel_reader;
+mod stats;
+mod warnings;
+mod raw_record;
+mod dir_validation;
+mod multi_format;
+mod budget;
+mod merge;
+mod diff;
+mod compare;
+mod dedup;
+mod filter;
+mod index;
+mod redact;
+mod tee;
+mod raw_bytes;
+mod throttle;
+mod sql;
+mod labeled;
+mod canonical;
+mod validation;
+mod format;
+#[cfg(feature = "chain")]
+mod chain;
+#[cfg(feature = "parquet")]
+mod parquet_format;
+#[cfg(feature = "gzip")]
+mod gzip;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "chrono")]
+mod report_format;
+#[cfg(feature = "async")]
+mod async_parser;
 
 use std::error::Error;
-use std::io::{BufWriter, Read, Write};
+use std::io::{BufWriter, Read, Seek, Write};
 use std::marker::PhantomData;
 
-pub use txt_format::{YPBankTextRecord};
-pub use csv_format::{YPBankCsvRecord};
+/// Размер буфера чтения и записи по умолчанию (8 КиБ).
+pub const DEFAULT_BUFFER_CAPACITY: usize = 8 * 1024;
+
+pub use txt_format::{CommentedRecords, PresenceRecords, ProcessedFields, RecordSeparator, TextReader, YPBankTextRecord};
+pub use csv_format::{CsvOptions, CsvReadable, CsvReader, CurrencyColumn, YPBankCsvRecord};
 pub use bin_format::{YPBankBinRecord};
-pub use common::{Transaction, TransactionType, TransactionStatus};
+pub use json_format::YPBankJsonRecord;
+pub use fixed_format::{FixedColumn, FixedLayout, FixedReader, YPBankFixedRecord};
+pub use common::{Defaults, SentinelRendering, Transaction, TransactionBuilder, TransactionType, TransactionStatus, ValidationConfig, EXTERNAL_USER_LITERAL};
+pub use ordering::{find_id_gaps, is_sorted_by_id};
+pub use channel_reader::ChannelReader;
+pub use stats::{bucket_by_time, moving_average, pivot_by_user_and_type, top_n_by_amount, verify_control_total, CountKey, Counts, HyperLogLog, PivotTable, StatusCounts, Summary, TypeCounts, UserRollup, UserTotals};
+pub use errors::{AmountOverflowError, BudgetError, BudgetExceeded, BudgetLimit, ChainError, ControlTotalMismatch, ConvertError, CsvRecordError, InvalidEnumValue, LabeledError, LimitError, MergeError, NonPrintableDescription, OrderError, ParseEnumError, ParseError, TeeError, TextRecordError, TimestampOutOfRange, UnsortedIdError, ValidationError};
+pub use warnings::{Warning, WarningKind, WarningPolicy, WarningReader};
+pub use raw_record::RawRecord;
+pub use dir_validation::{validate_dir, DirReport, FailurePolicy, FileReport};
+pub use multi_format::MultiFormatParser;
+pub use budget::{BudgetedParser, CountingSource, ResourceBudget};
+pub use merge::{merge_by_timestamp, BoxedParser, KWayMerge, MergeByTimestamp};
+pub use diff::DiffReport;
+pub use compare::{diff, diff_with, DiffOptions, FieldDiff, Mismatch, TransactionDiff};
+pub use dedup::{dedup, DedupParser, KeepPolicy};
+pub use filter::Filtered;
+pub use index::TransactionIndex;
+pub use redact::{redact, DescriptionRedaction, RedactOptions};
+pub use tee::Tee;
+pub use raw_bytes::{RawBytesRecords, RecordWithRaw};
+pub use throttle::Throttled;
+pub use sql::{to_sql_inserts, to_sql_inserts_batched};
+pub use labeled::LabeledParser;
+pub use canonical::{canonicalize, CanonicalPolicy};
+pub use validation::ValidatedParser;
+pub use format::{Format, RecordSink};
+#[cfg(feature = "chain")]
+pub use chain::{serialize_with_chain, verify_chain};
+#[cfg(feature = "parquet")]
+pub use parquet_format::write_parquet;
+#[cfg(feature = "gzip")]
+pub use gzip::MaybeGzip;
+#[cfg(feature = "parallel")]
+pub use parallel::parse_parallel;
+#[cfg(feature = "chrono")]
+pub use report_format::YPBankReportRecord;
+#[cfg(feature = "async")]
+pub use async_parser::AsyncParser;
 
 /// Трейт для типов, поддерживающих чтение из источника данных.
 ///
 /// Этот трейт позволяет абстрагироваться от конкретного формата данных (CSV, бинарный и т.д.)
-/// и способа их чтения. Типы, реализующие этот трейт, могут быть преобразованы в `Transaction`.
-pub trait Readable<Source: Read> : Sized + Into<Transaction> {
+/// и способа их чтения. Типизированные записи форматов дополнительно реализуют `Into<Transaction>`;
+/// «сырые» записи (`RawRecord`) сохраняют значения полей как строки и такого преобразования не имеют.
+pub trait Readable<Source: Read> : Sized {
     /// Тип читателя, используемого для извлечения данных.
     type Reader;
     /// Тип ошибки, возникающей при чтении.
@@ -25,8 +104,56 @@ pub trait Readable<Source: Read> : Sized + Into<Transaction> {
 
     #[doc(hidden)]
     fn build_reader(source: Source) -> Self::Reader;
+
+    /// Создает читатель с буфером заданного размера.
+    ///
+    /// По умолчанию размер буфера не настраивается и используется `build_reader`.
+    #[doc(hidden)]
+    fn build_reader_with_capacity(source: Source, _capacity: usize) -> Self::Reader {
+        Self::build_reader(source)
+    }
     #[doc(hidden)]
     fn read(reader: &mut Self::Reader) -> Result<Self, Self::Error>;
+
+    /// Читает следующую запись, возвращая только её идентификатор.
+    ///
+    /// По умолчанию запись разбирается полностью; форматы, умеющие извлекать `TX_ID`
+    /// без разбора остальных полей, переопределяют этот метод.
+    #[doc(hidden)]
+    fn read_id(reader: &mut Self::Reader) -> Result<u64, Self::Error>
+    where
+        Self: Into<Transaction>
+    {
+        Self::read(reader).map(|record| record.into().id)
+    }
+
+    /// Читает заголовок версии схемы в начале потока и проверяет, что версия допустима.
+    ///
+    /// По умолчанию формат не имеет заголовка версии, поэтому проверка всегда завершается ошибкой.
+    #[doc(hidden)]
+    fn check_version(_reader: &mut Self::Reader, _accepted: &[u32]) -> Result<(), Self::Error> {
+        Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Format has no schema version header").into())
+    }
+
+    /// Можно ли продолжить чтение после ошибки `error`.
+    ///
+    /// По умолчанию любая ошибка считается фатальной. Форматы, которые после ошибки разбора
+    /// уже стоят на границе следующей записи, переопределяют этот метод.
+    #[doc(hidden)]
+    fn is_recoverable(_error: &Self::Error) -> bool {
+        false
+    }
+
+    /// Возвращает читатель к началу источника и сбрасывает состояние разбора.
+    ///
+    /// По умолчанию формат перемотку не поддерживает.
+    #[doc(hidden)]
+    fn rewind(_reader: &mut Self::Reader) -> Result<(), Self::Error>
+    where
+        Source: Seek
+    {
+        Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "Format does not support rewinding").into())
+    }
 }
 
 /// Трейт для проверки, является ли ошибка указанием на конец файла (EOF).
@@ -39,15 +166,21 @@ pub trait IsEofError {
 ///
 /// `Parser` читает исходный поток (`Source`) и использует реализацию `Readable`
 /// для `TRecord`, чтобы итеративно извлекать записи.
+///
+/// Итератор выдает `Err` для каждой испорченной записи. Если формат может пропустить такую
+/// запись, чтение продолжается со следующей; после фатальной ошибки итератор возвращает `None`.
+/// `None` без предшествующей ошибки означает штатный конец потока.
 pub struct Parser<TRecord, Source>
 where
     TRecord: Readable<Source>,
     Source: Read
 {
     reader: TRecord::Reader,
-    /// Содержит ошибку чтения, если она произошла в процессе итерации.
-    /// После возникновения ошибки итератор будет возвращать `None`.
-    pub read_error: Option<TRecord::Error>,
+    /// Ошибка, найденная до чтения записей (например, при проверке версии); выдается первым вызовом `next()`.
+    pending_error: Option<TRecord::Error>,
+    /// Результат, прочитанный `peek` и еще не выданный `next()`; `Some(None)` — конец потока.
+    peeked: Option<Option<Result<TRecord, TRecord::Error>>>,
+    finished: bool,
     _marker: PhantomData<Source>,
 }
 
@@ -56,16 +189,12 @@ where
     TRecord: Readable<Source>,
     Source: Read,
 {
-    type Item = TRecord;
+    type Item = Result<TRecord, TRecord::Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match TRecord::read(&mut self.reader) {
-            Ok(record) => Some(record),
-            Err(e) if e.is_eof() => None,
-            Err(e) => {
-                self.read_error = Some(e);
-                None
-            }
+        match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => self.read_next(),
         }
     }
 }
@@ -77,14 +206,163 @@ where
 {
     /// Создает новый экземпляр парсера из источника данных.
     pub fn new(source: Source) -> Self {
-        let reader = TRecord::build_reader(source);
+        Self::from_reader(TRecord::build_reader(source))
+    }
+
+    /// Создает парсер, читающий источник через буфер размером `capacity` байт.
+    ///
+    /// Для больших последовательных файлов буфер крупнее `DEFAULT_BUFFER_CAPACITY`
+    /// уменьшает число системных вызовов.
+    pub fn with_buffer_capacity(source: Source, capacity: usize) -> Self {
+        Self::from_reader(TRecord::build_reader_with_capacity(source, capacity))
+    }
 
+    /// Создает парсер поверх заранее настроенного читателя формата.
+    ///
+    /// Позволяет изменить параметры чтения, которые `new` выставляет по умолчанию.
+    pub fn from_reader(reader: TRecord::Reader) -> Self {
         Self {
             reader,
-            read_error: None,
+            pending_error: None,
+            peeked: None,
+            finished: false,
             _marker: PhantomData,
         }
     }
+
+    /// Создает парсер, который перед чтением записей проверяет заголовок версии схемы.
+    ///
+    /// Если заголовок отсутствует или версия не входит в `accepted`, первый же вызов `next()`
+    /// вернет эту ошибку, и записи читаться не будут.
+    pub fn require_version(source: Source, accepted: &[u32]) -> Self {
+        let mut parser = Self::new(source);
+
+        if let Err(e) = TRecord::check_version(&mut parser.reader, accepted) {
+            parser.pending_error = Some(e);
+        }
+
+        parser
+    }
+
+    /// Читает следующий результат из источника, минуя `peek`.
+    fn read_next(&mut self) -> Option<Result<TRecord, TRecord::Error>> {
+        if self.finished {
+            return None;
+        }
+
+        if let Some(e) = self.pending_error.take() {
+            self.finished = true;
+            return Some(Err(e));
+        }
+
+        match TRecord::read(&mut self.reader) {
+            Ok(record) => Some(Ok(record)),
+            Err(e) if e.is_eof() => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = !TRecord::is_recoverable(&e);
+                Some(Err(e))
+            }
+        }
+    }
+
+    /// Возвращает следующий результат, не извлекая его: последующий `next()` вернет его же.
+    ///
+    /// Ошибки тоже можно подсмотреть; повторно они не выдаются. `None` означает конец потока.
+    pub fn peek(&mut self) -> Option<&Result<TRecord, TRecord::Error>> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.read_next());
+        }
+
+        self.peeked.as_ref().and_then(Option::as_ref)
+    }
+
+    /// Читает поток до конца, не сохраняя записи, и возвращает их количество.
+    ///
+    /// При первой ошибке возвращает номер испорченной записи (с нуля) и саму ошибку.
+    pub fn validate_all(self) -> Result<usize, (usize, TRecord::Error)> {
+        let mut count = 0;
+
+        for record in self {
+            record.map_err(|e| (count, e))?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Читает все записи потока.
+    ///
+    /// Возвращает первую же ошибку чтения, а не усеченный список записей.
+    pub fn into_vec(self) -> Result<Vec<TRecord>, TRecord::Error> {
+        self.collect()
+    }
+}
+
+impl<TRecord, Source> Parser<TRecord, Source>
+where
+    TRecord: Readable<Source> + Into<Transaction>,
+    Source: Read
+{
+    /// Читает не более `max` записей и возвращает их как транзакции.
+    ///
+    /// В отличие от `Iterator::take`, не усекает поток молча: если в источнике есть еще записи,
+    /// возвращает `LimitError::Exceeded`.
+    pub fn take_limited(mut self, max: usize) -> Result<Vec<Transaction>, LimitError<TRecord::Error>> {
+        let mut transactions = Vec::new();
+
+        while transactions.len() < max {
+            match self.next() {
+                Some(record) => transactions.push(record.map_err(LimitError::Read)?.into()),
+                None => return Ok(transactions),
+            }
+        }
+
+        match self.next() {
+            Some(_) => Err(LimitError::Exceeded { limit: max }),
+            None => Ok(transactions),
+        }
+    }
+
+    /// Читает поток до конца, разделяя корректные записи и ошибки.
+    ///
+    /// Испорченные записи пропускаются, если формат может продолжить чтение со следующей;
+    /// ошибка сохраняется вместе с номером записи (с нуля, считая и испорченные).
+    /// Фатальная ошибка также попадает в список и завершает чтение.
+    pub fn partition_valid(self) -> (Vec<Transaction>, Vec<(usize, TRecord::Error)>) {
+        let mut transactions = Vec::new();
+        let mut errors = Vec::new();
+
+        for (index, record) in self.enumerate() {
+            match record {
+                Ok(record) => transactions.push(record.into()),
+                Err(error) => errors.push((index, error)),
+            }
+        }
+
+        (transactions, errors)
+    }
+}
+
+impl<TRecord, Source> Parser<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read + Seek
+{
+    /// Перематывает источник к началу, чтобы прочитать записи повторно.
+    ///
+    /// Состояние разбора сбрасывается (для CSV заголовок пропускается заново), отложенная ошибка
+    /// очищается. Доступно только для источников с `Seek`: поток без перемотки прочитать повторно нельзя.
+    pub fn rewind(&mut self) -> Result<(), TRecord::Error> {
+        TRecord::rewind(&mut self.reader)?;
+        self.pending_error = None;
+        self.peeked = None;
+        self.finished = false;
+
+        Ok(())
+    }
 }
 
 /// Трейт для типов, поддерживающих запись в поток данных.
@@ -93,21 +371,55 @@ where
 pub trait Writable: From<Transaction> {
     /// Тип ошибки, возникающей при записи.
     type Error: Error + From<std::io::Error> + Into<std::io::Error>;
+    /// Настройки записи потока, которые хранит `Serializer` (см. `Serializer::with_options`).
+    ///
+    /// Форматы без настроек используют `()`.
+    type Options: Default;
+
+    /// Имена полей записи в порядке их следования (для CSV — столбцы заголовка).
+    fn field_names() -> &'static [&'static str] {
+        &common::FIELD_NAMES
+    }
 
     #[doc(hidden)]
     fn write_header<W: Write>(writer: &mut W) -> Result<(), Self::Error>;
 
     #[doc(hidden)]
     fn write<W: Write>(&self, writer: &mut W) -> Result<(), Self::Error>;
+
+    /// Записывает заголовок перед первой записью потока.
+    ///
+    /// По умолчанию заголовок не зависит от записей; форматы с заголовком, определяемым
+    /// содержимым (например, `RawRecord` для CSV), переопределяют этот метод. Выбранный
+    /// по первой записи набор столбцов сохраняется в `options` для последующих записей.
+    #[doc(hidden)]
+    fn write_header_for<W: Write>(writer: &mut W, _first: &Self, _options: &mut Self::Options) -> Result<(), Self::Error> {
+        Self::write_header(writer)
+    }
+
+    /// Записывает запись с настройками `options`, выводя идентификатор внешнего контрагента согласно `sentinel`.
+    ///
+    /// По умолчанию совпадает с `write`; переопределяется форматами с настройками и текстовыми форматами.
+    #[doc(hidden)]
+    fn write_rendered<W: Write>(&self, writer: &mut W, _sentinel: &SentinelRendering, _options: &Self::Options) -> Result<(), Self::Error> {
+        self.write(writer)
+    }
 }
 
 /// Сериализатор, преобразующий поток записей в байты и записывающий их в целевой поток.
+///
+/// При удалении сериализатора буфер сбрасывается в целевой поток, поэтому записи, добавленные
+/// через `push`, не теряются и без явного сброса; ошибка такого сброса, однако, игнорируется.
+/// Чтобы получить её, завершайте запись вызовом `finish`.
 pub struct Serializer<TRecord, Target>
 where
     TRecord: Writable,
     Target: Write,
 {
     target: BufWriter<Target>,
+    sentinel: SentinelRendering,
+    options: TRecord::Options,
+    header_written: bool,
     _marker: PhantomData<TRecord>,
 }
 
@@ -120,37 +432,235 @@ where
     ///
     /// `target` автоматически оборачивается в `BufWriter` для эффективности.
     pub fn new(target: Target) -> Self {
-        let buffered_target = BufWriter::new(target);
+        Self::with_buffer_capacity(target, DEFAULT_BUFFER_CAPACITY)
+    }
+
+    /// Создает сериализатор с буфером записи размером `capacity` байт.
+    pub fn with_buffer_capacity(target: Target, capacity: usize) -> Self {
+        let buffered_target = BufWriter::with_capacity(capacity, target);
         Self {
             target: buffered_target,
+            sentinel: SentinelRendering::default(),
+            options: TRecord::Options::default(),
+            header_written: false,
             _marker: PhantomData,
         }
     }
 
+    /// Создает сериализатор для дописывания в существующий файл: заголовок не записывается.
+    ///
+    /// Предназначен для `target`, открытого в режиме добавления, в котором заголовок уже есть.
+    pub fn new_append(target: Target) -> Self {
+        Self { header_written: true, ..Self::new(target) }
+    }
+
+    /// Задает способ вывода идентификатора внешнего контрагента (по умолчанию `SentinelRendering::Zero`).
+    pub fn with_sentinel(mut self, sentinel: SentinelRendering) -> Self {
+        self.sentinel = sentinel;
+        self
+    }
+
+    /// Задает настройки записи формата (например, разделитель блоков TXT или раскладку столбцов).
+    pub fn with_options(mut self, options: TRecord::Options) -> Self {
+        self.options = options;
+        self
+    }
+
     /// Сериализует коллекцию записей и записывает их в целевой поток.
     ///
-    /// Сначала записывается заголовок (если предусмотрен форматом), затем все записи,
-    /// после чего буфер сбрасывается.
+    /// Сначала записывается заголовок (если предусмотрен форматом и еще не записан),
+    /// затем все записи, после чего буфер сбрасывается.
     pub fn serialize<I>(&mut self, records: I) -> Result<(), TRecord::Error>
     where I : IntoIterator<Item = TRecord>,
     {
-        TRecord::write_header(&mut self.target)?;
+        let mut records = records.into_iter().peekable();
+
+        if !self.header_written {
+            match records.peek() {
+                Some(first) => TRecord::write_header_for(&mut self.target, first, &mut self.options)?,
+                None => TRecord::write_header(&mut self.target)?,
+            }
+        }
 
         for record in records {
-            record.write(&mut self.target)?;
+            record.write_rendered(&mut self.target, &self.sentinel, &self.options)?;
         }
 
+        self.header_written = true;
         self.target.flush()?;
 
         Ok(())
     }
 
+    /// Записывает заголовок формата, если он еще не был записан этим сериализатором.
+    pub fn write_header_once(&mut self) -> Result<(), TRecord::Error> {
+        if !self.header_written {
+            TRecord::write_header(&mut self.target)?;
+            self.header_written = true;
+        }
+
+        Ok(())
+    }
+
+    /// Дописывает одну запись в целевой поток.
+    ///
+    /// Перед первой записью автоматически записывается заголовок, если `write_header_once`
+    /// еще не вызывался. Данные остаются в буфере до его сброса.
+    pub fn push(&mut self, record: &TRecord) -> Result<(), TRecord::Error> {
+        if !self.header_written {
+            TRecord::write_header_for(&mut self.target, record, &mut self.options)?;
+            self.header_written = true;
+        }
+
+        record.write_rendered(&mut self.target, &self.sentinel, &self.options)
+    }
+
+    /// Сбрасывает буфер и возвращает целевой поток.
+    ///
+    /// В отличие от удаления сериализатора, при котором ошибка сброса теряется, возвращает её вызывающему.
+    /// Это единственный публичный способ вернуть целевой поток.
+    pub fn finish(self) -> Result<Target, TRecord::Error> {
+        self.target.into_inner().map_err(|e| e.into_error().into())
+    }
+
+    /// Возвращает буфер вместе с несброшенными данными (только для тестов).
+    ///
+    /// В отличие от `finish`, ничего не сбрасывает: данные записываются при `BufWriter::into_inner`
+    /// или при удалении буфера.
     #[cfg(test)]
     pub fn into_inner(self) -> BufWriter<Target> {
         self.target
     }
 }
 
+impl<TRecord: Writable> Serializer<TRecord, std::fs::File> {
+    /// Создает (или перезаписывает) файл `path` и сериализатор, пишущий в него через буфер.
+    pub fn to_path(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        Ok(Self::new(std::fs::File::create(path)?))
+    }
+}
+
+/// Сериализует записи в новый буфер в памяти и возвращает полученные байты.
+///
+/// Записывает заголовок формата (если он предусмотрен), затем все записи по порядку.
+pub fn serialize_to_vec<TRecord: Writable>(records: &[TRecord]) -> Result<Vec<u8>, TRecord::Error> {
+    let mut buffer = Vec::new();
+    let mut options = TRecord::Options::default();
+
+    match records.first() {
+        Some(first) => TRecord::write_header_for(&mut buffer, first, &mut options)?,
+        None => TRecord::write_header(&mut buffer)?,
+    }
+
+    for record in records {
+        record.write_rendered(&mut buffer, &SentinelRendering::Zero, &options)?;
+    }
+
+    Ok(buffer)
+}
+
+/// Разбирает CSV из строки в памяти.
+///
+/// Возвращает первую ошибку разбора вместо усеченного списка.
+pub fn parse_csv_str(s: &str) -> Result<Vec<Transaction>, CsvRecordError> {
+    parse_str::<YPBankCsvRecord>(s)
+}
+
+/// Разбирает текстовый формат из строки в памяти.
+///
+/// Возвращает первую ошибку разбора вместо усеченного списка.
+pub fn parse_txt_str(s: &str) -> Result<Vec<Transaction>, TextRecordError> {
+    parse_str::<YPBankTextRecord>(s)
+}
+
+fn parse_str<'a, TRecord>(s: &'a str) -> Result<Vec<Transaction>, TRecord::Error>
+where
+    TRecord: Readable<&'a [u8]> + Into<Transaction>,
+{
+    Parser::<TRecord, _>::new(s.as_bytes()).map(|record| record.map(Into::into)).collect()
+}
+
+/// Преобразует поток записей формата `RIn` из `source` в формат `ROut` и записывает в `target`.
+///
+/// Записи читаются и записываются по одной, без накопления в памяти. Заголовок целевого формата
+/// записывается один раз. Преобразование останавливается на первой ошибке чтения или записи;
+/// ошибка содержит номер записи. Возвращает число преобразованных записей.
+pub fn convert<RIn, ROut, Source, W>(source: Source, target: W) -> Result<usize, ConvertError>
+where
+    RIn: Readable<Source> + Into<Transaction>,
+    ROut: Writable,
+    Source: Read,
+    W: Write,
+{
+    transform::<RIn, ROut, Source, W, _>(source, target, |_| true)
+}
+
+/// Как `convert`, но записывает только транзакции, для которых `predicate` вернул `true`.
+///
+/// Номер записи в ошибке отсчитывается по входному потоку. Заголовок записывается перед первой
+/// оставленной записью, а если не осталось ни одной — один раз в конце. Возвращает число записанных записей.
+pub fn transform<RIn, ROut, Source, W, F>(source: Source, target: W, predicate: F) -> Result<usize, ConvertError>
+where
+    RIn: Readable<Source> + Into<Transaction>,
+    ROut: Writable,
+    Source: Read,
+    W: Write,
+    F: FnMut(&Transaction) -> bool,
+{
+    transform_with(Parser::<RIn, Source>::new(source), Serializer::<ROut, W>::new(target), predicate)
+}
+
+/// Как `transform`, но читает из заранее созданного `parser` и пишет в настроенный `serializer`.
+///
+/// Позволяет задать параметры чтения (`Parser::from_reader`) и записи (`Serializer::with_options`,
+/// `Serializer::with_sentinel`), например разделитель блоков TXT. Заголовок не записывается,
+/// если сериализатор уже записал его или создан через `Serializer::new_append`.
+pub fn transform_with<RIn, ROut, Source, W, F>(
+    parser: Parser<RIn, Source>,
+    mut serializer: Serializer<ROut, W>,
+    mut predicate: F,
+) -> Result<usize, ConvertError>
+where
+    RIn: Readable<Source> + Into<Transaction>,
+    ROut: Writable,
+    Source: Read,
+    W: Write,
+    F: FnMut(&Transaction) -> bool,
+{
+    let mut count: usize = 0;
+    let mut last_index = None;
+
+    for (index, record) in parser.enumerate() {
+        let transaction: Transaction = record.map_err(|e| ConvertError::Read { index, source: e.into() })?.into();
+        if !predicate(&transaction) {
+            continue;
+        }
+        let record = ROut::from(transaction);
+
+        if !serializer.header_written {
+            ROut::write_header_for(&mut serializer.target, &record, &mut serializer.options)
+                .map_err(|e| ConvertError::Header(e.into()))?;
+            serializer.header_written = true;
+        }
+        record
+            .write_rendered(&mut serializer.target, &serializer.sentinel, &serializer.options)
+            .map_err(|e| ConvertError::Write { index, source: e.into() })?;
+        count += 1;
+        last_index = Some(index);
+    }
+
+    if !serializer.header_written {
+        ROut::write_header(&mut serializer.target).map_err(|e| ConvertError::Header(e.into()))?;
+    }
+    // Сбрасывается последняя запись, а если записей не было — заголовок.
+    serializer.target.flush().map_err(|e| match last_index {
+        Some(index) => ConvertError::Write { index, source: e },
+        None => ConvertError::Header(e),
+    })?;
+
+    Ok(count)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -174,6 +684,7 @@ mod tests {
                 timestamp: 0,
                 transaction_status: TransactionStatus::Success,
                 description: String::new(),
+                currency: None,
             }
         }
     }
@@ -260,6 +771,7 @@ mod tests {
 
     impl Writable for MockRecord {
         type Error = MockError;
+        type Options = ();
 
         fn write_header<W: Write>(_writer: &mut W) -> Result<(), Self::Error> {
             Ok(())
@@ -278,16 +790,7 @@ mod tests {
     }
 
     fn sample_transaction(id: u64) -> Transaction {
-        Transaction {
-            id,
-            transaction_type: TransactionType::Deposit,
-            from_user_id: 0,
-            to_user_id: 0,
-            amount: 0,
-            timestamp: 0,
-            transaction_status: TransactionStatus::Success,
-            description: String::new(),
-        }
+        TransactionBuilder::new(id, TransactionType::Deposit).status(TransactionStatus::Success).build_unchecked()
     }
 
     // ==================== Parser tests ====================
@@ -296,7 +799,7 @@ mod tests {
     fn parser_reads_multiple_records() {
         let source = make_source(&[1, 2, 3]);
         let parser = Parser::<MockRecord, _>::new(source);
-        let records: Vec<MockRecord> = parser.collect();
+        let records: Vec<MockRecord> = parser.collect::<Result<_, _>>().unwrap();
 
         assert_eq!(records.len(), 3);
         assert_eq!(records[0].id, 1);
@@ -307,36 +810,32 @@ mod tests {
     #[test]
     fn parser_empty_source_yields_no_records() {
         let source = make_source(&[]);
-        let parser = Parser::<MockRecord, _>::new(source);
-        let records: Vec<MockRecord> = parser.collect();
+        let mut parser = Parser::<MockRecord, _>::new(source);
 
-        assert!(records.is_empty());
+        assert!(parser.next().is_none());
     }
 
     #[test]
     fn parser_single_record() {
         let source = make_source(&[42]);
         let parser = Parser::<MockRecord, _>::new(source);
-        let records: Vec<MockRecord> = parser.collect();
+        let records: Vec<MockRecord> = parser.collect::<Result<_, _>>().unwrap();
 
         assert_eq!(records.len(), 1);
         assert_eq!(records[0].id, 42);
     }
 
     #[test]
-    fn parser_sets_read_error_on_invalid_data() {
+    fn parser_yields_error_on_invalid_data() {
         // 3 bytes — incomplete u64, not EOF and not a valid record
         let source = Cursor::new(vec![1u8, 2, 3]);
         let mut parser = Parser::<MockRecord, _>::new(source);
-        let first = parser.next();
 
-        // Should get None (error path) and read_error should be set
-        assert!(first.is_none());
-        assert!(parser.read_error.is_some());
+        assert!(matches!(parser.next(), Some(Err(MockError::Io(_)))));
     }
 
     #[test]
-    fn parser_stops_after_error() {
+    fn parser_stops_after_fatal_error() {
         // valid record followed by incomplete data
         let mut bytes = 1u64.to_le_bytes().to_vec();
         bytes.extend_from_slice(&[0xFFu8; 3]); // incomplete
@@ -344,20 +843,192 @@ mod tests {
 
         let mut parser = Parser::<MockRecord, _>::new(source);
 
-        let first = parser.next();
-        assert!(first.is_some());
-        assert_eq!(first.unwrap().id, 1);
+        assert_eq!(parser.next().unwrap().unwrap().id, 1);
+        assert!(matches!(parser.next(), Some(Err(MockError::Io(_)))));
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn collect_returns_error_instead_of_truncated_records() {
+        let mut data = make_source(&[1, 2]).into_inner();
+        data.extend_from_slice(&[1, 2, 3]);
+
+        let result = Parser::<MockRecord, _>::new(Cursor::new(data)).collect::<Result<Vec<_>, _>>();
+        assert!(matches!(result, Err(MockError::Io(_))));
+
+        let csv = CSV_RECORDS.replace("1003,WITHDRAWAL", "1003,UNKNOWN");
+        let result = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv)).collect::<Result<Vec<_>, _>>();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn validate_all_counts_valid_records() {
+        let parser = Parser::<MockRecord, _>::new(make_source(&[1, 2, 3]));
+
+        assert_eq!(parser.validate_all().unwrap(), 3);
+    }
+
+    #[test]
+    fn validate_all_reports_index_of_first_bad_record() {
+        let data = CSV_RECORDS.replace("1003,WITHDRAWAL", "1003,UNKNOWN");
+
+        let (index, _) = Parser::<YPBankCsvRecord, _>::new(Cursor::new(data)).validate_all().unwrap_err();
+
+        assert_eq!(index, 2);
+    }
+
+    #[test]
+    fn into_vec_returns_all_records() {
+        let records = Parser::<MockRecord, _>::new(make_source(&[1, 2, 3])).into_vec().unwrap();
+
+        assert_eq!(records, vec![MockRecord { id: 1 }, MockRecord { id: 2 }, MockRecord { id: 3 }]);
+    }
+
+    #[test]
+    fn into_vec_returns_error_instead_of_truncated_records() {
+        let mut data = make_source(&[1, 2]).into_inner();
+        data.extend_from_slice(&[1, 2, 3]);
+
+        let result = Parser::<MockRecord, _>::new(Cursor::new(data)).into_vec();
+
+        assert!(matches!(result, Err(MockError::Io(_))));
+    }
+
+    #[test]
+    fn take_limited_under_limit_returns_all_records() {
+        let transactions = Parser::<MockRecord, _>::new(make_source(&[1, 2])).take_limited(3).unwrap();
+
+        assert_eq!(transactions.iter().map(|t| t.id).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn take_limited_exactly_at_limit_returns_all_records() {
+        let transactions = Parser::<MockRecord, _>::new(make_source(&[1, 2, 3])).take_limited(3).unwrap();
+
+        assert_eq!(transactions.iter().map(|t| t.id).collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn take_limited_over_limit_reports_exceeded() {
+        let result = Parser::<MockRecord, _>::new(make_source(&[1, 2, 3, 4])).take_limited(3);
+
+        assert!(matches!(result, Err(LimitError::Exceeded { limit: 3 })));
+    }
+
+    #[test]
+    fn take_limited_returns_read_error() {
+        let mut data = make_source(&[1]).into_inner();
+        data.extend_from_slice(&[1, 2, 3]);
+
+        let result = Parser::<MockRecord, _>::new(Cursor::new(data)).take_limited(3);
+
+        assert!(matches!(result, Err(LimitError::Read(MockError::Io(_)))));
+    }
+
+    #[test]
+    fn partition_valid_skips_bad_text_record() {
+        let input = "\
+TX_ID: 1
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 100
+TIMESTAMP: 1000
+STATUS: SUCCESS
+
+TX_ID: 2
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 1x0
+TIMESTAMP: 2000
+STATUS: SUCCESS
+
+TX_ID: 3
+TX_TYPE: WITHDRAWAL
+FROM_USER_ID: 10
+TO_USER_ID: 0
+AMOUNT: 50
+TIMESTAMP: 3000
+STATUS: SUCCESS
+";
+        let (transactions, errors) = Parser::<YPBankTextRecord, _>::new(Cursor::new(input)).partition_valid();
+
+        assert_eq!(transactions.iter().map(|t| t.id).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], (1, TextRecordError::ParseError { line: 13, .. })));
+    }
+
+    #[test]
+    fn partition_valid_skips_bad_csv_record() {
+        let input = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc 1\"
+2,DEPOSIT,0,10,100,2000,UNKNOWN,\"Desc 2\"
+3,WITHDRAWAL,10,0,50,3000,SUCCESS,\"Desc 3\"
+";
+        let (transactions, errors) = Parser::<YPBankCsvRecord, _>::new(Cursor::new(input)).partition_valid();
+
+        assert_eq!(transactions.iter().map(|t| t.id).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], (1, CsvRecordError::InvalidData { line: 3, .. })));
+    }
+
+    #[test]
+    fn partition_valid_stops_after_fatal_error() {
+        let mut data = make_source(&[1, 2]).into_inner();
+        data.extend_from_slice(&[1, 2, 3]);
+
+        let (transactions, errors) = Parser::<MockRecord, _>::new(Cursor::new(data)).partition_valid();
+
+        assert_eq!(transactions.len(), 2);
+        assert!(matches!(errors[..], [(2, MockError::Io(_))]));
+    }
+
+    #[test]
+    fn peek_then_next_returns_same_record() {
+        let mut parser = Parser::<MockRecord, _>::new(make_source(&[1, 2]));
+
+        assert_eq!(parser.peek().unwrap().as_ref().unwrap(), &MockRecord { id: 1 });
+        assert_eq!(parser.peek().unwrap().as_ref().unwrap(), &MockRecord { id: 1 });
+        assert_eq!(parser.next().unwrap().unwrap(), MockRecord { id: 1 });
+        assert_eq!(parser.next().unwrap().unwrap(), MockRecord { id: 2 });
+    }
+
+    #[test]
+    fn peek_at_eof_returns_none() {
+        let mut parser = Parser::<MockRecord, _>::new(make_source(&[1]));
+        parser.next();
+
+        assert!(parser.peek().is_none());
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn peeked_error_is_not_duplicated() {
+        let mut data = make_source(&[1]).into_inner();
+        data.extend_from_slice(&[1, 2, 3]);
+        let mut parser = Parser::<MockRecord, _>::new(Cursor::new(data));
+        parser.next();
+
+        assert!(matches!(parser.peek(), Some(Err(MockError::Io(_)))));
+        assert!(matches!(parser.next(), Some(Err(MockError::Io(_)))));
+        assert!(parser.peek().is_none());
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn rewind_is_unsupported_by_default() {
+        let mut parser = Parser::<MockRecord, _>::new(make_source(&[1]));
 
-        let second = parser.next();
-        assert!(second.is_none());
-        assert!(parser.read_error.is_some());
+        assert!(matches!(parser.rewind(), Err(MockError::Io(e)) if e.kind() == io::ErrorKind::Unsupported));
     }
 
     #[test]
     fn parser_converts_records_to_transactions() {
         let source = make_source(&[10, 20]);
         let parser = Parser::<MockRecord, _>::new(source);
-        let transactions: Vec<Transaction> = parser.map(|r| r.into()).collect();
+        let transactions: Vec<Transaction> = parser.map(|r| r.map(Into::into)).collect::<Result<_, _>>().unwrap();
 
         assert_eq!(transactions.len(), 2);
         assert_eq!(transactions[0].id, 10);
@@ -365,13 +1036,11 @@ mod tests {
     }
 
     #[test]
-    fn parser_no_error_after_successful_parse() {
+    fn parser_yields_no_error_after_successful_parse() {
         let source = make_source(&[1, 2]);
         let mut parser = Parser::<MockRecord, _>::new(source);
 
-        while parser.next().is_some() {}
-
-        assert!(parser.read_error.is_none());
+        assert!(parser.all(|record| record.is_ok()));
     }
 
     // ==================== Serializer tests ====================
@@ -445,7 +1114,7 @@ mod tests {
         // Parse back
         let source = Cursor::new(bytes);
         let parser = Parser::<MockRecord, _>::new(source);
-        let parsed: Vec<MockRecord> = parser.collect();
+        let parsed: Vec<MockRecord> = parser.collect::<Result<_, _>>().unwrap();
 
         assert_eq!(parsed, original);
     }
@@ -465,11 +1134,247 @@ mod tests {
         // parse → MockRecord → Transaction
         let source = Cursor::new(bytes);
         let parser = Parser::<MockRecord, _>::new(source);
-        let parsed_transactions: Vec<Transaction> = parser.map(|r| r.into()).collect();
+        let parsed_transactions: Vec<Transaction> = parser.map(|r| r.map(Into::into)).collect::<Result<_, _>>().unwrap();
 
         assert_eq!(parsed_transactions.len(), transactions.len());
         for (parsed, original) in parsed_transactions.iter().zip(transactions.iter()) {
             assert_eq!(parsed.id, original.id);
         }
     }
+
+    const CSV_RECORDS: &str = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1001,DEPOSIT,0,501,50000,1672531200000,SUCCESS,\"Initial account funding\"
+1002,TRANSFER,501,502,15000,1672534800000,FAILURE,\"Payment for services, invoice #123\"
+1003,WITHDRAWAL,502,0,1000,1672538400000,PENDING,\"ATM withdrawal\"
+";
+
+    fn csv_records() -> Vec<YPBankCsvRecord> {
+        Parser::<YPBankCsvRecord, _>::new(Cursor::new(CSV_RECORDS))
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn push_matches_batch_serialization() {
+        let records = csv_records();
+
+        let mut batch = Serializer::<YPBankCsvRecord, _>::new(Vec::new());
+        batch.serialize(csv_records()).unwrap();
+        let batch = batch.into_inner().into_inner().unwrap();
+
+        let mut incremental = Serializer::<YPBankCsvRecord, _>::new(Vec::new());
+        incremental.write_header_once().unwrap();
+        incremental.push(&records[0]).unwrap();
+        incremental.write_header_once().unwrap();
+        for record in &records[1..] {
+            incremental.push(record).unwrap();
+        }
+        let incremental = incremental.into_inner().into_inner().unwrap();
+
+        assert_eq!(incremental, batch);
+    }
+
+    #[test]
+    fn push_emits_header_once_without_explicit_call() {
+        let records = csv_records();
+
+        let mut serializer = Serializer::<YPBankCsvRecord, _>::new(Vec::new());
+        for record in &records {
+            serializer.push(record).unwrap();
+        }
+        let output = String::from_utf8(serializer.into_inner().into_inner().unwrap()).unwrap();
+
+        assert_eq!(output.matches("TX_ID").count(), 1);
+        assert_eq!(output.lines().count(), 4);
+    }
+
+    #[test]
+    fn dropped_serializer_flushes_pushed_records() {
+        let records = csv_records();
+        let mut output = Vec::new();
+
+        let mut serializer = Serializer::<YPBankCsvRecord, _>::new(&mut output);
+        for record in &records {
+            serializer.push(record).unwrap();
+        }
+        drop(serializer);
+
+        assert_eq!(Parser::<YPBankCsvRecord, _>::new(Cursor::new(output)).into_vec().unwrap(), records);
+    }
+
+    #[test]
+    fn finish_reports_flush_error() {
+        struct FailingWriter;
+
+        impl Write for FailingWriter {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("disk full"))
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut serializer = Serializer::<YPBankJsonRecord, _>::new(FailingWriter);
+        serializer.push(&Transaction::from(csv_records().remove(0)).into()).unwrap();
+
+        assert_eq!(serializer.finish().err().unwrap().to_string(), "disk full");
+    }
+
+    #[test]
+    fn parse_csv_str_matches_parser() {
+        let expected: Vec<Transaction> = csv_records().into_iter().map(Into::into).collect();
+
+        assert_eq!(parse_csv_str(CSV_RECORDS).unwrap(), expected);
+        assert!(parse_csv_str("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_csv_str_returns_first_error() {
+        let data = CSV_RECORDS.replace("1003,WITHDRAWAL", "1003,UNKNOWN");
+
+        assert!(matches!(parse_csv_str(&data), Err(CsvRecordError::InvalidData { line: 4, .. })));
+    }
+
+    #[test]
+    fn parse_txt_str_matches_parser() {
+        let text = String::from_utf8(serialize_to_vec(&csv_records().into_iter().map(YPBankTextRecord::from).collect::<Vec<_>>()).unwrap()).unwrap();
+        let expected: Vec<Transaction> = Parser::<YPBankTextRecord, _>::new(Cursor::new(text.clone()))
+            .map(|r| r.map(Into::into))
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(parse_txt_str(&text).unwrap(), expected);
+        assert!(matches!(parse_txt_str("TX_ID 1\n"), Err(TextRecordError::MissingColonAfterKey { line: 1, .. })));
+    }
+
+    #[test]
+    fn to_path_writes_file_that_reads_back() {
+        let path = std::env::temp_dir().join(format!("ypbank_to_path_{}.csv", std::process::id()));
+        let records: Vec<YPBankCsvRecord> = Parser::<YPBankCsvRecord, _>::new(Cursor::new(CSV_RECORDS)).into_vec().unwrap();
+
+        let mut serializer = Serializer::<YPBankCsvRecord, _>::to_path(&path).unwrap();
+        for record in &records {
+            serializer.push(record).unwrap();
+        }
+        serializer.finish().unwrap();
+
+        let read_back = Parser::<YPBankCsvRecord, _>::new(std::fs::File::open(&path).unwrap()).into_vec().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read_back, records);
+    }
+
+    #[test]
+    fn append_serializer_does_not_repeat_header() {
+        let mut existing = Vec::new();
+        Serializer::<YPBankCsvRecord, _>::new(&mut existing).serialize(csv_records().into_iter().take(1)).unwrap();
+
+        let mut appender = Serializer::<YPBankCsvRecord, _>::new_append(&mut existing);
+        appender.serialize(csv_records().into_iter().skip(1)).unwrap();
+        drop(appender);
+
+        let output = String::from_utf8(existing).unwrap();
+        assert_eq!(output.matches("TX_ID,").count(), 1);
+        assert_eq!(
+            Parser::<YPBankCsvRecord, _>::new(Cursor::new(output)).map(|r| r.unwrap().id()).collect::<Vec<_>>(),
+            csv_records().iter().map(|r| r.id()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn convert_streams_csv_to_txt_and_back() {
+        let mut text = Vec::new();
+        let count = convert::<YPBankCsvRecord, YPBankTextRecord, _, _>(Cursor::new(CSV_RECORDS), &mut text).unwrap();
+        assert_eq!(count, 3);
+
+        let mut csv = Vec::new();
+        convert::<YPBankTextRecord, YPBankCsvRecord, _, _>(Cursor::new(text), &mut csv).unwrap();
+
+        let parse = |data: &[u8]| -> Vec<Transaction> {
+            Parser::<YPBankCsvRecord, _>::new(Cursor::new(data.to_vec()))
+                .map(|r| r.map(Into::into))
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+        assert_eq!(parse(&csv), parse(CSV_RECORDS.as_bytes()));
+    }
+
+    #[test]
+    fn description_is_stable_across_txt_and_csv() {
+        let original: Vec<Transaction> = ["Plain", "He said \"hi\"", "\"edge\"", "\""]
+            .into_iter()
+            .enumerate()
+            .map(|(i, description)| Transaction { description: description.to_string(), ..sample_transaction(i as u64) })
+            .collect();
+        let text_records: Vec<YPBankTextRecord> = original.iter().cloned().map(Into::into).collect();
+
+        let text = serialize_to_vec(&text_records).unwrap();
+        let mut csv = Vec::new();
+        convert::<YPBankTextRecord, YPBankCsvRecord, _, _>(Cursor::new(text), &mut csv).unwrap();
+        let mut text = Vec::new();
+        convert::<YPBankCsvRecord, YPBankTextRecord, _, _>(Cursor::new(csv), &mut text).unwrap();
+
+        let round_tripped: Vec<Transaction> = Parser::<YPBankTextRecord, _>::new(Cursor::new(text))
+            .map(|r| r.map(Into::into))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(round_tripped, original);
+    }
+
+    #[test]
+    fn convert_reports_index_of_failed_record() {
+        let data = CSV_RECORDS.replace("1002,TRANSFER", "1002,UNKNOWN");
+        let mut text = Vec::new();
+
+        let error = convert::<YPBankCsvRecord, YPBankTextRecord, _, _>(Cursor::new(data), &mut text).unwrap_err();
+
+        assert!(matches!(error, ConvertError::Read { index: 1, .. }));
+        assert!(String::from_utf8(text).unwrap().contains("TX_ID: 1001"));
+    }
+
+    #[test]
+    fn transform_keeps_only_matching_records() {
+        let mut text = Vec::new();
+
+        let count = transform::<YPBankCsvRecord, YPBankTextRecord, _, _, _>(Cursor::new(CSV_RECORDS), &mut text, |t| {
+            t.transaction_status == TransactionStatus::Success
+        })
+        .unwrap();
+
+        let expected: Vec<Transaction> = parse_csv_str(CSV_RECORDS)
+            .unwrap()
+            .into_iter()
+            .filter(|t| t.transaction_status == TransactionStatus::Success)
+            .collect();
+        assert_eq!(count, 1);
+        assert_eq!(parse_txt_str(std::str::from_utf8(&text).unwrap()).unwrap(), expected);
+    }
+
+    #[test]
+    fn transform_reports_input_index_of_failed_record() {
+        let data = CSV_RECORDS.replace("1003,WITHDRAWAL", "1003,UNKNOWN");
+        let mut text = Vec::new();
+
+        let error = transform::<YPBankCsvRecord, YPBankTextRecord, _, _, _>(Cursor::new(data), &mut text, |t| t.id != 1001).unwrap_err();
+
+        assert!(matches!(error, ConvertError::Read { index: 2, .. }));
+    }
+
+    #[test]
+    fn transform_with_writes_and_reads_marker_separated_text() {
+        let separator = RecordSeparator::marker("---").unwrap();
+        let mut text = Vec::new();
+
+        let serializer = Serializer::<YPBankTextRecord, _>::new(&mut text).with_options(separator.clone());
+        let count = transform_with(Parser::<YPBankCsvRecord, _>::new(Cursor::new(CSV_RECORDS)), serializer, |_| true).unwrap();
+
+        let mut csv = Vec::new();
+        let reader = TextReader::new(Cursor::new(text.clone())).record_separator(separator);
+        transform_with(Parser::<YPBankTextRecord, _>::from_reader(reader), Serializer::<YPBankCsvRecord, _>::new(&mut csv), |_| true).unwrap();
+        assert_eq!(count, 3);
+        assert!(String::from_utf8(text).unwrap().contains("\n---\n"));
+        assert_eq!(parse_csv_str(std::str::from_utf8(&csv).unwrap()).unwrap(), parse_csv_str(CSV_RECORDS).unwrap());
+    }
 }
@@ -0,0 +1,257 @@
+use crate::common::Transaction;
+use crate::errors::{MergeError, ParseError};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io::{Error, ErrorKind};
+
+/// Источник транзакций для `KWayMerge` с произвольным форматом и типом чтения.
+pub type BoxedParser = Box<dyn Iterator<Item = Result<Transaction, ParseError>>>;
+
+/// Общее слияние отсортированных по `timestamp` потоков: порядок выдачи задает ключ `key`.
+///
+/// При равных ключах раньше идет запись из источника с меньшим индексом. В куче находится
+/// не больше одной записи из каждого источника.
+struct SortedMerge<I, E, K> {
+    sources: Vec<I>,
+    heads: Vec<Option<Transaction>>,
+    heap: BinaryHeap<Reverse<(K, usize)>>,
+    key: fn(&Transaction) -> K,
+    started: bool,
+    pending_error: Option<MergeError<E>>,
+    finished: bool,
+}
+
+impl<I, T, E, K> SortedMerge<I, E, K>
+where
+    I: Iterator<Item = Result<T, E>>,
+    T: Into<Transaction>,
+    K: Ord,
+{
+    fn new(sources: Vec<I>, key: fn(&Transaction) -> K) -> Self {
+        let heads = sources.iter().map(|_| None).collect();
+
+        Self {
+            sources,
+            heads,
+            heap: BinaryHeap::new(),
+            key,
+            started: false,
+            pending_error: None,
+            finished: false,
+        }
+    }
+
+    /// Читает следующую запись источника `index` в кучу, проверяя порядок меток.
+    fn advance(&mut self, index: usize, previous: Option<u64>) -> Result<(), MergeError<E>> {
+        let Some(transaction) = self.sources[index].next().transpose().map_err(MergeError::Source)? else {
+            return Ok(());
+        };
+        let transaction: Transaction = transaction.into();
+
+        if let Some(previous) = previous
+            && transaction.timestamp < previous
+        {
+            return Err(MergeError::OutOfOrder {
+                source_index: index,
+                id: transaction.id,
+                timestamp: transaction.timestamp,
+                previous,
+            });
+        }
+
+        self.heap.push(Reverse(((self.key)(&transaction), index)));
+        self.heads[index] = Some(transaction);
+
+        Ok(())
+    }
+}
+
+impl<I, T, E, K> Iterator for SortedMerge<I, E, K>
+where
+    I: Iterator<Item = Result<T, E>>,
+    T: Into<Transaction>,
+    K: Ord,
+{
+    type Item = Result<Transaction, MergeError<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        if let Some(e) = self.pending_error.take() {
+            self.finished = true;
+            return Some(Err(e));
+        }
+
+        if !self.started {
+            self.started = true;
+            for index in 0..self.sources.len() {
+                if let Err(e) = self.advance(index, None) {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        let Reverse((_, index)) = self.heap.pop()?;
+        let transaction = self.heads[index].take()?;
+
+        if let Err(e) = self.advance(index, Some(transaction.timestamp)) {
+            self.pending_error = Some(e);
+        }
+
+        Some(Ok(transaction))
+    }
+}
+
+/// Слияние нескольких отсортированных по `timestamp` потоков в один.
+///
+/// Выдает записи в общем порядке временных меток, используя двоичную кучу; при равных метках
+/// раньше идет запись из источника с меньшим индексом. Если какой-либо источник оказывается
+/// неупорядоченным или возвращает ошибку, итератор выдает её и завершается.
+pub struct KWayMerge(SortedMerge<BoxedParser, ParseError, u64>);
+
+impl KWayMerge {
+    /// Создает слияние указанных источников.
+    pub fn new(parsers: Vec<BoxedParser>) -> Self {
+        Self(SortedMerge::new(parsers, |transaction| transaction.timestamp))
+    }
+}
+
+impl Iterator for KWayMerge {
+    type Item = Result<Transaction, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|item| {
+            item.map_err(|e| match e {
+                MergeError::Source(e) => e,
+                MergeError::OutOfOrder { source_index, id, .. } => {
+                    let message = format!("Source {} is not sorted by timestamp at transaction {}", source_index, id);
+                    Error::new(ErrorKind::InvalidData, message).into()
+                }
+            })
+        })
+    }
+}
+
+/// Слияние отсортированных по `timestamp` потоков с произвольным типом записей и ошибок.
+///
+/// Создается функцией `merge_by_timestamp`.
+pub struct MergeByTimestamp<I, E>(SortedMerge<I, E, (u64, u64)>);
+
+/// Сливает потоки, каждый из которых отсортирован по `timestamp`, в один упорядоченный поток.
+///
+/// В памяти одновременно находится не больше одной записи из каждого источника. При равных
+/// метках раньше идет запись с меньшим `id`. Ошибка источника или нарушение им порядка
+/// (`MergeError::OutOfOrder`) выдается после уже готовых записей и завершает слияние.
+pub fn merge_by_timestamp<I, T, E>(parsers: Vec<I>) -> MergeByTimestamp<I, E>
+where
+    I: Iterator<Item = Result<T, E>>,
+    T: Into<Transaction>,
+{
+    MergeByTimestamp(SortedMerge::new(parsers, |transaction| (transaction.timestamp, transaction.id)))
+}
+
+impl<I, T, E> Iterator for MergeByTimestamp<I, E>
+where
+    I: Iterator<Item = Result<T, E>>,
+    T: Into<Transaction>,
+{
+    type Item = Result<Transaction, MergeError<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Parser, YPBankCsvRecord};
+    use std::io::Cursor;
+
+    fn csv_parser(rows: &[(u64, u64)]) -> Parser<YPBankCsvRecord, Cursor<String>> {
+        let mut csv_data = String::from("TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n");
+        for (id, timestamp) in rows {
+            csv_data.push_str(&format!("{},DEPOSIT,0,10,100,{},SUCCESS,\"Desc\"\n", id, timestamp));
+        }
+
+        Parser::new(Cursor::new(csv_data))
+    }
+
+    fn csv_source(rows: &[(u64, u64)]) -> BoxedParser {
+        Box::new(csv_parser(rows).map(|record| record.map(Into::into).map_err(Into::into)))
+    }
+
+    #[test]
+    fn merges_three_sorted_streams() {
+        let merge = KWayMerge::new(vec![
+            csv_source(&[(1, 100), (2, 400), (3, 700)]),
+            csv_source(&[(4, 200), (5, 500)]),
+            csv_source(&[(6, 50), (7, 400), (8, 900)]),
+        ]);
+
+        let transactions: Vec<Transaction> = merge.collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(
+            transactions.iter().map(|t| t.timestamp).collect::<Vec<_>>(),
+            vec![50, 100, 200, 400, 400, 500, 700, 900]
+        );
+        assert_eq!(transactions.iter().map(|t| t.id).collect::<Vec<_>>(), vec![6, 1, 4, 2, 7, 5, 3, 8]);
+    }
+
+    #[test]
+    fn unsorted_source_is_an_error() {
+        let mut merge = KWayMerge::new(vec![
+            csv_source(&[(1, 100), (2, 300)]),
+            csv_source(&[(3, 200), (4, 150)]),
+        ]);
+
+        assert_eq!(merge.next().unwrap().unwrap().id, 1);
+        assert_eq!(merge.next().unwrap().unwrap().id, 3);
+        assert!(matches!(merge.next(), Some(Err(ParseError::Io(e))) if e.kind() == ErrorKind::InvalidData));
+        assert!(merge.next().is_none());
+    }
+
+    fn merged_ids(parsers: Vec<Parser<YPBankCsvRecord, Cursor<String>>>) -> Vec<u64> {
+        merge_by_timestamp(parsers).map(|t| t.unwrap().id).collect()
+    }
+
+    #[test]
+    fn merge_by_timestamp_interleaves_two_streams() {
+        let ids = merged_ids(vec![
+            csv_parser(&[(1, 100), (3, 300), (5, 500)]),
+            csv_parser(&[(2, 200), (4, 400), (6, 600), (7, 700)]),
+        ]);
+
+        assert_eq!(ids, vec![1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn merge_by_timestamp_breaks_ties_by_id() {
+        let ids = merged_ids(vec![
+            csv_parser(&[(9, 100), (3, 400), (8, 900)]),
+            csv_parser(&[(5, 200), (1, 400)]),
+            csv_parser(&[(6, 50), (2, 400), (7, 900)]),
+        ]);
+
+        assert_eq!(ids, vec![6, 9, 5, 1, 2, 3, 7, 8]);
+    }
+
+    #[test]
+    fn merge_by_timestamp_reports_out_of_order_source() {
+        let mut merge = merge_by_timestamp(vec![
+            csv_parser(&[(1, 100), (2, 300)]),
+            csv_parser(&[(3, 200), (4, 150)]),
+        ]);
+
+        assert_eq!(merge.next().unwrap().unwrap().id, 1);
+        assert_eq!(merge.next().unwrap().unwrap().id, 3);
+        assert!(matches!(
+            merge.next(),
+            Some(Err(MergeError::OutOfOrder { source_index: 1, id: 4, timestamp: 150, previous: 200 }))
+        ));
+        assert!(merge.next().is_none());
+    }
+}
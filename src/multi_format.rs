@@ -0,0 +1,199 @@
+use crate::common::Transaction;
+use crate::errors::ParseError;
+use crate::{Parser, Readable, YPBankCsvRecord, YPBankJsonRecord, YPBankTextRecord};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Cursor, Error, ErrorKind, Read};
+
+/// Префикс строки-маркера, задающей формат следующего фрагмента потока.
+const FORMAT_MARKER_PREFIX: &[u8] = b"@format";
+
+/// Заголовок, подставляемый перед CSV-фрагментом без собственного заголовка.
+const CSV_HEADER: &[u8] = b"TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n";
+
+/// Парсер склеенного потока, в котором записи разных форматов предваряются маркерами.
+///
+/// Каждый фрагмент начинается строкой `@format csv`, `@format txt` или `@format json`
+/// и продолжается до следующего маркера или конца потока. CSV-фрагмент может не содержать
+/// заголовка — в этом случае используется стандартный. Неизвестный маркер завершает чтение ошибкой.
+pub struct MultiFormatParser<R> {
+    inner: BufReader<R>,
+    /// Маркер следующего фрагмента, прочитанный при поиске конца текущего.
+    next_marker: Option<Vec<u8>>,
+    pending: VecDeque<Result<Transaction, ParseError>>,
+    finished: bool,
+}
+
+impl<R: Read> MultiFormatParser<R> {
+    /// Создает парсер поверх источника данных.
+    pub fn new(source: R) -> Self {
+        Self {
+            inner: BufReader::new(source),
+            next_marker: None,
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// Читает следующий фрагмент и разбирает его записи (и ошибки разбора) в `pending`.
+    ///
+    /// Возвращает `Ok(false)`, если поток закончился.
+    fn read_segment(&mut self) -> Result<bool, ParseError> {
+        let marker = match self.next_marker.take() {
+            Some(marker) => marker,
+            None => {
+                let mut line = Vec::new();
+                loop {
+                    line.clear();
+                    if self.inner.read_until(b'\n', &mut line)? == 0 {
+                        return Ok(false);
+                    }
+                    if !line.trim_ascii().is_empty() {
+                        break;
+                    }
+                }
+
+                if !is_marker(&line) {
+                    return Err(Error::new(ErrorKind::InvalidData, "Expected @format marker before records").into());
+                }
+                line
+            }
+        };
+
+        let mut segment = Vec::new();
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            if self.inner.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+            if is_marker(&line) {
+                self.next_marker = Some(line);
+                break;
+            }
+            segment.extend_from_slice(&line);
+        }
+
+        let format = marker[FORMAT_MARKER_PREFIX.len()..].trim_ascii();
+        match format {
+            b"csv" => {
+                if !segment.trim_ascii_start().starts_with(b"TX_ID") {
+                    segment.splice(0..0, CSV_HEADER.iter().copied());
+                }
+                self.parse_segment::<YPBankCsvRecord>(segment);
+            }
+            b"txt" => self.parse_segment::<YPBankTextRecord>(segment),
+            b"json" => self.parse_segment::<YPBankJsonRecord>(segment),
+            other => {
+                let message = format!("Unknown format marker: {}", String::from_utf8_lossy(other));
+                return Err(Error::new(ErrorKind::InvalidData, message).into());
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn parse_segment<TRecord>(&mut self, segment: Vec<u8>)
+    where
+        TRecord: Readable<Cursor<Vec<u8>>> + Into<Transaction>,
+        TRecord::Error: Into<ParseError>,
+    {
+        let parser = Parser::<TRecord, _>::new(Cursor::new(segment));
+        self.pending.extend(parser.map(|record| record.map(Into::into).map_err(Into::into)));
+    }
+}
+
+fn is_marker(line: &[u8]) -> bool {
+    line.trim_ascii_start()
+        .strip_prefix(FORMAT_MARKER_PREFIX)
+        .is_some_and(|rest| rest.first().is_some_and(u8::is_ascii_whitespace))
+}
+
+impl<R: Read> Iterator for MultiFormatParser<R> {
+    type Item = Result<Transaction, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(item);
+            }
+
+            if self.finished {
+                return None;
+            }
+
+            match self.read_segment() {
+                Ok(true) => continue,
+                Ok(false) => {
+                    self.finished = true;
+                    return None;
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alternating_csv_text_and_json_records() {
+        let input = "\
+@format csv
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"From CSV\"
+@format txt
+TX_ID: 2
+TX_TYPE: WITHDRAWAL
+FROM_USER_ID: 10
+TO_USER_ID: 0
+AMOUNT: 50
+TIMESTAMP: 2000
+STATUS: PENDING
+DESCRIPTION: \"From text\"
+
+@format csv
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+3,TRANSFER,10,11,5,3000,FAILURE,\"With header\"
+@format json
+{\"TX_ID\":4,\"TX_TYPE\":\"DEPOSIT\",\"FROM_USER_ID\":0,\"TO_USER_ID\":12,\"AMOUNT\":9,\"TIMESTAMP\":4000,\"STATUS\":\"SUCCESS\",\"DESCRIPTION\":\"From JSON\"}
+
+@format txt
+TX_ID: 5
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 13
+AMOUNT: 1
+TIMESTAMP: 5000
+STATUS: SUCCESS
+DESCRIPTION: \"Text again\"
+";
+        let mut parser = MultiFormatParser::new(Cursor::new(input));
+        let transactions: Vec<Transaction> = parser.by_ref().collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(transactions.iter().map(|t| t.id).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(transactions[0].description, "From CSV");
+        assert_eq!(transactions[1].description, "From text");
+        assert_eq!(transactions[2].amount, 5);
+        assert_eq!(transactions[3].description, "From JSON");
+        assert_eq!(transactions[4].to_user_id, 13);
+    }
+
+    #[test]
+    fn unknown_marker_is_an_error() {
+        let input = "\
+@format csv
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"From CSV\"
+@format xml
+<tx id=\"2\"/>
+";
+        let mut parser = MultiFormatParser::new(Cursor::new(input));
+
+        assert_eq!(parser.next().unwrap().unwrap().id, 1);
+        assert!(matches!(parser.next(), Some(Err(ParseError::Io(e))) if e.kind() == ErrorKind::InvalidData));
+        assert!(parser.next().is_none());
+    }
+}
@@ -0,0 +1,219 @@
+use crate::errors::{OrderError, UnsortedIdError};
+use crate::{IsEofError, Parser, Readable, Transaction};
+use std::io::Read;
+use std::ops::Range;
+
+/// Проверяет, что записи в источнике упорядочены по неубыванию `TX_ID`.
+///
+/// Используется как дешёвая предварительная проверка: форматы, поддерживающие чтение
+/// одного идентификатора (например, CSV), не разбирают остальные поля записи.
+/// Возвращает `Ok(false)` на первой записи, идентификатор которой меньше предыдущего.
+pub fn is_sorted_by_id<TRecord, Source>(source: Source) -> Result<bool, TRecord::Error>
+where
+    TRecord: Readable<Source> + Into<Transaction>,
+    Source: Read
+{
+    let mut reader = TRecord::build_reader(source);
+    let mut previous_id = None;
+
+    loop {
+        match TRecord::read_id(&mut reader) {
+            Ok(id) => {
+                if previous_id.is_some_and(|previous| id < previous) {
+                    return Ok(false);
+                }
+
+                previous_id = Some(id);
+            }
+            Err(e) if e.is_eof() => return Ok(true),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Находит пропуски в последовательности идентификаторов, упорядоченных по возрастанию.
+///
+/// Возвращает диапазоны отсутствующих `TX_ID` между первым и последним идентификатором.
+/// Повторяющиеся идентификаторы допускаются; убывание идентификатора является ошибкой.
+pub fn find_id_gaps<I>(records: I) -> Result<Vec<Range<u64>>, UnsortedIdError>
+where
+    I: IntoIterator<Item = Transaction>,
+{
+    let mut gaps = Vec::new();
+    let mut previous: Option<u64> = None;
+
+    for transaction in records {
+        let id = transaction.id;
+
+        if let Some(previous) = previous {
+            if id < previous {
+                return Err(UnsortedIdError { previous, id });
+            }
+
+            if id - previous > 1 {
+                gaps.push(previous.saturating_add(1)..id);
+            }
+        }
+
+        previous = Some(id);
+    }
+
+    Ok(gaps)
+}
+
+impl<TRecord, Source> Parser<TRecord, Source>
+where
+    TRecord: Readable<Source> + Into<Transaction>,
+    Source: Read
+{
+    /// Проверяет, что временные метки записей не убывают; равные соседние метки допустимы.
+    ///
+    /// Записи читаются потоково и не накапливаются. Возвращает первую запись, метка которой
+    /// меньше метки предыдущей, или первую ошибку чтения.
+    pub fn check_monotonic_timestamps(self) -> Result<(), OrderError<TRecord::Error>> {
+        let mut previous = None;
+
+        for (index, record) in self.enumerate() {
+            let timestamp = record.map_err(|source| OrderError::Read { index, source })?.into().timestamp;
+
+            if let Some(previous) = previous
+                && timestamp < previous
+            {
+                return Err(OrderError::OutOfOrder { index, previous, timestamp });
+            }
+
+            previous = Some(timestamp);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CsvRecordError, YPBankCsvRecord, YPBankTextRecord};
+    use std::io::Cursor;
+
+    #[test]
+    fn csv_sorted_file_is_detected() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc 1\"
+2,WITHDRAWAL,10,0,50,2000,PENDING,\"Desc 2\"
+2,DEPOSIT,0,10,100,3000,SUCCESS,\"Same id\"
+7,TRANSFER,10,11,5,4000,FAILURE,\"Desc 7\"
+";
+
+        let result = is_sorted_by_id::<YPBankCsvRecord, _>(Cursor::new(csv_data));
+
+        assert!(result.unwrap());
+    }
+
+    #[test]
+    fn csv_unsorted_file_is_detected() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc 1\"
+5,WITHDRAWAL,10,0,50,2000,PENDING,\"Desc 5\"
+3,DEPOSIT,0,10,100,3000,SUCCESS,\"Desc 3\"
+";
+
+        let result = is_sorted_by_id::<YPBankCsvRecord, _>(Cursor::new(csv_data));
+
+        assert!(!result.unwrap());
+    }
+
+    #[test]
+    fn csv_invalid_id_is_an_error() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+NOT_A_NUMBER,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc\"
+";
+
+        let err = is_sorted_by_id::<YPBankCsvRecord, _>(Cursor::new(csv_data)).unwrap_err();
+
+        assert!(matches!(err, CsvRecordError::InvalidData { line: 2, .. }));
+    }
+
+    #[test]
+    fn txt_falls_back_to_full_parsing() {
+        let input = r#"
+TX_ID: 2
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 100
+TIMESTAMP: 1
+STATUS: SUCCESS
+DESCRIPTION: "Second"
+
+TX_ID: 1
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 100
+TIMESTAMP: 2
+STATUS: SUCCESS
+DESCRIPTION: "First"
+"#;
+
+        let result = is_sorted_by_id::<YPBankTextRecord, _>(Cursor::new(input.as_bytes()));
+
+        assert!(!result.unwrap());
+    }
+
+    fn with_ids(ids: &[u64]) -> Vec<Transaction> {
+        ids.iter()
+            .map(|&id| Transaction {
+                id,
+                transaction_type: crate::TransactionType::Deposit,
+                from_user_id: 0,
+                to_user_id: 10,
+                amount: 100,
+                timestamp: id,
+                transaction_status: crate::TransactionStatus::Success,
+                description: String::new(),
+                currency: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn id_gaps_are_reported_as_ranges() {
+        assert_eq!(find_id_gaps(with_ids(&[1, 2, 4, 5, 8])), Ok(vec![3..4, 6..8]));
+        assert_eq!(find_id_gaps(with_ids(&[1, 1, 2, 2, 4, 7])), Ok(vec![3..4, 5..7]));
+        assert_eq!(find_id_gaps(with_ids(&[u64::MAX - 4, u64::MAX - 2, u64::MAX, u64::MAX])), Ok(vec![u64::MAX - 3..u64::MAX - 2, u64::MAX - 1..u64::MAX]));
+    }
+
+    #[test]
+    fn id_gaps_reject_unsorted_ids() {
+        assert_eq!(find_id_gaps(with_ids(&[1, 3, 2])), Err(UnsortedIdError { previous: 3, id: 2 }));
+    }
+
+    fn check_timestamps(timestamps: &[u64]) -> Result<(), OrderError<crate::CsvRecordError>> {
+        let mut csv_data = String::from("TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n");
+        for (i, timestamp) in timestamps.iter().enumerate() {
+            csv_data.push_str(&format!("{},DEPOSIT,0,10,100,{},SUCCESS,\"\"\n", i + 1, timestamp));
+        }
+
+        Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data)).check_monotonic_timestamps()
+    }
+
+    #[test]
+    fn in_order_timestamps_pass() {
+        assert!(check_timestamps(&[100, 200, 300]).is_ok());
+    }
+
+    #[test]
+    fn equal_adjacent_timestamps_are_allowed() {
+        assert!(check_timestamps(&[100, 200, 200, 300]).is_ok());
+    }
+
+    #[test]
+    fn first_out_of_order_record_is_reported() {
+        let result = check_timestamps(&[100, 300, 200, 50]);
+
+        assert!(matches!(result, Err(OrderError::OutOfOrder { index: 2, previous: 300, timestamp: 200 })));
+    }
+}
@@ -0,0 +1,169 @@
+use crate::common::Transaction;
+use crate::errors::CsvRecordError;
+use crate::{Parser, YPBankCsvRecord};
+use std::io::Read;
+
+/// Разбирает CSV-источник в несколько потоков, сохраняя исходный порядок записей.
+///
+/// Источник читается в память целиком и делится на `num_threads` частей по границам записей;
+/// переводы строк внутри кавычек (многострочные описания) границей не считаются. Каждая часть
+/// разбирается отдельным потоком с заголовком исходного файла. Возвращается первая по порядку
+/// ошибка; номер строки в ней указан относительно исходного файла. Паника потока разбора
+/// возвращается как `CsvRecordError::Io`.
+pub fn parse_parallel<R: Read + Send>(mut source: R, num_threads: usize) -> Result<Vec<Transaction>, CsvRecordError> {
+    let mut data = Vec::new();
+    source.read_to_end(&mut data)?;
+
+    let header_len = data.iter().position(|&b| b == b'\n').map_or(data.len(), |pos| pos + 1);
+    let (header, body) = data.split_at(header_len);
+    let points = split_points(body, num_threads.max(1));
+
+    let chunks: Vec<Result<Vec<Transaction>, CsvRecordError>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = points
+            .windows(2)
+            .map(|bounds| {
+                let chunk = &body[bounds[0]..bounds[1]];
+                // строка файла, с которой начинается часть: заголовок занимает первую
+                let first_line = 2 + body[..bounds[0]].iter().filter(|&&b| b == b'\n').count() as u64;
+                scope.spawn(move || parse_chunk(header, chunk, first_line))
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err(std::io::Error::other("CSV parsing thread panicked").into()))
+            })
+            .collect()
+    });
+
+    let mut transactions = Vec::new();
+    for chunk in chunks {
+        transactions.extend(chunk?);
+    }
+
+    Ok(transactions)
+}
+
+/// Начала частей `body` (и конец последней), выровненные по переводам строк вне кавычек.
+fn split_points(body: &[u8], parts: usize) -> Vec<usize> {
+    let target = body.len() / parts + 1;
+    let mut points = vec![0];
+    let mut next = target;
+    let mut in_quotes = false;
+
+    for (i, &b) in body.iter().enumerate() {
+        match b {
+            // удвоенная кавычка внутри поля переключает состояние дважды и ничего не меняет
+            b'"' => in_quotes = !in_quotes,
+            b'\n' if !in_quotes && i + 1 >= next => {
+                points.push(i + 1);
+                next = i + 1 + target;
+            }
+            _ => {}
+        }
+    }
+
+    if points.last() != Some(&body.len()) {
+        points.push(body.len());
+    }
+
+    points
+}
+
+/// Разбирает часть файла; `first_line` — номер строки исходного файла, с которой она начинается.
+fn parse_chunk(header: &[u8], chunk: &[u8], first_line: u64) -> Result<Vec<Transaction>, CsvRecordError> {
+    Parser::<YPBankCsvRecord, _>::new(header.chain(chunk))
+        .map(|record| record.map(Transaction::from))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| match error {
+            // в отдельном разборе части первая строка записей имеет номер 2; номер записи
+            // относительно исходного файла без разбора предыдущих частей неизвестен
+            CsvRecordError::InvalidData { line, field, source, .. } => {
+                CsvRecordError::InvalidData { line: original_line(line, first_line), record: None, field, source }
+            }
+            CsvRecordError::FieldOverflow { line, field, value, .. } => {
+                CsvRecordError::FieldOverflow { line: original_line(line, first_line), record: None, field, value }
+            }
+            other => other,
+        })
+}
+
+/// Переводит номер строки части в номер строки исходного файла.
+///
+/// Строки 0 (номер неизвестен) и 1 (заголовок) у всех частей общие с исходным файлом и не сдвигаются.
+fn original_line(line: u64, first_line: u64) -> u64 {
+    match line {
+        0 | 1 => line,
+        _ => line - 2 + first_line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn csv_data(count: u64) -> String {
+        let mut data = String::from("TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n");
+        for i in 0..count {
+            match i % 3 {
+                0 => data.push_str(&format!("{},DEPOSIT,0,10,{},{},SUCCESS,\"Plain {}\"\n", i, i + 1, i * 10, i)),
+                1 => data.push_str(&format!("{},TRANSFER,10,20,{},{},PENDING,\"Line one\nline \"\"two\"\"\"\n", i, i + 1, i * 10)),
+                _ => data.push_str(&format!("{},WITHDRAWAL,20,0,{},{},FAILURE,\"Comma, inside\"\n", i, i + 1, i * 10)),
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn parallel_result_matches_sequential() {
+        let data = csv_data(3000);
+        let sequential: Vec<Transaction> = Parser::<YPBankCsvRecord, _>::new(Cursor::new(data.clone()))
+            .map(|record| record.map(Transaction::from))
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        for threads in [1, 2, 7] {
+            let parallel = parse_parallel(Cursor::new(data.clone()), threads).unwrap();
+            assert_eq!(parallel, sequential);
+        }
+        assert_eq!(sequential.len(), 3000);
+    }
+
+    #[test]
+    fn split_points_skip_newlines_inside_quotes() {
+        let body = b"1,\"a\nb\"\n2,\"c\"\n";
+
+        assert_eq!(split_points(body, 4), vec![0, 8, 14]);
+    }
+
+    #[test]
+    fn error_line_refers_to_original_file() {
+        let data = csv_data(100).replacen("90,DEPOSIT", "90,BROKEN", 1);
+        // записи с остатком 1 от деления id на 3 занимают по две строки
+        let line = 2 + (0..90u64).map(|i| if i % 3 == 1 { 2 } else { 1 }).sum::<u64>();
+
+        let error = parse_parallel(Cursor::new(data), 4).unwrap_err();
+
+        assert!(matches!(error, CsvRecordError::InvalidData { line: l, .. } if l == line));
+    }
+
+    #[test]
+    fn invalid_header_is_reported_as_error() {
+        let mut data = csv_data(100).into_bytes();
+        data[3] = 0xFF;
+
+        for threads in [1, 4] {
+            let error = parse_parallel(Cursor::new(data.clone()), threads).unwrap_err();
+            assert!(matches!(error, CsvRecordError::InvalidData { line: 0 | 1, .. }), "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn empty_source_yields_no_transactions() {
+        assert!(parse_parallel(Cursor::new(""), 4).unwrap().is_empty());
+    }
+}
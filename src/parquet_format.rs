@@ -0,0 +1,148 @@
+use crate::common::Transaction;
+use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::{SerializedFileWriter, SerializedRowGroupWriter};
+use parquet::schema::parser::parse_message_type;
+use std::io::{Error, Seek, Write};
+use std::sync::Arc;
+
+/// Схема Parquet-файла: восемь полей транзакции, перечисления хранятся строками.
+const PARQUET_SCHEMA: &str = "
+message transaction {
+    REQUIRED INT64 TX_ID (INTEGER(64, false));
+    REQUIRED BYTE_ARRAY TX_TYPE (STRING);
+    REQUIRED INT64 FROM_USER_ID (INTEGER(64, false));
+    REQUIRED INT64 TO_USER_ID (INTEGER(64, false));
+    REQUIRED INT64 AMOUNT;
+    REQUIRED INT64 TIMESTAMP (INTEGER(64, false));
+    REQUIRED BYTE_ARRAY STATUS (STRING);
+    REQUIRED BYTE_ARRAY DESCRIPTION (STRING);
+}
+";
+
+/// Записывает транзакции в формате Parquet группами строк по `batch_size` записей.
+///
+/// Строковые столбцы (`TX_TYPE`, `STATUS`, `DESCRIPTION`) пишутся со словарным кодированием.
+/// `batch_size`, равный нулю, трактуется как единица. Валюты в схеме нет, и она отбрасывается.
+pub fn write_parquet<I, W>(records: I, writer: W, batch_size: usize) -> Result<(), Error>
+where
+    I: IntoIterator<Item = Transaction>,
+    W: Write + Seek + Send,
+{
+    let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+    let properties = Arc::new(WriterProperties::builder().set_dictionary_enabled(true).build());
+    let mut file_writer = SerializedFileWriter::new(writer, schema, properties)?;
+
+    let batch_size = batch_size.max(1);
+    let mut batch = Vec::with_capacity(batch_size);
+
+    for transaction in records {
+        batch.push(transaction);
+
+        if batch.len() == batch_size {
+            write_row_group(&mut file_writer, &batch)?;
+            batch.clear();
+        }
+    }
+
+    if !batch.is_empty() {
+        write_row_group(&mut file_writer, &batch)?;
+    }
+
+    file_writer.close()?;
+
+    Ok(())
+}
+
+fn write_row_group<W: Write + Send>(file_writer: &mut SerializedFileWriter<W>, batch: &[Transaction]) -> Result<(), ParquetError> {
+    let mut row_group = file_writer.next_row_group()?;
+
+    write_int64_column(&mut row_group, batch.iter().map(|t| t.id as i64))?;
+    write_string_column(&mut row_group, batch.iter().map(|t| t.transaction_type.to_string()))?;
+    write_int64_column(&mut row_group, batch.iter().map(|t| t.from_user_id as i64))?;
+    write_int64_column(&mut row_group, batch.iter().map(|t| t.to_user_id as i64))?;
+    write_int64_column(&mut row_group, batch.iter().map(|t| t.amount))?;
+    write_int64_column(&mut row_group, batch.iter().map(|t| t.timestamp as i64))?;
+    write_string_column(&mut row_group, batch.iter().map(|t| t.transaction_status.to_string()))?;
+    write_string_column(&mut row_group, batch.iter().map(|t| t.description.clone()))?;
+
+    row_group.close()?;
+
+    Ok(())
+}
+
+fn write_int64_column<W, I>(row_group: &mut SerializedRowGroupWriter<'_, W>, values: I) -> Result<(), ParquetError>
+where
+    W: Write + Send,
+    I: Iterator<Item = i64>,
+{
+    let mut column = row_group
+        .next_column()?
+        .ok_or_else(|| ParquetError::General("Parquet schema has fewer columns than expected".to_string()))?;
+
+    column.typed::<Int64Type>().write_batch(&values.collect::<Vec<_>>(), None, None)?;
+    column.close()
+}
+
+fn write_string_column<W, I>(row_group: &mut SerializedRowGroupWriter<'_, W>, values: I) -> Result<(), ParquetError>
+where
+    W: Write + Send,
+    I: Iterator<Item = String>,
+{
+    let mut column = row_group
+        .next_column()?
+        .ok_or_else(|| ParquetError::General("Parquet schema has fewer columns than expected".to_string()))?;
+
+    let values: Vec<ByteArray> = values.map(|value| ByteArray::from(value.into_bytes())).collect();
+    column.typed::<ByteArrayType>().write_batch(&values, None, None)?;
+    column.close()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::test_transaction;
+    use crate::{TransactionStatus, TransactionType};
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+    use std::fs::{self, File};
+
+    #[test]
+    fn parquet_round_trip_preserves_schema_and_values() {
+        let records = vec![
+            test_transaction(1),
+            Transaction { transaction_type: TransactionType::Transfer, transaction_status: TransactionStatus::Pending, ..test_transaction(2) },
+            Transaction { transaction_type: TransactionType::Withdrawal, transaction_status: TransactionStatus::Failure, ..test_transaction(3) },
+        ];
+
+        let path = std::env::temp_dir().join(format!("ypbank_parquet_{}.parquet", std::process::id()));
+        write_parquet(records.clone(), File::create(&path).unwrap(), 2).unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.num_row_groups(), 2);
+
+        let schema = metadata.file_metadata().schema_descr();
+        let columns: Vec<_> = schema.columns().iter().map(|c| c.name().to_string()).collect();
+        assert_eq!(
+            columns,
+            vec!["TX_ID", "TX_TYPE", "FROM_USER_ID", "TO_USER_ID", "AMOUNT", "TIMESTAMP", "STATUS", "DESCRIPTION"]
+        );
+
+        let rows: Vec<_> = reader.get_row_iter(None).unwrap().map(|row| row.unwrap()).collect();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(rows.len(), 3);
+        for (row, expected) in rows.iter().zip(&records) {
+            assert_eq!(row.get_ulong(0).unwrap(), expected.id);
+            assert_eq!(row.get_string(1).unwrap(), &expected.transaction_type.to_string());
+            assert_eq!(row.get_ulong(2).unwrap(), expected.from_user_id);
+            assert_eq!(row.get_ulong(3).unwrap(), expected.to_user_id);
+            assert_eq!(row.get_long(4).unwrap(), expected.amount);
+            assert_eq!(row.get_ulong(5).unwrap(), expected.timestamp);
+            assert_eq!(row.get_string(6).unwrap(), &expected.transaction_status.to_string());
+            assert_eq!(row.get_string(7).unwrap(), &expected.description);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::common::Transaction;
+use crate::{Parser, Readable};
+use std::io::Read;
+
+/// Транзакция вместе с точными исходными байтами записи.
+///
+/// `raw` содержит строки записи с их переводами строк, но не разделитель
+/// и не следующую запись; комментарии перед записью не входят.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordWithRaw {
+    /// Разобранная транзакция.
+    pub parsed: Transaction,
+    /// Исходные байты записи.
+    pub raw: Vec<u8>,
+}
+
+/// Итератор транзакций вместе с исходными байтами; создается `Parser::with_raw`
+/// для текстового формата и CSV.
+pub struct RawBytesRecords<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read
+{
+    pub(crate) parser: Parser<TRecord, Source>,
+}
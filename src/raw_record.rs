@@ -0,0 +1,286 @@
+use crate::common::Transaction;
+use crate::csv_format::{trim_field, CsvReader};
+use crate::errors::TextRecordError;
+use crate::txt_format::TextReader;
+use crate::{Readable, Writable, YPBankCsvRecord, YPBankTextRecord};
+use std::fmt::{Debug, Formatter};
+use std::io::{Error, ErrorKind, Read, Seek, Write};
+use std::marker::PhantomData;
+
+/// «Сырая» запись: значения всех полей в исходном строковом виде.
+///
+/// Параметр `Format` задает формат, в котором запись читается и пишется
+/// (`RawRecord<YPBankCsvRecord>` или `RawRecord<YPBankTextRecord>`).
+/// В отличие от типизированных записей, сохраняет неизвестные поля и не проверяет значения,
+/// поэтому подходит для сквозной передачи данных без потерь: поля пишутся обратно
+/// в том же порядке, в котором были прочитаны.
+pub struct RawRecord<Format> {
+    /// Пары (имя, значение) в исходном порядке (`TX_ID`, `AMOUNT`, ... и любые дополнительные).
+    pub fields: Vec<(String, String)>,
+    _format: PhantomData<Format>,
+}
+
+impl<Format> RawRecord<Format> {
+    /// Создает запись из набора полей.
+    pub fn new(fields: Vec<(String, String)>) -> Self {
+        Self {
+            fields,
+            _format: PhantomData,
+        }
+    }
+
+    /// Возвращает значение поля по имени.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+}
+
+impl<Format> Debug for RawRecord<Format> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RawRecord").field("fields", &self.fields).finish()
+    }
+}
+
+impl<Format> PartialEq for RawRecord<Format> {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields == other.fields
+    }
+}
+
+impl<Format> From<Transaction> for RawRecord<Format> {
+    fn from(value: Transaction) -> Self {
+        let fields = [
+            ("TX_ID", value.id.to_string()),
+            ("TX_TYPE", value.transaction_type.to_string()),
+            ("FROM_USER_ID", value.from_user_id.to_string()),
+            ("TO_USER_ID", value.to_user_id.to_string()),
+            ("AMOUNT", value.amount.to_string()),
+            ("TIMESTAMP", value.timestamp.to_string()),
+            ("STATUS", value.transaction_status.to_string()),
+            ("DESCRIPTION", value.description),
+        ];
+
+        Self::new(fields.into_iter().map(|(k, v)| (k.to_owned(), v)).collect())
+    }
+}
+
+impl<R: Read> Readable<R> for RawRecord<YPBankCsvRecord> {
+    type Reader = CsvReader<R>;
+    type Error = Error;
+
+    fn build_reader(source: R) -> Self::Reader {
+        CsvReader::new(source)
+    }
+
+    fn build_reader_with_capacity(source: R, capacity: usize) -> Self::Reader {
+        CsvReader::with_capacity(source, capacity)
+    }
+
+    fn rewind(reader: &mut Self::Reader) -> Result<(), Error>
+    where
+        R: Seek
+    {
+        reader.rewind().map_err(Into::into)
+    }
+
+    fn read(reader: &mut Self::Reader) -> Result<Self, Error> {
+        let headers = reader.inner.headers().map_err(|e| Error::new(ErrorKind::InvalidData, e))?.clone();
+
+        let expected_fields = reader.expected_fields()?;
+        let mut record = csv::ByteRecord::new();
+        if !reader.read_row(&mut record, expected_fields)? {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "End of CSV"));
+        }
+        let record = csv::StringRecord::from_byte_record(record).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+        let fields = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(k, v)| (k.to_owned(), trim_field(k, v).to_owned()))
+            .collect();
+
+        Ok(Self::new(fields))
+    }
+}
+
+impl Writable for RawRecord<YPBankCsvRecord> {
+    type Error = Error;
+    type Options = ();
+
+    fn write_header<W: Write>(writer: &mut W) -> Result<(), Error> {
+        YPBankCsvRecord::write_header(writer)
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        write_csv_row(writer, self.fields.iter().map(|(k, v)| (v.as_str(), k == "DESCRIPTION")))
+    }
+
+    fn write_header_for<W: Write>(writer: &mut W, first: &Self, _options: &mut ()) -> Result<(), Error> {
+        write_csv_row(writer, first.fields.iter().map(|(k, _)| (k.as_str(), false)))
+    }
+}
+
+/// Записывает строку CSV из пар (значение, обязательные кавычки).
+///
+/// Значения без обязательных кавычек заключаются в них, только если этого требуют спецсимволы.
+/// `DESCRIPTION` всегда пишется в кавычках, как в типизированном формате.
+fn write_csv_row<'a, W, I>(writer: &mut W, values: I) -> Result<(), Error>
+where
+    W: Write,
+    I: IntoIterator<Item = (&'a str, bool)>,
+{
+    for (index, (value, always_quote)) in values.into_iter().enumerate() {
+        if index > 0 {
+            writer.write_all(b",")?;
+        }
+
+        let needs_quotes = always_quote
+            || value.contains([',', '"', '\n', '\r'])
+            || value.trim() != value;
+
+        if needs_quotes {
+            write!(writer, "\"{}\"", value.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(value.as_bytes())?;
+        }
+    }
+
+    writer.write_all(b"\n")
+}
+
+impl<R: Read> Readable<R> for RawRecord<YPBankTextRecord> {
+    type Reader = TextReader<R>;
+    type Error = TextRecordError;
+
+    fn build_reader(source: R) -> Self::Reader {
+        TextReader::new(source)
+    }
+
+    fn build_reader_with_capacity(source: R, capacity: usize) -> Self::Reader {
+        TextReader::with_capacity(source, capacity)
+    }
+
+    fn read(reader: &mut Self::Reader) -> Result<Self, TextRecordError> {
+        Ok(Self::new(reader.read_block()?))
+    }
+
+    fn rewind(reader: &mut Self::Reader) -> Result<(), TextRecordError>
+    where
+        R: Seek
+    {
+        reader.rewind()
+    }
+}
+
+impl Writable for RawRecord<YPBankTextRecord> {
+    type Error = Error;
+    type Options = ();
+
+    fn write_header<W: Write>(_: &mut W) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        for (key, value) in &self.fields {
+            if key == "DESCRIPTION" {
+                writeln!(writer, "{}: \"{}\"", key, value)?;
+            } else {
+                writeln!(writer, "{}: {}", key, value)?;
+            }
+        }
+
+        writeln!(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{serialize_to_vec, Parser};
+    use std::io::Cursor;
+
+    #[test]
+    fn csv_unknown_column_survives_read_write() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION,BRANCH_ID
+1001,DEPOSIT,0,501,50000,1672531200000,SUCCESS,\"Initial, funding\",B-17
+";
+        let parser = Parser::<RawRecord<YPBankCsvRecord>, _>::new(Cursor::new(csv_data));
+        let records: Vec<_> = parser.collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].get("BRANCH_ID"), Some("B-17"));
+
+        let bytes = serialize_to_vec(&records).unwrap();
+
+        let mut parser = Parser::<RawRecord<YPBankCsvRecord>, _>::new(Cursor::new(bytes));
+        let reread = parser.next().expect("Should have a record").unwrap();
+        assert!(parser.next().is_none());
+
+        assert_eq!(reread, records[0]);
+        assert_eq!(reread.get("DESCRIPTION"), Some("Initial, funding"));
+    }
+
+    #[test]
+    fn txt_unknown_key_survives_read_write() {
+        let input = r#"
+TX_ID: 1
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 100
+TIMESTAMP: 1
+STATUS: SUCCESS
+DESCRIPTION: "Terminal deposit"
+BRANCH_ID: B-17
+"#;
+        let parser = Parser::<RawRecord<YPBankTextRecord>, _>::new(Cursor::new(input.as_bytes()));
+        let records: Vec<_> = parser.collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].get("BRANCH_ID"), Some("B-17"));
+
+        let bytes = serialize_to_vec(&records).unwrap();
+
+        let parser = Parser::<RawRecord<YPBankTextRecord>, _>::new(Cursor::new(bytes));
+        let reread: Vec<_> = parser.collect::<Result<_, _>>().unwrap();
+        assert_eq!(reread, records);
+    }
+
+    #[test]
+    fn raw_record_from_transaction_has_canonical_fields() {
+        let transaction = Transaction {
+            id: 5,
+            transaction_type: crate::TransactionType::Withdrawal,
+            from_user_id: 3,
+            to_user_id: 0,
+            amount: 42,
+            timestamp: 7,
+            transaction_status: crate::TransactionStatus::Pending,
+            description: "Cash".to_string(),
+            currency: None,
+        };
+
+        let record = RawRecord::<YPBankTextRecord>::from(transaction);
+
+        assert_eq!(record.fields.len(), 8);
+        assert_eq!(record.get("TX_TYPE"), Some("WITHDRAWAL"));
+        assert_eq!(record.get("STATUS"), Some("PENDING"));
+        assert_eq!(record.get("DESCRIPTION"), Some("Cash"));
+    }
+
+    #[test]
+    fn csv_extra_column_keeps_position_byte_identical() {
+        let csv_data = "\
+TX_ID,TX_TYPE,BRANCH_ID,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1001,DEPOSIT,B-17,0,501,50000,1672531200000,SUCCESS,\"Initial funding\"
+1002,TRANSFER,B-03,501,502,1500,1672531260000,PENDING,\"Rent, March\"
+";
+        let parser = Parser::<RawRecord<YPBankCsvRecord>, _>::new(Cursor::new(csv_data));
+        let records: Vec<_> = parser.collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].fields[2], ("BRANCH_ID".to_string(), "B-17".to_string()));
+
+        let bytes = serialize_to_vec(&records).unwrap();
+
+        assert_eq!(String::from_utf8(bytes).unwrap(), csv_data);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::common::{fnv1a, Transaction};
+use std::collections::HashMap;
+
+/// Что делать с описанием при обезличивании.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DescriptionRedaction {
+    /// Оставить без изменений.
+    #[default]
+    Keep,
+    /// Заменить пустой строкой.
+    Blank,
+    /// Заменить шестнадцатеричным хешем FNV-1a: одинаковые описания дают одинаковый хеш.
+    Hash,
+}
+
+/// Настройки обезличивания транзакций.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RedactOptions {
+    /// Заменять ID пользователей последовательными псевдонимами `1, 2, ...`.
+    pub alias_user_ids: bool,
+    /// Обработка описаний.
+    pub description: DescriptionRedaction,
+}
+
+/// Обезличивает транзакции перед передачей внешним проверяющим.
+///
+/// Псевдонимы выдаются в порядке первого появления пользователя (сначала отправитель,
+/// затем получатель), поэтому один и тот же ID в пределах вызова всегда получает один
+/// и тот же псевдоним в обоих полях. ID `0` — внешний контрагент — не заменяется.
+pub fn redact(records: &mut [Transaction], opts: RedactOptions) {
+    let mut aliases: HashMap<u64, u64> = HashMap::new();
+
+    for transaction in records.iter_mut() {
+        if opts.alias_user_ids {
+            transaction.from_user_id = alias(&mut aliases, transaction.from_user_id);
+            transaction.to_user_id = alias(&mut aliases, transaction.to_user_id);
+        }
+
+        match opts.description {
+            DescriptionRedaction::Keep => {}
+            DescriptionRedaction::Blank => transaction.description.clear(),
+            DescriptionRedaction::Hash => transaction.description = format!("{:016x}", fnv1a(transaction.description.as_bytes())),
+        }
+    }
+}
+
+fn alias(aliases: &mut HashMap<u64, u64>, user_id: u64) -> u64 {
+    if user_id == 0 {
+        return 0;
+    }
+
+    let next = aliases.len() as u64 + 1;
+    *aliases.entry(user_id).or_insert(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::test_transaction;
+    use crate::TransactionType;
+
+    fn records() -> Vec<Transaction> {
+        vec![
+            Transaction { to_user_id: 501, description: "Salary".to_string(), ..test_transaction(1) },
+            Transaction { transaction_type: TransactionType::Transfer, from_user_id: 501, to_user_id: 777, description: "Rent".to_string(), ..test_transaction(2) },
+            Transaction { transaction_type: TransactionType::Transfer, from_user_id: 777, to_user_id: 501, description: "Refund".to_string(), ..test_transaction(3) },
+            Transaction { transaction_type: TransactionType::Withdrawal, from_user_id: 777, to_user_id: 0, description: "Salary".to_string(), ..test_transaction(4) },
+        ]
+    }
+
+    #[test]
+    fn aliases_are_consistent_across_records() {
+        let mut records = records();
+        redact(&mut records, RedactOptions { alias_user_ids: true, ..RedactOptions::default() });
+
+        let parties: Vec<(u64, u64)> = records.iter().map(|t| (t.from_user_id, t.to_user_id)).collect();
+        assert_eq!(parties, vec![(0, 1), (1, 2), (2, 1), (2, 0)]);
+        assert_eq!(records[1].description, "Rent");
+    }
+
+    #[test]
+    fn user_zero_sentinel_is_preserved() {
+        let mut records = vec![Transaction { to_user_id: 0, description: "".to_string(), ..test_transaction(1) }];
+        redact(&mut records, RedactOptions { alias_user_ids: true, ..RedactOptions::default() });
+
+        assert_eq!((records[0].from_user_id, records[0].to_user_id), (0, 0));
+    }
+
+    #[test]
+    fn descriptions_are_blanked_or_hashed() {
+        let mut blanked = records();
+        redact(&mut blanked, RedactOptions { description: DescriptionRedaction::Blank, ..RedactOptions::default() });
+        assert!(blanked.iter().all(|t| t.description.is_empty()));
+        assert_eq!(blanked[1].from_user_id, 501);
+
+        let mut hashed = records();
+        redact(&mut hashed, RedactOptions { description: DescriptionRedaction::Hash, ..RedactOptions::default() });
+        assert_eq!(hashed[0].description, hashed[3].description);
+        assert_ne!(hashed[0].description, hashed[1].description);
+        assert_eq!(hashed[0].description.len(), 16);
+        assert!(!hashed.iter().any(|t| t.description.contains("Salary")));
+    }
+}
@@ -0,0 +1,100 @@
+use crate::common::Transaction;
+use crate::Writable;
+use std::io::{Error, Write};
+
+/// Число знаков дробной части суммы в отчете (копейки, центы).
+const REPORT_MINOR_UNITS: u32 = 2;
+
+/// Транзакция в виде человекочитаемого отчета для обращений в поддержку.
+///
+/// Формат только для записи: каждая транзакция выводится блоком выровненных полей,
+/// сумма — десятичной дробью с разделителем разрядов, метка времени — датой ISO 8601 в UTC.
+/// Валюта выводится отдельной строкой, только если она указана.
+/// Блоки разделяются пустой строкой.
+#[derive(Debug, Clone, PartialEq)]
+pub struct YPBankReportRecord(Transaction);
+
+impl From<Transaction> for YPBankReportRecord {
+    fn from(value: Transaction) -> Self {
+        Self(value)
+    }
+}
+
+impl Writable for YPBankReportRecord {
+    type Error = Error;
+    type Options = ();
+
+    fn write_header<W: Write>(_: &mut W) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let transaction = &self.0;
+        let timestamp = match transaction.timestamp_datetime() {
+            Ok(datetime) => datetime.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            Err(e) => e.to_string(),
+        };
+
+        writeln!(writer, "Transaction #{}", transaction.id)?;
+        writeln!(writer, "  Type:        {}", transaction.transaction_type)?;
+        writeln!(writer, "  From user:   {}", transaction.from_user_id)?;
+        writeln!(writer, "  To user:     {}", transaction.to_user_id)?;
+        writeln!(writer, "  Amount:      {}", transaction.formatted_amount(REPORT_MINOR_UNITS, Some(',')))?;
+        if let Some(currency) = &transaction.currency {
+            writeln!(writer, "  Currency:    {}", currency)?;
+        }
+        writeln!(writer, "  Timestamp:   {}", timestamp)?;
+        writeln!(writer, "  Status:      {}", transaction.transaction_status)?;
+        writeln!(writer, "  Description: {}", transaction.description)?;
+        writeln!(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{serialize_to_vec, TransactionBuilder, TransactionStatus, TransactionType};
+
+    #[test]
+    fn report_contains_labels_and_formatted_values() {
+        let record = YPBankReportRecord::from(Transaction {
+            id: 1001,
+            transaction_type: TransactionType::Transfer,
+            from_user_id: 501,
+            to_user_id: 502,
+            amount: 123456789,
+            timestamp: 1672531200000,
+            transaction_status: TransactionStatus::Success,
+            description: "Monthly rent".to_string(),
+            currency: None,
+        });
+
+        let report = String::from_utf8(serialize_to_vec(&[record]).unwrap()).unwrap();
+
+        assert_eq!(
+            report,
+            "\
+Transaction #1001
+  Type:        TRANSFER
+  From user:   501
+  To user:     502
+  Amount:      1,234,567.89
+  Timestamp:   2023-01-01T00:00:00.000Z
+  Status:      SUCCESS
+  Description: Monthly rent
+
+"
+        );
+    }
+
+    #[test]
+    fn currency_line_is_written_only_when_present() {
+        let transaction = TransactionBuilder::new(1, TransactionType::Deposit).to_user_id(10).amount(100).build().unwrap();
+        let with_currency = Transaction { currency: Some("EUR".to_string()), ..transaction.clone() };
+
+        let report = String::from_utf8(serialize_to_vec(&[transaction.into(), with_currency.into()] as &[YPBankReportRecord]).unwrap()).unwrap();
+
+        assert_eq!(report.matches("Currency:").count(), 1);
+        assert!(report.contains("  Amount:      1.00\n  Currency:    EUR\n"));
+    }
+}
@@ -0,0 +1,114 @@
+use crate::common::Transaction;
+use std::io::{Error, ErrorKind, Write};
+
+/// Столбцы таблицы в порядке вывода значений.
+const SQL_COLUMNS: &str = "tx_id, tx_type, from_user_id, to_user_id, amount, timestamp, status, description";
+
+/// Записывает транзакции как SQL-операторы `INSERT`, по одному на запись.
+///
+/// Эквивалентно `to_sql_inserts_batched` с размером пакета 1.
+/// Валюта не записывается: в таблице нет столбца `CURRENCY`.
+pub fn to_sql_inserts<I, W>(records: I, table: &str, writer: W) -> Result<(), Error>
+where
+    I: IntoIterator<Item = Transaction>,
+    W: Write,
+{
+    to_sql_inserts_batched(records, table, writer, 1)
+}
+
+/// Записывает транзакции как многострочные SQL-операторы `INSERT` по `batch_size` записей.
+///
+/// Описание заключается в одинарные кавычки с удвоением кавычек внутри строки,
+/// перечисления выводятся каноническими строками (`DEPOSIT`, `SUCCESS`, ...).
+/// Имя таблицы может содержать только латинские буквы, цифры, `_` и `.`,
+/// иначе возвращается ошибка `InvalidInput`. `batch_size`, равный нулю, трактуется как единица.
+/// Валюта не записывается: в таблице нет столбца `CURRENCY`.
+pub fn to_sql_inserts_batched<I, W>(records: I, table: &str, mut writer: W, batch_size: usize) -> Result<(), Error>
+where
+    I: IntoIterator<Item = Transaction>,
+    W: Write,
+{
+    let is_valid_table = !table.is_empty() && table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+    if !is_valid_table {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("Invalid SQL table name: {}", table)));
+    }
+
+    let batch_size = batch_size.max(1);
+    let mut in_batch = 0;
+
+    for transaction in records {
+        if in_batch == 0 {
+            write!(writer, "INSERT INTO {} ({}) VALUES\n  ", table, SQL_COLUMNS)?;
+        } else {
+            write!(writer, ",\n  ")?;
+        }
+
+        write!(
+            writer,
+            "({}, '{}', {}, {}, {}, {}, '{}', '{}')",
+            transaction.id,
+            transaction.transaction_type,
+            transaction.from_user_id,
+            transaction.to_user_id,
+            transaction.amount,
+            transaction.timestamp,
+            transaction.transaction_status,
+            transaction.description.replace('\'', "''")
+        )?;
+
+        in_batch += 1;
+        if in_batch == batch_size {
+            writeln!(writer, ";")?;
+            in_batch = 0;
+        }
+    }
+
+    if in_batch > 0 {
+        writeln!(writer, ";")?;
+    }
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::test_transaction;
+
+    #[test]
+    fn single_insert_escapes_description() {
+        let mut output = Vec::new();
+
+        to_sql_inserts(vec![Transaction { description: "O'Brien's rent".to_string(), ..test_transaction(1) }], "transactions", &mut output).unwrap();
+
+        let expected = "\
+INSERT INTO transactions (tx_id, tx_type, from_user_id, to_user_id, amount, timestamp, status, description) VALUES
+  (1, 'DEPOSIT', 0, 10, 100, 1001, 'SUCCESS', 'O''Brien''s rent');
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn batched_inserts_group_rows() {
+        let records = vec![
+            Transaction { description: "a".to_string(), ..test_transaction(1) },
+            Transaction { description: "b".to_string(), ..test_transaction(2) },
+            Transaction { description: "c".to_string(), ..test_transaction(3) },
+        ];
+        let mut output = Vec::new();
+
+        to_sql_inserts_batched(records, "transactions", &mut output, 2).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches("INSERT INTO").count(), 2);
+        assert!(output.contains("'a'),\n  (2, "));
+        assert!(output.ends_with("'c');\n"));
+    }
+
+    #[test]
+    fn invalid_table_name_is_rejected() {
+        let err = to_sql_inserts(vec![Transaction { description: "a".to_string(), ..test_transaction(1) }], "t; DROP TABLE x", Vec::new()).unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,759 @@
+use crate::common::{Transaction, TransactionStatus, TransactionType};
+use crate::errors::{AmountOverflowError, ControlTotalMismatch};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap, VecDeque};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use std::marker::PhantomData;
+
+const TRANSACTION_TYPES: [TransactionType; 3] = [
+    TransactionType::Deposit,
+    TransactionType::Transfer,
+    TransactionType::Withdrawal,
+];
+
+/// Сводная таблица сумм: строки — пользователи, столбцы — типы транзакций.
+#[derive(Debug, Default, PartialEq)]
+pub struct PivotTable {
+    rows: BTreeMap<u64, [i64; 3]>,
+}
+
+impl PivotTable {
+    /// Возвращает сумму операций указанного типа для пользователя (0, если операций не было).
+    pub fn get(&self, user_id: u64, transaction_type: TransactionType) -> i64 {
+        self.rows
+            .get(&user_id)
+            .map_or(0, |row| row[u8::from(transaction_type) as usize])
+    }
+
+    /// Идентификаторы пользователей, присутствующих в таблице, по возрастанию.
+    pub fn users(&self) -> impl Iterator<Item = u64> + '_ {
+        self.rows.keys().copied()
+    }
+
+    /// Записывает таблицу в CSV: заголовок `USER_ID,DEPOSIT,TRANSFER,WITHDRAWAL`
+    /// и по одной строке на пользователя.
+    pub fn to_csv<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        write!(writer, "USER_ID")?;
+        for transaction_type in TRANSACTION_TYPES {
+            write!(writer, ",{}", transaction_type)?;
+        }
+        writeln!(writer)?;
+
+        for (user_id, row) in &self.rows {
+            write!(writer, "{}", user_id)?;
+            for amount in row {
+                write!(writer, ",{}", amount)?;
+            }
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Строит сводную таблицу сумм по пользователям и типам транзакций.
+///
+/// Операция относится к пользователю, чей счёт она затрагивает: пополнение — к получателю,
+/// снятие и перевод — к отправителю. Суммы складываются с проверкой переполнения.
+pub fn pivot_by_user_and_type<I>(records: I) -> Result<PivotTable, AmountOverflowError>
+where
+    I: IntoIterator<Item = Transaction>,
+{
+    let mut table = PivotTable::default();
+
+    for transaction in records {
+        let user_id = match transaction.transaction_type {
+            TransactionType::Deposit => transaction.to_user_id,
+            TransactionType::Transfer | TransactionType::Withdrawal => transaction.from_user_id,
+        };
+
+        let cell = &mut table.rows.entry(user_id).or_default()[u8::from(transaction.transaction_type) as usize];
+        *cell = cell
+            .checked_add(transaction.amount)
+            .ok_or(AmountOverflowError { id: transaction.id })?;
+    }
+
+    Ok(table)
+}
+
+/// Проверяет, что сумма сумм всех транзакций равна контрольной сумме `expected`.
+///
+/// Суммирование ведется в `i128` с проверкой переполнения, поэтому результат точен
+/// для любого реалистичного числа записей.
+pub fn verify_control_total<I>(records: I, expected: u64) -> Result<(), ControlTotalMismatch>
+where
+    I: IntoIterator<Item = Transaction>,
+{
+    let actual = records
+        .into_iter()
+        .try_fold(0i128, |sum, transaction| sum.checked_add(i128::from(transaction.amount)))
+        .unwrap_or(i128::MAX);
+
+    if actual != i128::from(expected) {
+        return Err(ControlTotalMismatch { expected, actual });
+    }
+
+    Ok(())
+}
+
+/// Сводка по набору транзакций: количество, суммы по типам и количество по статусам.
+///
+/// Суммы накапливаются в `i128`, поэтому не переполняются даже на суммах, близких к `i64::MAX`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Summary {
+    count: u64,
+    amounts_by_type: [i128; 3],
+    counts_by_status: StatusCounts,
+}
+
+impl Summary {
+    /// Общее количество транзакций.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Сумма сумм всех транзакций.
+    pub fn total_amount(&self) -> i128 {
+        self.amounts_by_type.iter().sum()
+    }
+
+    /// Сумма сумм транзакций типа `transaction_type`.
+    pub fn amount_for(&self, transaction_type: TransactionType) -> i128 {
+        self.amounts_by_type[transaction_type.as_u8() as usize]
+    }
+
+    /// Количество транзакций со статусом `status`.
+    pub fn count_for(&self, status: TransactionStatus) -> u64 {
+        self.counts_by_status.get(status)
+    }
+
+    /// Учитывает транзакцию в сводке.
+    pub fn add(&mut self, transaction: &Transaction) {
+        self.count += 1;
+        self.amounts_by_type[transaction.transaction_type.as_u8() as usize] += i128::from(transaction.amount);
+        self.counts_by_status.add(transaction);
+    }
+}
+
+impl FromIterator<Transaction> for Summary {
+    fn from_iter<I: IntoIterator<Item = Transaction>>(records: I) -> Self {
+        let mut summary = Summary::default();
+        for transaction in records {
+            summary.add(&transaction);
+        }
+        summary
+    }
+}
+
+/// Поле транзакции с тремя значениями, по которому ведет подсчет `Counts`.
+pub trait CountKey: Copy + Display {
+    /// Все значения в порядке вывода; позиция значения совпадает с его `as_u8`.
+    const ALL: [Self; 3];
+
+    /// Значение поля у транзакции.
+    fn of(transaction: &Transaction) -> Self;
+
+    /// Позиция значения в `ALL`.
+    fn index(self) -> usize;
+}
+
+impl CountKey for TransactionType {
+    const ALL: [Self; 3] = TRANSACTION_TYPES;
+
+    fn of(transaction: &Transaction) -> Self {
+        transaction.transaction_type
+    }
+
+    fn index(self) -> usize {
+        self.as_u8() as usize
+    }
+}
+
+impl CountKey for TransactionStatus {
+    const ALL: [Self; 3] = [TransactionStatus::Success, TransactionStatus::Failure, TransactionStatus::Pending];
+
+    fn of(transaction: &Transaction) -> Self {
+        transaction.transaction_status
+    }
+
+    fn index(self) -> usize {
+        self.as_u8() as usize
+    }
+}
+
+/// Количество транзакций по значениям поля `K` для компактного вывода в лог, например
+/// `DEPOSIT=12 TRANSFER=5 WITHDRAWAL=3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Counts<K> {
+    counts: [u64; 3],
+    _key: PhantomData<K>,
+}
+
+/// Количество транзакций по типам.
+pub type TypeCounts = Counts<TransactionType>;
+
+/// Количество транзакций по статусам.
+pub type StatusCounts = Counts<TransactionStatus>;
+
+impl<K> Default for Counts<K> {
+    fn default() -> Self {
+        Self { counts: [0; 3], _key: PhantomData }
+    }
+}
+
+impl<K: CountKey> Counts<K> {
+    /// Подсчитывает транзакции, не забирая их.
+    pub fn from_transactions<'a>(records: impl IntoIterator<Item = &'a Transaction>) -> Self {
+        records.into_iter().fold(Self::default(), |mut counts, transaction| {
+            counts.add(transaction);
+            counts
+        })
+    }
+
+    /// Количество транзакций со значением `key`.
+    pub fn get(&self, key: K) -> u64 {
+        self.counts[key.index()]
+    }
+
+    /// Учитывает транзакцию.
+    pub fn add(&mut self, transaction: &Transaction) {
+        self.counts[K::of(transaction).index()] += 1;
+    }
+}
+
+impl<K: CountKey> Display for Counts<K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, key) in K::ALL.iter().enumerate() {
+            let separator = if i == 0 { "" } else { " " };
+            write!(f, "{}{}={}", separator, key, self.get(*key))?;
+        }
+        Ok(())
+    }
+}
+
+/// Итоги одного пользователя в `UserRollup`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct UserTotals {
+    /// Сумма отправленного: снятия и исходящие переводы.
+    pub sent_total: u128,
+    /// Сумма полученного: пополнения и входящие переводы.
+    pub received_total: u128,
+    /// Число транзакций с участием пользователя.
+    pub count: u32,
+}
+
+/// Сводка по пользователям для отчетности: суммы отправленного и полученного и число транзакций.
+///
+/// `DEPOSIT` учитывается у получателя, `WITHDRAWAL` — у отправителя, `TRANSFER` — у обоих;
+/// перевод самому себе считается одной транзакцией. Суммы берутся по модулю и накапливаются
+/// в `u128`. Внешний контрагент (ID `0`) по умолчанию не учитывается; с `include_external`
+/// он считается отправителем пополнений и получателем снятий.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UserRollup {
+    users: BTreeMap<u64, UserTotals>,
+    include_external: bool,
+}
+
+impl UserRollup {
+    /// Создает пустую сводку без учета внешнего контрагента.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Включает в сводку внешнего контрагента с ID `0`: источник `DEPOSIT`, получатель `WITHDRAWAL`
+    /// и участник переводов с ID `0`.
+    pub fn include_external(mut self, enabled: bool) -> Self {
+        self.include_external = enabled;
+        self
+    }
+
+    /// Учитывает транзакцию в сводке.
+    pub fn add(&mut self, transaction: &Transaction) {
+        let amount = u128::from(transaction.amount.unsigned_abs());
+        let (sender, receiver) = match transaction.transaction_type {
+            // внешняя сторона пополнения и снятия — всегда ID 0, учитывается только с `include_external`
+            TransactionType::Deposit => (Some(0), Some(transaction.to_user_id)),
+            TransactionType::Withdrawal => (Some(transaction.from_user_id), Some(0)),
+            TransactionType::Transfer => (Some(transaction.from_user_id), Some(transaction.to_user_id)),
+        };
+
+        if let Some(user_id) = sender.filter(|&id| self.include_external || id != 0) {
+            let totals = self.users.entry(user_id).or_default();
+            totals.sent_total += amount;
+            totals.count += 1;
+        }
+
+        if let Some(user_id) = receiver.filter(|&id| self.include_external || id != 0) {
+            let totals = self.users.entry(user_id).or_default();
+            totals.received_total += amount;
+            if sender != Some(user_id) {
+                totals.count += 1;
+            }
+        }
+    }
+
+    /// Итоги пользователя `user_id`, если он участвовал в транзакциях.
+    pub fn get(&self, user_id: u64) -> Option<&UserTotals> {
+        self.users.get(&user_id)
+    }
+
+    /// Итоги всех пользователей в порядке возрастания ID.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &UserTotals)> {
+        self.users.iter().map(|(&user_id, totals)| (user_id, totals))
+    }
+
+    /// Забирает итоги в виде словаря `ID пользователя -> итоги`.
+    pub fn into_map(self) -> BTreeMap<u64, UserTotals> {
+        self.users
+    }
+}
+
+impl FromIterator<Transaction> for UserRollup {
+    fn from_iter<I: IntoIterator<Item = Transaction>>(records: I) -> Self {
+        let mut rollup = UserRollup::new();
+        for transaction in records {
+            rollup.add(&transaction);
+        }
+        rollup
+    }
+}
+
+/// Транзакция, упорядоченная по сумме; при равных суммах выше ранг у меньшего `id`.
+struct RankedByAmount(Transaction);
+
+impl RankedByAmount {
+    fn key(&self) -> (i64, Reverse<u64>) {
+        (self.0.amount, Reverse(self.0.id))
+    }
+}
+
+impl PartialEq for RankedByAmount {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for RankedByAmount {}
+
+impl PartialOrd for RankedByAmount {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RankedByAmount {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Выбирает `n` транзакций с наибольшими суммами, не сортируя весь поток.
+///
+/// Использует ограниченную кучу из `n` элементов (O(n) памяти). Результат упорядочен по убыванию
+/// суммы, при равных суммах — по возрастанию `id`. Первая ошибка потока прерывает выбор.
+pub fn top_n_by_amount<I, E>(records: I, n: usize) -> Result<Vec<Transaction>, E>
+where
+    I: IntoIterator<Item = Result<Transaction, E>>,
+{
+    let mut heap: BinaryHeap<Reverse<RankedByAmount>> = BinaryHeap::with_capacity(n + 1);
+
+    for record in records {
+        heap.push(Reverse(RankedByAmount(record?)));
+
+        if heap.len() > n {
+            heap.pop();
+        }
+    }
+
+    Ok(heap.into_sorted_vec().into_iter().map(|Reverse(ranked)| ranked.0).collect())
+}
+
+/// Округляет временную метку вниз до начала интервала длиной `interval_ms`.
+fn floor_timestamp(timestamp: u64, interval_ms: u64) -> u64 {
+    timestamp - timestamp % interval_ms
+}
+
+/// Группирует транзакции по интервалам фиксированной длины (например, по часам).
+///
+/// Ключ группы — начало интервала, то есть временная метка, округленная вниз до кратной
+/// `interval_ms`. Внутри группы сохраняется исходный порядок записей.
+///
+/// # Panics
+///
+/// Паникует, если `interval_ms` равен нулю.
+pub fn bucket_by_time<I>(records: I, interval_ms: u64) -> BTreeMap<u64, Vec<Transaction>>
+where
+    I: IntoIterator<Item = Transaction>,
+{
+    assert!(interval_ms > 0, "interval_ms must be positive");
+
+    let mut buckets: BTreeMap<u64, Vec<Transaction>> = BTreeMap::new();
+    for transaction in records {
+        buckets
+            .entry(floor_timestamp(transaction.timestamp, interval_ms))
+            .or_default()
+            .push(transaction);
+    }
+
+    buckets
+}
+
+/// Выдает каждую запись вместе со средней суммой `window` предшествующих ей записей.
+///
+/// Пока предшествующих записей меньше `window`, среднее берется по имеющимся;
+/// для первой записи (и при `window == 0`) оно равно `0.0`. Хранит только последние
+/// `window` сумм в кольцевом буфере. Ошибки исходного потока передаются дальше без изменений.
+pub fn moving_average<I, E>(records: I, window: usize) -> impl Iterator<Item = Result<(Transaction, f64), E>>
+where
+    I: IntoIterator<Item = Result<Transaction, E>>,
+{
+    let mut recent: VecDeque<i64> = VecDeque::with_capacity(window);
+    let mut sum: i128 = 0;
+
+    records.into_iter().map(move |record| {
+        let transaction = record?;
+
+        let average = if recent.is_empty() {
+            0.0
+        } else {
+            sum as f64 / recent.len() as f64
+        };
+
+        if window > 0 {
+            if recent.len() == window {
+                sum -= recent.pop_front().map_or(0, i128::from);
+            }
+            recent.push_back(transaction.amount);
+            sum += i128::from(transaction.amount);
+        }
+
+        Ok((transaction, average))
+    })
+}
+
+/// Точность HyperLogLog по умолчанию: 2^14 регистров (16 КиБ памяти).
+const DEFAULT_HLL_PRECISION: u8 = 14;
+
+/// Приближенный счетчик уникальных пользователей на основе HyperLogLog.
+///
+/// В отличие от точного подсчета через `HashSet`, занимает постоянный объем памяти
+/// (`2^precision` байт) независимо от числа уникальных значений.
+/// Стандартная относительная ошибка оценки — `1.04 / sqrt(2^precision)`,
+/// то есть около 0.81% при точности по умолчанию (см. `error_bound`).
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HyperLogLog {
+    /// Создает счетчик с точностью по умолчанию.
+    pub fn new() -> Self {
+        Self::with_precision(DEFAULT_HLL_PRECISION)
+    }
+
+    /// Создает счетчик с `2^precision` регистрами; `precision` ограничивается диапазоном 4..=16.
+    pub fn with_precision(precision: u8) -> Self {
+        let precision = precision.clamp(4, 16);
+
+        Self {
+            precision,
+            registers: vec![0; 1 << precision],
+        }
+    }
+
+    /// Учитывает идентификатор пользователя.
+    pub fn observe(&mut self, id: u64) {
+        let mut hasher = DefaultHasher::new();
+        id.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let index = (hash >> (64 - self.precision)) as usize;
+        // сторожевой бит гарантирует, что ранг не превысит 64 - precision + 1
+        let remaining = (hash << self.precision) | (1 << (self.precision - 1));
+        let rank = remaining.leading_zeros() as u8 + 1;
+
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    /// Возвращает оценку числа уникальных идентификаторов.
+    pub fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        if raw <= 2.5 * m && zeros > 0 {
+            // для малых мощностей точнее линейный подсчет
+            return (m * (m / zeros as f64).ln()).round() as u64;
+        }
+
+        raw.round() as u64
+    }
+
+    /// Стандартная относительная ошибка оценки для текущей точности.
+    pub fn error_bound(&self) -> f64 {
+        1.04 / (self.registers.len() as f64).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::test_transaction;
+    use crate::{TransactionStatus};
+
+    fn sample_records() -> Vec<Transaction> {
+        vec![
+            test_transaction(1),
+            Transaction { amount: 50, ..test_transaction(2) },
+            Transaction { transaction_type: TransactionType::Transfer, from_user_id: 10, to_user_id: 20, amount: 30, ..test_transaction(3) },
+            Transaction { transaction_type: TransactionType::Withdrawal, from_user_id: 20, to_user_id: 0, amount: 5, ..test_transaction(4) },
+            Transaction { to_user_id: 20, amount: 7, ..test_transaction(5) },
+        ]
+    }
+
+    #[test]
+    fn summary_accumulates_totals_by_type_and_status() {
+        let mut records = sample_records();
+        records[2].transaction_status = TransactionStatus::Failure;
+        records[4].transaction_status = TransactionStatus::Pending;
+
+        let summary = Summary::from_iter(records);
+
+        assert_eq!(summary.count(), 5);
+        assert_eq!(summary.total_amount(), 192);
+        assert_eq!(summary.amount_for(TransactionType::Deposit), 157);
+        assert_eq!(summary.amount_for(TransactionType::Transfer), 30);
+        assert_eq!(summary.amount_for(TransactionType::Withdrawal), 5);
+        assert_eq!(summary.count_for(TransactionStatus::Success), 3);
+        assert_eq!(summary.count_for(TransactionStatus::Failure), 1);
+        assert_eq!(summary.count_for(TransactionStatus::Pending), 1);
+    }
+
+    #[test]
+    fn type_and_status_counts_render_one_line() {
+        let mut records = sample_records();
+        records[2].transaction_status = TransactionStatus::Failure;
+
+        let types = TypeCounts::from_transactions(&records);
+        let statuses = StatusCounts::from_transactions(&records);
+
+        assert_eq!(types.get(TransactionType::Deposit), 3);
+        assert_eq!(types.get(TransactionType::Transfer), 1);
+        assert_eq!(types.get(TransactionType::Withdrawal), 1);
+        assert_eq!(types.to_string(), "DEPOSIT=3 TRANSFER=1 WITHDRAWAL=1");
+        assert_eq!(statuses.get(TransactionStatus::Success), 4);
+        assert_eq!(statuses.to_string(), "SUCCESS=4 FAILURE=1 PENDING=0");
+    }
+
+    #[test]
+    fn user_rollup_attributes_each_type_to_the_right_side() {
+        let records = vec![
+            test_transaction(1),
+            Transaction { transaction_type: TransactionType::Transfer, from_user_id: 10, to_user_id: 20, amount: 30, ..test_transaction(2) },
+            Transaction { transaction_type: TransactionType::Transfer, from_user_id: 20, amount: 5, ..test_transaction(3) },
+            Transaction { transaction_type: TransactionType::Withdrawal, from_user_id: 10, to_user_id: 0, amount: 40, ..test_transaction(4) },
+            Transaction { transaction_type: TransactionType::Transfer, from_user_id: 10, amount: 7, ..test_transaction(5) },
+        ];
+
+        let rollup = UserRollup::from_iter(records.clone());
+
+        assert_eq!(rollup.get(10), Some(&UserTotals { sent_total: 77, received_total: 112, count: 5 }));
+        assert_eq!(rollup.get(20), Some(&UserTotals { sent_total: 5, received_total: 30, count: 2 }));
+        assert_eq!(rollup.get(0), None);
+        assert_eq!(rollup.iter().map(|(user_id, _)| user_id).collect::<Vec<_>>(), vec![10, 20]);
+
+        let mut with_external = UserRollup::new().include_external(true);
+        for transaction in &records {
+            with_external.add(transaction);
+        }
+        // ID 0 отправляет пополнение и получает снятие; итоги остальных пользователей не меняются
+        assert_eq!(with_external.get(0), Some(&UserTotals { sent_total: 100, received_total: 40, count: 2 }));
+        assert_eq!(with_external.get(10), rollup.get(10));
+        with_external.add(&Transaction { transaction_type: TransactionType::Transfer, from_user_id: 20, to_user_id: 0, amount: 1, ..test_transaction(6) });
+        assert_eq!(with_external.get(0), Some(&UserTotals { sent_total: 100, received_total: 41, count: 3 }));
+    }
+
+    #[test]
+    fn user_rollup_does_not_overflow() {
+        let rollup: UserRollup = (0..4).map(|id| Transaction { amount: i64::MAX, ..test_transaction(id) }).collect();
+
+        assert_eq!(rollup.get(10).unwrap().received_total, 4 * i64::MAX as u128);
+    }
+
+    #[test]
+    fn summary_handles_amounts_near_overflow() {
+        let records = (0..4).map(|id| Transaction { amount: i64::MAX, ..test_transaction(id) });
+
+        let summary: Summary = records.collect();
+
+        assert_eq!(summary.amount_for(TransactionType::Deposit), 4 * i128::from(i64::MAX));
+        assert_eq!(summary.total_amount(), 4 * i128::from(i64::MAX));
+    }
+
+    #[test]
+    fn pivot_sums_cells_by_user_and_type() {
+        let table = pivot_by_user_and_type(sample_records()).unwrap();
+
+        assert_eq!(table.users().collect::<Vec<_>>(), vec![10, 20]);
+        assert_eq!(table.get(10, TransactionType::Deposit), 150);
+        assert_eq!(table.get(10, TransactionType::Transfer), 30);
+        assert_eq!(table.get(10, TransactionType::Withdrawal), 0);
+        assert_eq!(table.get(20, TransactionType::Deposit), 7);
+        assert_eq!(table.get(20, TransactionType::Withdrawal), 5);
+        assert_eq!(table.get(99, TransactionType::Deposit), 0);
+    }
+
+    #[test]
+    fn pivot_writes_csv_matrix() {
+        let table = pivot_by_user_and_type(sample_records()).unwrap();
+        let mut output = Vec::new();
+
+        table.to_csv(&mut output).unwrap();
+
+        let expected = "\
+USER_ID,DEPOSIT,TRANSFER,WITHDRAWAL
+10,150,30,0
+20,7,0,5
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn pivot_reports_overflow() {
+        let records = vec![
+            Transaction { amount: i64::MAX, ..test_transaction(1) },
+            Transaction { amount: 1, ..test_transaction(2) },
+        ];
+
+        assert_eq!(pivot_by_user_and_type(records), Err(AmountOverflowError { id: 2 }));
+    }
+
+    #[test]
+    fn top_n_returns_largest_in_descending_order() {
+        let records = vec![
+            Transaction { amount: 50, ..test_transaction(1) },
+            Transaction { amount: 300, ..test_transaction(2) },
+            Transaction { amount: 120, ..test_transaction(3) },
+            Transaction { amount: 300, ..test_transaction(4) },
+            Transaction { amount: 7, ..test_transaction(5) },
+            Transaction { amount: 120, ..test_transaction(6) },
+        ];
+
+        let top = top_n_by_amount(records.into_iter().map(Ok::<_, ()>), 3).unwrap();
+
+        assert_eq!(top.iter().map(|t| t.id).collect::<Vec<_>>(), vec![2, 4, 3]);
+    }
+
+    #[test]
+    fn top_n_propagates_errors() {
+        let records = vec![Ok(Transaction { amount: 50, ..test_transaction(1) }), Err("broken")];
+
+        assert_eq!(top_n_by_amount(records, 1), Err("broken"));
+    }
+
+    #[test]
+    fn control_total_matches() {
+        assert_eq!(verify_control_total(sample_records(), 192), Ok(()));
+    }
+
+    #[test]
+    fn control_total_mismatch_reports_both_sums() {
+        assert_eq!(
+            verify_control_total(sample_records(), 200),
+            Err(ControlTotalMismatch { expected: 200, actual: 192 })
+        );
+    }
+
+    #[test]
+    fn bucket_by_time_groups_by_hour() {
+        const HOUR: u64 = 3_600_000;
+        let mut records = sample_records();
+        let timestamps = [0, HOUR - 1, HOUR, 3 * HOUR + 5, HOUR + 10];
+        for (record, timestamp) in records.iter_mut().zip(timestamps) {
+            record.timestamp = timestamp;
+        }
+
+        let buckets = bucket_by_time(records, HOUR);
+
+        let ids = |start: u64| buckets[&start].iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(buckets.keys().copied().collect::<Vec<_>>(), vec![0, HOUR, 3 * HOUR]);
+        assert_eq!(ids(0), vec![1, 2]);
+        assert_eq!(ids(HOUR), vec![3, 5]);
+        assert_eq!(ids(3 * HOUR), vec![4]);
+    }
+
+    #[test]
+    fn moving_average_uses_preceding_window() {
+        let amounts = [10, 20, 30, 40, 100];
+        let records = amounts
+            .iter()
+            .enumerate()
+            .map(|(i, &amount)| Ok::<_, ()>(Transaction { to_user_id: 1, amount, ..test_transaction(i as u64) }));
+
+        let averages: Vec<f64> = moving_average(records, 3).map(|r| r.unwrap().1).collect();
+
+        assert_eq!(averages, vec![0.0, 10.0, 15.0, 20.0, 30.0]);
+    }
+
+    #[test]
+    fn moving_average_passes_errors_through() {
+        let records = vec![
+            Ok(Transaction { to_user_id: 1, amount: 10, ..test_transaction(1) }),
+            Err("broken"),
+            Ok(Transaction { to_user_id: 1, amount: 30, ..test_transaction(2) }),
+        ];
+
+        let results: Vec<_> = moving_average(records, 2).collect();
+
+        assert_eq!(results[1], Err("broken"));
+        assert_eq!(results[2].as_ref().unwrap().1, 10.0);
+    }
+
+    #[test]
+    fn hyperloglog_estimate_is_within_error_bound() {
+        let mut counter = HyperLogLog::new();
+        let distinct = 100_000u64;
+
+        for _ in 0..3 {
+            for id in 1..=distinct {
+                counter.observe(id * 7919); // повторы не должны менять оценку
+            }
+        }
+
+        let estimate = counter.estimate() as f64;
+        let relative_error = (estimate - distinct as f64).abs() / distinct as f64;
+
+        // три стандартных отклонения
+        assert!(relative_error <= 3.0 * counter.error_bound(), "estimate {} is too far from {}", estimate, distinct);
+    }
+
+    #[test]
+    fn hyperloglog_small_cardinality_is_close() {
+        let mut counter = HyperLogLog::new();
+        for id in 0..100 {
+            counter.observe(id);
+        }
+
+        let estimate = counter.estimate();
+        assert!((98..=102).contains(&estimate), "estimate {}", estimate);
+        assert_eq!(HyperLogLog::new().estimate(), 0);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::common::Transaction;
+use crate::errors::TeeError;
+use crate::{Parser, Readable, Serializer, Writable};
+use std::io::{Read, Write};
+
+/// Итератор транзакций, копирующий каждую успешно прочитанную запись в `Serializer`.
+///
+/// Ошибка чтения передается как `TeeError::Read`, ошибка записи копии — как `TeeError::Write`;
+/// в обоих случаях транзакция не выдается. Сериализатор буферизует данные: чтобы записать остаток,
+/// заберите его через `into_serializer` или дождитесь удаления адаптера.
+pub struct Tee<TRecord, Source, TCopy, Target>
+where
+    TRecord: Readable<Source>,
+    Source: Read,
+    TCopy: Writable,
+    Target: Write,
+{
+    parser: Parser<TRecord, Source>,
+    serializer: Serializer<TCopy, Target>,
+}
+
+impl<TRecord, Source, TCopy, Target> Tee<TRecord, Source, TCopy, Target>
+where
+    TRecord: Readable<Source>,
+    Source: Read,
+    TCopy: Writable,
+    Target: Write,
+{
+    /// Возвращает сериализатор копии.
+    pub fn into_serializer(self) -> Serializer<TCopy, Target> {
+        self.serializer
+    }
+}
+
+impl<TRecord, Source, TCopy, Target> Iterator for Tee<TRecord, Source, TCopy, Target>
+where
+    TRecord: Readable<Source> + Into<Transaction>,
+    Source: Read,
+    TCopy: Writable,
+    Target: Write,
+{
+    type Item = Result<Transaction, TeeError<TRecord::Error, TCopy::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let transaction: Transaction = match self.parser.next()? {
+            Ok(record) => record.into(),
+            Err(e) => return Some(Err(TeeError::Read(e))),
+        };
+
+        if let Err(e) = self.serializer.push(&TCopy::from(transaction.clone())) {
+            return Some(Err(TeeError::Write(e)));
+        }
+
+        Some(Ok(transaction))
+    }
+}
+
+impl<TRecord, Source> Parser<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read
+{
+    /// Оборачивает парсер так, что каждая прочитанная транзакция дополнительно записывается в `serializer`.
+    pub fn tee<TCopy, Target>(self, serializer: Serializer<TCopy, Target>) -> Tee<TRecord, Source, TCopy, Target>
+    where
+        TCopy: Writable,
+        Target: Write,
+    {
+        Tee { parser: self, serializer }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{serialize_to_vec, YPBankCsvRecord, YPBankTextRecord};
+    use std::io::{Cursor, ErrorKind};
+
+    const CSV_DATA: &str = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"First\"
+2,BROKEN,0,10,100,2000,SUCCESS,\"Broken\"
+3,TRANSFER,10,20,50,3000,PENDING,\"Third\"
+";
+
+    #[test]
+    fn tee_copies_records_that_flowed_through() {
+        let mut copy = Vec::new();
+        let results: Vec<_> = Parser::<YPBankCsvRecord, _>::new(Cursor::new(CSV_DATA))
+            .tee(Serializer::<YPBankTextRecord, _>::new(&mut copy))
+            .collect();
+
+        assert!(matches!(results[1], Err(TeeError::Read(_))));
+        let transactions: Vec<Transaction> = results.into_iter().filter_map(Result::ok).collect();
+        assert_eq!(transactions.iter().map(|t| t.id).collect::<Vec<_>>(), vec![1, 3]);
+
+        let expected: Vec<YPBankTextRecord> = transactions.into_iter().map(Into::into).collect();
+        assert_eq!(copy, serialize_to_vec(&expected).unwrap());
+    }
+
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(ErrorKind::BrokenPipe, "closed"))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_error_is_reported() {
+        let serializer = Serializer::<YPBankTextRecord, _>::with_buffer_capacity(FailingWriter, 0);
+        let mut tee = Parser::<YPBankCsvRecord, _>::new(Cursor::new(CSV_DATA)).tee(serializer);
+
+        assert!(matches!(tee.next(), Some(Err(TeeError::Write(e))) if e.kind() == ErrorKind::BrokenPipe));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{Parser, Readable};
+use std::io::Read;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Итератор, выдающий записи парсера не чаще заданного числа в секунду.
+pub struct Throttled<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read
+{
+    parser: Parser<TRecord, Source>,
+    interval: Duration,
+    next_at: Option<Instant>,
+}
+
+impl<TRecord, Source> Iterator for Throttled<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read
+{
+    type Item = Result<TRecord, TRecord::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(next_at) = self.next_at {
+            let now = Instant::now();
+            if next_at > now {
+                thread::sleep(next_at - now);
+            }
+        }
+
+        let record = self.parser.next()?;
+        self.next_at = Some(Instant::now() + self.interval);
+
+        Some(record)
+    }
+}
+
+impl<TRecord, Source> Parser<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read
+{
+    /// Ограничивает скорость выдачи записей величиной `per_second` записей в секунду.
+    ///
+    /// Первая запись выдается сразу, каждая следующая — не раньше, чем через `1 / per_second` секунды
+    /// после предыдущей. При `per_second == 0` ограничение не применяется.
+    pub fn throttle(self, per_second: u32) -> Throttled<TRecord, Source> {
+        let interval = match per_second {
+            0 => Duration::ZERO,
+            rate => Duration::from_secs(1) / rate,
+        };
+
+        Throttled {
+            parser: self,
+            interval,
+            next_at: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::YPBankCsvRecord;
+    use std::io::Cursor;
+
+    const CSV_DATA: &str = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Desc 1\"
+2,DEPOSIT,0,10,100,2000,SUCCESS,\"Desc 2\"
+3,DEPOSIT,0,10,100,3000,SUCCESS,\"Desc 3\"
+4,DEPOSIT,0,10,100,4000,SUCCESS,\"Desc 4\"
+5,DEPOSIT,0,10,100,5000,SUCCESS,\"Desc 5\"
+";
+
+    #[test]
+    fn throttle_spaces_out_records() {
+        let parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(CSV_DATA));
+        let started = Instant::now();
+
+        let records: Vec<_> = parser.throttle(100).collect();
+
+        assert_eq!(records.len(), 5);
+        // пять записей при 100 в секунду: четыре интервала по 10 мс
+        assert!(started.elapsed() >= Duration::from_millis(40), "elapsed {:?}", started.elapsed());
+    }
+
+    #[test]
+    fn zero_rate_does_not_throttle() {
+        let parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(CSV_DATA));
+        let mut throttled = parser.throttle(0);
+
+        assert_eq!(throttled.by_ref().filter(Result::is_ok).count(), 5);
+    }
+}
@@ -1,17 +1,24 @@
-use crate::common::{Transaction, TransactionStatus, TransactionType};
-use crate::errors::TextRecordError;
-use crate::{Readable, Writable};
+use crate::common::{deserialize_currency, deserialize_user_id, hex_to_decimal, is_overflowing, LineKind, LineSource, ENUM_FIELDS, NUMERIC_FIELDS, Defaults, SentinelRendering, Transaction, TransactionStatus, TransactionType};
+use crate::errors::{LineError, TextRecordError};
+use crate::{Parser, RawBytesRecords, Readable, RecordWithRaw, Writable, YPBankCsvRecord};
 use serde::Deserialize;
 use serde_with::{serde_as, DisplayFromStr};
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
 
 //noinspection DuplicatedCode
 /// Запись транзакции в текстовом формате "ключ-значение".
 ///
 /// Каждая запись состоит из набора строк вида `КЛЮЧ: ЗНАЧЕНИЕ`.
 /// Записи разделяются одной или несколькими пустыми строками.
-/// Комментарии начинаются с символа `#`.
+/// Комментарии начинаются с символа `#`. Строки могут завершаться `\n`, `\r\n` или одиночным `\r`.
+/// Поле `DESCRIPTION` необязательно: при его отсутствии описание считается пустым.
+/// Необязательное поле `CURRENCY` содержит код валюты ISO 4217 и записывается, только если он задан.
+/// Значение записывается в кавычках; при чтении снимается ровно одна пара обрамляющих кавычек.
+/// Переводы строк и обратный слеш в описании экранируются как `\n`, `\r` и `\\`, поэтому
+/// многострочное описание занимает одну строку и не разрывает блок.
+/// Первая строка может содержать заголовок версии схемы `#!ypbank-txt vN`
+/// (см. `Parser::require_version`); при обычном чтении он пропускается как комментарий.
 #[serde_as]
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -23,12 +30,10 @@ pub struct YPBankTextRecord {
     #[serde(rename = "TX_TYPE")]
     transaction_type: TransactionType,
 
-    #[serde(rename = "FROM_USER_ID")]
-    #[serde_as(as = "DisplayFromStr")]
+    #[serde(rename = "FROM_USER_ID", deserialize_with = "deserialize_user_id")]
     from_user_id: u64,
 
-    #[serde(rename = "TO_USER_ID")]
-    #[serde_as(as = "DisplayFromStr")]
+    #[serde(rename = "TO_USER_ID", deserialize_with = "deserialize_user_id")]
     to_user_id: u64,
 
     #[serde(rename = "AMOUNT")]
@@ -41,8 +46,11 @@ pub struct YPBankTextRecord {
 
     #[serde(rename = "STATUS")]
     transaction_status: TransactionStatus,
-    #[serde(rename = "DESCRIPTION")]
-    description: String
+    #[serde(rename = "DESCRIPTION", default)]
+    description: String,
+
+    #[serde(rename = "CURRENCY", default, deserialize_with = "deserialize_currency")]
+    currency: Option<String>,
 }
 
 //noinspection DuplicatedCode
@@ -57,6 +65,7 @@ impl From<YPBankTextRecord> for Transaction {
             timestamp: value.timestamp,
             transaction_status: value.transaction_status,
             description: value.description,
+            currency: value.currency,
         }
     }
 }
@@ -72,99 +81,782 @@ impl From<Transaction> for YPBankTextRecord {
             timestamp: value.timestamp,
             transaction_status: value.transaction_status,
             description: value.description,
+            currency: value.currency,
         }
     }
 }
 
-impl<R: Read> Readable<R> for YPBankTextRecord {
-    type Reader = BufReader<R>;
-    type Error = TextRecordError;
+impl From<YPBankCsvRecord> for YPBankTextRecord {
+    fn from(value: YPBankCsvRecord) -> Self {
+        Transaction::from(value).into()
+    }
+}
 
-    fn build_reader(source: R) -> Self::Reader {
-        BufReader::new(source)
+//noinspection DuplicatedCode
+impl YPBankTextRecord {
+    /// Уникальный идентификатор транзакции.
+    pub fn id(&self) -> u64 {
+        self.id
     }
 
-    fn read(reader: &mut Self::Reader) -> Result<YPBankTextRecord, TextRecordError> {
-        if reader.fill_buf()?.is_empty() {
-            return Err(TextRecordError::EndOfFile);
+    /// Тип операции.
+    pub fn transaction_type(&self) -> TransactionType {
+        self.transaction_type
+    }
+
+    /// ID отправителя.
+    pub fn from_user_id(&self) -> u64 {
+        self.from_user_id
+    }
+
+    /// ID получателя.
+    pub fn to_user_id(&self) -> u64 {
+        self.to_user_id
+    }
+
+    /// Сумма операции.
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    /// Временная метка операции.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Статус транзакции.
+    pub fn transaction_status(&self) -> TransactionStatus {
+        self.transaction_status
+    }
+
+    /// Описание транзакции.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// Набор полей текстовой записи, присутствовавших в исходном блоке.
+///
+/// Позволяет отличить значения, явно заданные в источнике, от значений по умолчанию
+/// (например, при обновлении в базе данных только присутствовавших столбцов).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProcessedFields(u8);
+
+impl ProcessedFields {
+    pub const TX_ID: Self = Self(1 << 0);
+    pub const TX_TYPE: Self = Self(1 << 1);
+    pub const FROM_USER_ID: Self = Self(1 << 2);
+    pub const TO_USER_ID: Self = Self(1 << 3);
+    pub const AMOUNT: Self = Self(1 << 4);
+    pub const TIMESTAMP: Self = Self(1 << 5);
+    pub const STATUS: Self = Self(1 << 6);
+    pub const DESCRIPTION: Self = Self(1 << 7);
+
+    /// Поля, без которых запись не может быть разобрана.
+    pub const REQUIRED: Self = Self(0b0111_1111);
+
+    /// Имена ключей и соответствующие им флаги в порядке полей записи.
+    const FIELDS: [(&'static str, Self); 8] = [
+        ("TX_ID", Self::TX_ID),
+        ("TX_TYPE", Self::TX_TYPE),
+        ("FROM_USER_ID", Self::FROM_USER_ID),
+        ("TO_USER_ID", Self::TO_USER_ID),
+        ("AMOUNT", Self::AMOUNT),
+        ("TIMESTAMP", Self::TIMESTAMP),
+        ("STATUS", Self::STATUS),
+        ("DESCRIPTION", Self::DESCRIPTION),
+    ];
+
+    /// Возвращает флаг поля по имени ключа.
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::FIELDS.iter().find(|(name, _)| *name == key).map(|&(_, field)| field)
+    }
+
+    /// Имена полей из `required`, флаги которых не установлены, в порядке полей записи.
+    pub fn missing(self, required: Self) -> impl Iterator<Item = &'static str> {
+        Self::FIELDS
+            .into_iter()
+            .filter(move |&(_, field)| required.contains(field) && !self.contains(field))
+            .map(|(name, _)| name)
+    }
+
+    /// Проверяет, что установлены все флаги `other`.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Устанавливает флаги `other`.
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// Битовое представление набора.
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+}
+
+impl std::ops::BitOr for ProcessedFields {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Префикс заголовка версии схемы, например `#!ypbank-txt v1`.
+const VERSION_HEADER_PREFIX: &str = "#!ypbank-txt v";
+
+/// Максимальная длина строки по умолчанию (1 МиБ).
+const DEFAULT_MAX_LINE_BYTES: usize = 1024 * 1024;
+
+/// Максимальное число полей в блоке по умолчанию.
+const DEFAULT_MAX_RECORD_FIELDS: usize = 64;
+
+/// Разделитель блоков записей текстового формата: настройка `TextReader::record_separator`
+/// при чтении и `Serializer::with_options` при записи.
+///
+/// Режимы взаимоисключающие: в режиме пустой строки (по умолчанию) блок заканчивается пустой строкой,
+/// в режиме маркера — только строкой, совпадающей с маркером (без учета окружающих пробелов), а пустые
+/// строки внутри блока пропускаются. Маркер не должен совпадать со строками данных или комментариев.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RecordSeparator {
+    /// Маркер без окружающих пробелов; `None` — пустая строка.
+    marker: Option<String>,
+}
+
+impl RecordSeparator {
+    /// Разделитель пустой строкой (по умолчанию).
+    pub fn blank_line() -> Self {
+        Self::default()
+    }
+
+    /// Разделитель отдельной строкой-маркером, например `---`.
+    ///
+    /// Пустой маркер и маркер с переводом строки отклоняются ошибкой `InvalidInput`:
+    /// записанный с ними файл нельзя прочитать обратно.
+    pub fn marker(marker: &str) -> std::io::Result<Self> {
+        let marker = marker.trim();
+        if marker.is_empty() || marker.contains(['\n', '\r']) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Record separator marker {:?} must be a single non-blank line", marker),
+            ));
         }
 
-        let mut kv_pairs: HashMap<String, String> = HashMap::with_capacity(8);
-        let mut line_buf = String::with_capacity(128);
+        Ok(Self { marker: Some(marker.to_owned()) })
+    }
+}
 
-        loop {
-            line_buf.clear();
-            let bytes_read = reader.read_line(&mut line_buf)?;
+/// Читатель текстового формата с настраиваемыми ограничениями.
+///
+/// Создается автоматически в `Parser::new`; для изменения настроек его можно собрать вручную
+/// и передать в `Parser::from_reader`.
+pub struct TextReader<R> {
+    lines: LineSource<BufReader<R>>,
+    max_record_fields: usize,
+    capture_comments: bool,
+    /// Комментарии, непосредственно предшествующие последнему прочитанному блоку.
+    comments: Vec<String>,
+    /// Поля, присутствовавшие в последнем прочитанном блоке.
+    processed: ProcessedFields,
+    defaults: Defaults,
+    hex_numbers: bool,
+    case_insensitive_enums: bool,
+    blank_description_as_none: bool,
+    /// Переименования ключей, применяемые до разбора полей.
+    key_aliases: HashMap<String, String>,
+    allow_duplicate_keys: bool,
+    /// Собирать ли исходные байты блока в `raw`.
+    capture_raw: bool,
+    /// Исходные байты последнего прочитанного блока.
+    raw: Vec<u8>,
+    /// Число начатых блоков записей.
+    blocks_read: usize,
+    /// Завершился ли последний прочитанный блок концом файла, а не пустой строкой.
+    block_at_eof: bool,
+    /// Номера строк пар последнего прочитанного блока, в порядке пар.
+    block_lines: Vec<usize>,
+    separator: RecordSeparator,
+}
 
-            if bytes_read == 0 { //EOF
-                break;
-            }
+/// Снимает ровно одну пару обрамляющих кавычек.
+///
+/// Кавычки внутри значения, в том числе по краям, сохраняются: `""x""` читается как `"x"`.
+fn unquote(value: &str) -> &str {
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) => inner,
+        None => value,
+    }
+}
 
-            let trimmed = line_buf.trim();
-            if trimmed.starts_with('#') {
+/// Экранирует обратный слеш и переводы строк в описании.
+fn escape_description(description: &str) -> std::borrow::Cow<'_, str> {
+    if !description.contains(['\\', '\n', '\r']) {
+        return description.into();
+    }
+
+    let mut escaped = String::with_capacity(description.len() + 2);
+    for c in description.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.into()
+}
+
+/// Восстанавливает описание, записанное `escape_description`.
+///
+/// Неизвестные последовательности (например, `\t`) остаются как есть, чтобы файлы без
+/// экранирования с одиночными слешами читались по-прежнему.
+fn unescape_description(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.peek() {
+            Some('\\') => unescaped.push('\\'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            _ => {
+                unescaped.push('\\');
                 continue;
             }
+        }
+        chars.next();
+    }
+
+    unescaped
+}
+
+impl<R: Read> TextReader<R> {
+    /// Создает читатель с настройками по умолчанию.
+    pub fn new(source: R) -> Self {
+        Self::with_capacity(source, crate::DEFAULT_BUFFER_CAPACITY)
+    }
+
+    /// Создает читатель с буфером размером `capacity` байт.
+    pub fn with_capacity(source: R, capacity: usize) -> Self {
+        Self {
+            lines: LineSource::new(BufReader::with_capacity(capacity, source), DEFAULT_MAX_LINE_BYTES),
+            max_record_fields: DEFAULT_MAX_RECORD_FIELDS,
+            capture_comments: false,
+            comments: Vec::new(),
+            processed: ProcessedFields::default(),
+            defaults: Defaults::default(),
+            hex_numbers: false,
+            case_insensitive_enums: false,
+            blank_description_as_none: false,
+            key_aliases: HashMap::new(),
+            allow_duplicate_keys: false,
+            capture_raw: false,
+            raw: Vec::new(),
+            blocks_read: 0,
+            block_at_eof: false,
+            block_lines: Vec::new(),
+            separator: RecordSeparator::default(),
+        }
+    }
+
+    /// Разрешает шестнадцатеричную запись числовых полей с префиксом `0x` (например, `0x1F4`).
+    ///
+    /// Значения без префикса по-прежнему разбираются как десятичные. По умолчанию выключено.
+    pub fn hex_numbers(mut self, enabled: bool) -> Self {
+        self.hex_numbers = enabled;
+        self
+    }
+
+    /// Разрешает запись `TX_TYPE` и `STATUS` в любом регистре (например, `deposit` или `Deposit`).
+    ///
+    /// По умолчанию выключено, чтобы опечатки в регистре не оставались незамеченными.
+    pub fn case_insensitive_enums(mut self, enabled: bool) -> Self {
+        self.case_insensitive_enums = enabled;
+        self
+    }
+
+    /// Считает описание из одних пробельных символов отсутствующим: оно читается как пустое,
+    /// и `Transaction::description_opt` возвращает `None`.
+    ///
+    /// По умолчанию выключено: пробелы сохраняются как часть описания.
+    pub fn blank_description_as_none(mut self, enabled: bool) -> Self {
+        self.blank_description_as_none = enabled;
+        self
+    }
+
+    /// Задает таблицу переименования ключей для файлов с другой схемой (например, `ID` → `TX_ID`).
+    ///
+    /// Ключ заменяется до разбора полей, поэтому ошибки и `ProcessedFields` используют новое имя.
+    /// Ключи, отсутствующие в таблице, остаются без изменений.
+    pub fn key_aliases(mut self, key_aliases: HashMap<String, String>) -> Self {
+        self.key_aliases = key_aliases;
+        self
+    }
+
+    /// Разрешает повтор ключа в блоке: используется последнее значение.
+    ///
+    /// По умолчанию выключено: повтор почти всегда означает повреждение данных,
+    /// поэтому блок пропускается с ошибкой `TextRecordError::DuplicateKey`.
+    pub fn allow_duplicate_keys(mut self, enabled: bool) -> Self {
+        self.allow_duplicate_keys = enabled;
+        self
+    }
+
+    /// Задает символ, с которого начинаются комментарии (по умолчанию `#`).
+    ///
+    /// Префикс проверяется только в начале строки, поэтому значение `KEY: #value` комментарием не считается.
+    /// При другом префиксе строка `#` считается данными, и заголовок версии нужно читать через
+    /// `Parser::require_version`.
+    pub fn comment_prefix(mut self, comment_prefix: char) -> Self {
+        self.lines.set_comment_prefix(comment_prefix);
+        self
+    }
+
+    /// Задает значения, подставляемые вместо отсутствующих в блоке полей.
+    ///
+    /// Подстановка не влияет на `ProcessedFields`: поле по-прежнему считается отсутствовавшим.
+    pub fn defaults(mut self, defaults: Defaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Ограничивает длину одной строки в байтах.
+    ///
+    /// При превышении чтение прерывается ошибкой `TextRecordError::LineTooLong`
+    /// до того, как буфер строки вырастет сверх лимита.
+    pub fn max_line_bytes(mut self, max_line_bytes: usize) -> Self {
+        self.lines.set_max_line_bytes(max_line_bytes);
+        self
+    }
+
+    /// Ограничивает число полей в одном блоке.
+    ///
+    /// При превышении блок пропускается, а чтение возвращает `TextRecordError::RecordTooLarge`.
+    pub fn max_record_fields(mut self, max_record_fields: usize) -> Self {
+        self.max_record_fields = max_record_fields;
+        self
+    }
+
+    /// Задает разделитель блоков записей (по умолчанию пустая строка).
+    pub fn record_separator(mut self, separator: RecordSeparator) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Является ли последняя прочитанная строка вида `kind` разделителем блоков.
+    fn is_separator(&self, kind: LineKind) -> bool {
+        match &self.separator.marker {
+            None => kind == LineKind::Blank,
+            Some(marker) => self.lines.line() == marker,
+        }
+    }
+
+    /// Пропускает оставшиеся строки текущего блока вместе с завершающим его разделителем.
+    fn skip_block(&mut self) -> Result<(), TextRecordError> {
+        while let Some(kind) = self.lines.next_line()? {
+            if self.is_separator(kind) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Читает очередной блок пар `КЛЮЧ: ЗНАЧЕНИЕ` в порядке их следования.
+    ///
+    /// Комментарии и разделители перед блоком пропускаются, кавычки вокруг значений снимаются.
+    /// Если включен захват комментариев, комментарии перед блоком сохраняются в `comments`;
+    /// разделитель между комментарием и блоком разрывает их связь.
+    /// Строка без двоеточия прерывает блок ошибкой `MissingColonAfterKey`, строка с пустым ключом —
+    /// ошибкой `EmptyKey`, повтор ключа (если не разрешен) — ошибкой `DuplicateKey`, а лишнее поле —
+    /// ошибкой `RecordTooLarge`; остаток блока при этом пропускается.
+    /// Номера строк пар сохраняются в `block_lines`.
+    /// Если данных больше нет, возвращает `TextRecordError::EndOfFile`.
+    pub(crate) fn read_block(&mut self) -> Result<Vec<(String, String)>, TextRecordError> {
+        self.comments.clear();
+        self.block_lines.clear();
+        self.block_at_eof = false;
+        self.raw.clear();
+        if self.lines.peek_byte()?.is_none() {
+            return Err(TextRecordError::EndOfFile);
+        }
+
+        let mut kv_pairs = Vec::with_capacity(8);
 
-            if trimmed.is_empty() {
+        while let Some(kind) = self.lines.next_line()? {
+            if self.is_separator(kind) {
                 if !kv_pairs.is_empty() {
-                    return Ok(Self::parse_transaction(&mut kv_pairs)?);
+                    return Ok(kv_pairs);
                 }
 
+                self.comments.clear();
                 continue;
             }
 
-            let (k, v) = trimmed
-                .split_once(':')
-                .ok_or(TextRecordError::MissingColonAfterKey)?;
+            // исходные байты блока: от первой пары до последней строки перед разделителем
+            if self.capture_raw && (kind == LineKind::Content || !kv_pairs.is_empty()) {
+                self.raw.extend_from_slice(self.lines.raw());
+            }
+
+            match kind {
+                LineKind::Comment => {
+                    if self.capture_comments && kv_pairs.is_empty() {
+                        self.comments.push(self.lines.text().to_owned());
+                    }
+
+                    continue;
+                }
+                // пустая строка, не являющаяся разделителем, допустима и внутри блока
+                LineKind::Blank => continue,
+                LineKind::Content => {}
+            }
+
+            if kv_pairs.is_empty() {
+                self.blocks_read += 1;
+            }
+
+            // ключ — до первого двоеточия, поэтому двоеточия в значении сохраняются
+            let Some((key, value)) = self.lines.text().split_once(':').map(|(k, v)| (k.trim(), v.trim())) else {
+                let (line, record_index) = (self.lines.line_number(), self.record_index());
+                self.skip_block()?;
+                return Err(TextRecordError::MissingColonAfterKey { line, record_index });
+            };
+
+            if key.is_empty() {
+                let (line, record_index) = (self.lines.line_number(), self.record_index());
+                self.skip_block()?;
+                return Err(TextRecordError::EmptyKey { line, record_index });
+            }
+
+            if kv_pairs.len() >= self.max_record_fields {
+                let (line, record_index) = (self.lines.line_number(), self.record_index());
+                self.skip_block()?;
+                return Err(TextRecordError::RecordTooLarge { fields: self.max_record_fields, line, record_index });
+            }
+
+            let key = self.key_aliases.get(key).map_or(key, String::as_str);
+            if !self.allow_duplicate_keys && kv_pairs.iter().any(|(k, _)| k == key) {
+                let (key, line, record_index) = (key.to_owned(), self.lines.line_number(), self.record_index());
+                self.skip_block()?;
+                return Err(TextRecordError::DuplicateKey { key, line, record_index });
+            }
 
-            kv_pairs.insert(k.trim().to_owned(), v.trim().trim_matches('"').to_owned());
+            self.block_lines.push(self.lines.line_number());
+            kv_pairs.push((key.to_owned(), unquote(value).to_owned()));
         }
 
+        self.block_at_eof = true;
         if kv_pairs.is_empty() {
             Err(TextRecordError::EndOfFile)
         } else {
-            Ok(Self::parse_transaction(&mut kv_pairs)?)
+            Ok(kv_pairs)
+        }
+    }
+
+    /// Номер (с нуля) последнего начатого блока записи.
+    fn record_index(&self) -> usize {
+        self.blocks_read.saturating_sub(1)
+    }
+
+    /// Создает ошибку разбора поля `key` последнего блока.
+    ///
+    /// Если поле в блоке не встречалось (или не указано), ошибка относится к первой строке блока.
+    fn parse_error(&self, error: serde::de::value::Error, kv_pairs: &[(String, String)], key: Option<&str>) -> TextRecordError {
+        TextRecordError::ParseError { error, line: self.field_line(kv_pairs, key), record_index: self.record_index() }
+    }
+
+    /// Номер строки поля `key` последнего блока или первой строки блока, если поля в нем нет.
+    fn field_line(&self, kv_pairs: &[(String, String)], key: Option<&str>) -> usize {
+        key.and_then(|key| kv_pairs.iter().rposition(|(k, _)| k == key))
+            .map_or_else(|| self.block_start_line(), |index| self.block_lines[index])
+    }
+
+    /// Номер первой строки последнего прочитанного блока.
+    fn block_start_line(&self) -> usize {
+        self.block_lines.first().copied().unwrap_or(self.lines.line_number())
+    }
+}
+
+impl<R: Read + Seek> TextReader<R> {
+    /// Возвращает читатель к началу источника и сбрасывает счетчики строк и блоков.
+    pub(crate) fn rewind(&mut self) -> Result<(), TextRecordError> {
+        self.lines.get_mut().seek(SeekFrom::Start(0))?;
+        self.lines.reset();
+        self.blocks_read = 0;
+        self.block_lines.clear();
+        self.comments.clear();
+        self.processed = ProcessedFields::default();
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Readable<R> for YPBankTextRecord {
+    type Reader = TextReader<R>;
+    type Error = TextRecordError;
+
+    fn build_reader(source: R) -> Self::Reader {
+        TextReader::new(source)
+    }
+
+    fn build_reader_with_capacity(source: R, capacity: usize) -> Self::Reader {
+        TextReader::with_capacity(source, capacity)
+    }
+
+    fn read(reader: &mut Self::Reader) -> Result<YPBankTextRecord, TextRecordError> {
+        let kv_pairs = reader.read_block()?;
+
+        reader.processed = ProcessedFields::default();
+        for (key, _) in &kv_pairs {
+            if let Some(field) = ProcessedFields::from_key(key) {
+                reader.processed.insert(field);
+            }
         }
+
+        let mut fields: HashMap<String, String> = kv_pairs.iter().cloned().collect();
+        if let Some(description) = fields.get_mut("DESCRIPTION") {
+            *description = unescape_description(description);
+            if reader.blank_description_as_none && description.trim().is_empty() {
+                description.clear();
+            }
+        }
+        reader.defaults.fill_missing(&mut fields);
+
+        let filled = fields
+            .keys()
+            .filter_map(|key| ProcessedFields::from_key(key))
+            .fold(ProcessedFields::default(), |acc, field| acc | field);
+        if reader.block_at_eof && !filled.contains(ProcessedFields::REQUIRED) {
+            return Err(TextRecordError::IncompleteRecord {
+                missing: filled.missing(ProcessedFields::REQUIRED).map(str::to_owned).collect(),
+                line: reader.block_start_line(),
+                record_index: reader.record_index(),
+            });
+        }
+
+        if let Some(field) = filled.missing(ProcessedFields::REQUIRED).next() {
+            return Err(TextRecordError::MissingField {
+                field,
+                line: reader.block_start_line(),
+                record_index: reader.record_index(),
+            });
+        }
+
+        if reader.hex_numbers {
+            for key in NUMERIC_FIELDS {
+                if let Some(value) = fields.get_mut(key) {
+                    *value = hex_to_decimal(value)
+                        .map_err(|error| reader.parse_error(serde::de::Error::custom(error), &kv_pairs, Some(key)))?
+                        .into_owned();
+                }
+            }
+        }
+
+        if reader.case_insensitive_enums {
+            for key in ENUM_FIELDS {
+                if let Some(value) = fields.get_mut(key) {
+                    value.make_ascii_uppercase();
+                }
+            }
+        }
+
+        for field in NUMERIC_FIELDS {
+            if let Some(value) = fields.get(field).filter(|value| is_overflowing(field, value)) {
+                return Err(TextRecordError::FieldOverflow {
+                    field,
+                    value: value.clone(),
+                    line: reader.field_line(&kv_pairs, Some(field)),
+                    record_index: reader.record_index(),
+                });
+            }
+        }
+
+        Self::parse_transaction(&mut fields)
+            .map_err(|(key, error)| reader.parse_error(error, &kv_pairs, key.as_deref()))
+    }
+
+    fn check_version(reader: &mut Self::Reader, accepted: &[u32]) -> Result<(), TextRecordError> {
+        // заголовок версии — это комментарий, поэтому первую строку можно поглотить только если она начинается с '#'
+        if reader.lines.peek_byte()? != Some(b'#') {
+            return Err(TextRecordError::MissingVersion);
+        }
+
+        match reader.lines.next_line() {
+            Ok(Some(_)) => {}
+            // строка не в UTF-8 тоже означает отсутствие заголовка
+            Ok(None) => return Err(TextRecordError::MissingVersion),
+            Err(LineError::InvalidUtf8 { .. }) => return Err(TextRecordError::MissingVersion),
+            Err(e) => return Err(e.into()),
+        }
+
+        let version = reader.lines.line()
+            .strip_prefix(VERSION_HEADER_PREFIX)
+            .ok_or(TextRecordError::MissingVersion)?
+            .parse()
+            .map_err(|_| TextRecordError::MissingVersion)?;
+
+        if !accepted.contains(&version) {
+            return Err(TextRecordError::UnsupportedVersion { version });
+        }
+
+        Ok(())
+    }
+
+    fn rewind(reader: &mut Self::Reader) -> Result<(), TextRecordError>
+    where
+        R: Seek
+    {
+        reader.rewind()
+    }
+
+    fn is_recoverable(error: &TextRecordError) -> bool {
+        // блок с ошибкой уже прочитан целиком, следующее чтение начнется со следующего блока
+        matches!(
+            error,
+            TextRecordError::ParseError { .. }
+                | TextRecordError::MissingColonAfterKey { .. }
+                | TextRecordError::EmptyKey { .. }
+                | TextRecordError::DuplicateKey { .. }
+                | TextRecordError::FieldOverflow { .. }
+                | TextRecordError::MissingField { .. }
+                | TextRecordError::RecordTooLarge { .. }
+        )
+    }
+}
+
+/// Итератор транзакций вместе с комментариями, предшествующими каждой записи.
+pub struct CommentedRecords<R: Read> {
+    parser: Parser<YPBankTextRecord, R>,
+}
+
+impl<R: Read> Iterator for CommentedRecords<R> {
+    type Item = Result<(Transaction, Vec<String>), TextRecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.parser.next()?;
+        let comments = std::mem::take(&mut self.parser.reader.comments);
+
+        Some(record.map(|record| (record.into(), comments)))
+    }
+}
+
+/// Итератор транзакций вместе с набором полей, присутствовавших в исходной записи.
+pub struct PresenceRecords<R: Read> {
+    parser: Parser<YPBankTextRecord, R>,
+}
+
+impl<R: Read> Iterator for PresenceRecords<R> {
+    type Item = Result<(Transaction, ProcessedFields), TextRecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.parser.next()?;
+
+        Some(record.map(|record| (record.into(), self.parser.reader.processed)))
+    }
+}
+
+impl<R: Read> Iterator for RawBytesRecords<YPBankTextRecord, R> {
+    type Item = Result<RecordWithRaw, TextRecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.parser.next()?;
+        let raw = std::mem::take(&mut self.parser.reader.raw);
+
+        Some(record.map(|record| RecordWithRaw { parsed: record.into(), raw }))
+    }
+}
+
+impl<R: Read> Parser<YPBankTextRecord, R> {
+    /// Переключает парсер в режим, в котором вместе с каждой транзакцией возвращаются
+    /// исходные байты её блока (без предшествующих комментариев и пустой строки-разделителя).
+    pub fn with_raw(mut self) -> RawBytesRecords<YPBankTextRecord, R> {
+        self.reader.capture_raw = true;
+
+        RawBytesRecords { parser: self }
+    }
+
+    /// Переключает парсер в режим, в котором вместе с каждой транзакцией возвращается
+    /// набор полей (`ProcessedFields`), явно заданных в записи.
+    pub fn with_presence(self) -> PresenceRecords<R> {
+        PresenceRecords { parser: self }
+    }
+
+    /// Переключает парсер в режим, в котором вместе с каждой транзакцией возвращаются
+    /// строки комментариев (без символа `#`), стоящие непосредственно перед записью.
+    pub fn with_comments(mut self) -> CommentedRecords<R> {
+        self.reader.capture_comments = true;
+
+        CommentedRecords { parser: self }
     }
 }
 
 impl Writable for YPBankTextRecord {
     type Error = std::io::Error;
+    type Options = RecordSeparator;
 
     fn write_header<W: Write>(_: &mut W) -> Result<(), Self::Error> {
         Ok(())
     }
 
     fn write<W: Write>(&self, writer: &mut W) -> Result<(), Self::Error> {
-        let mut buff_writer = BufWriter::new(writer);
+        self.write_rendered(writer, &SentinelRendering::Zero, &RecordSeparator::blank_line())
+    }
 
-        writeln!(&mut buff_writer, "TX_ID: {}", self.id)?;
-        writeln!(&mut buff_writer, "TX_TYPE: {}", self.transaction_type)?;
-        writeln!(&mut buff_writer, "FROM_USER_ID: {}", self.from_user_id)?;
+    fn write_rendered<W: Write>(&self, writer: &mut W, sentinel: &SentinelRendering, separator: &RecordSeparator) -> Result<(), Self::Error> {
+        // буферизацию обеспечивает `Serializer`, поэтому запись идет напрямую
+        writeln!(writer, "TX_ID: {}", self.id)?;
+        writeln!(writer, "TX_TYPE: {}", self.transaction_type)?;
+        writeln!(writer, "FROM_USER_ID: {}", sentinel.render(self.from_user_id))?;
 
-        writeln!(&mut buff_writer, "TO_USER_ID: {}", self.to_user_id)?;
-        writeln!(&mut buff_writer, "AMOUNT: {}", self.amount)?;
-        writeln!(&mut buff_writer, "TIMESTAMP: {}", self.timestamp)?;
+        writeln!(writer, "TO_USER_ID: {}", sentinel.render(self.to_user_id))?;
+        writeln!(writer, "AMOUNT: {}", self.amount)?;
+        writeln!(writer, "TIMESTAMP: {}", self.timestamp)?;
 
-        writeln!(&mut buff_writer, "STATUS: {}", self.transaction_status)?;
-        writeln!(&mut buff_writer, "DESCRIPTION: \"{}\"", self.description)?;
-        writeln!(&mut buff_writer)?;
-        buff_writer.flush()?;
+        writeln!(writer, "STATUS: {}", self.transaction_status)?;
+        writeln!(writer, "DESCRIPTION: \"{}\"", escape_description(&self.description))?;
+        if let Some(currency) = &self.currency {
+            writeln!(writer, "CURRENCY: {}", currency)?;
+        }
+        match &separator.marker {
+            None => writeln!(writer)?,
+            Some(marker) => writeln!(writer, "{}", marker)?,
+        }
         Ok(())
     }
 }
 
 impl YPBankTextRecord {
-    fn parse_transaction(map: &mut HashMap<String, String>) -> Result<Self, serde::de::value::Error> {
-        Self::deserialize(serde::de::value::MapDeserializer::new(map.drain()))
-            .map_err(|e: serde::de::value::Error| e)
+
+    /// Разбирает запись из пар `КЛЮЧ: ЗНАЧЕНИЕ`.
+    ///
+    /// При ошибке вместе с ней возвращается ключ поля, на котором разбор остановился,
+    /// или `None`, если ошибка обнаружена после разбора всех полей (например, поле отсутствует).
+    fn parse_transaction(map: &mut HashMap<String, String>) -> Result<Self, (Option<String>, serde::de::value::Error)> {
+        let mut pairs = map.drain();
+        let mut current_key = None;
+        let tracked = std::iter::from_fn(|| {
+            let pair = pairs.next();
+            current_key = pair.as_ref().map(|(key, _): &(String, String)| key.clone());
+            pair
+        });
+
+        Self::deserialize(serde::de::value::MapDeserializer::new(tracked))
+            .map_err(|e: serde::de::value::Error| (current_key, e))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Parser, Serializer};
+    use crate::Serializer;
     use std::io::Cursor;
 
     #[test]
@@ -178,6 +870,7 @@ mod tests {
             timestamp: 1633056800000,
             transaction_status: TransactionStatus::Failure,
             description: "User transfer".to_string(),
+            currency: None,
         };
         let rec2 = YPBankTextRecord {
             id: 999,
@@ -188,6 +881,7 @@ mod tests {
             timestamp: 1700000000,
             transaction_status: TransactionStatus::Success,
             description: "Second tx".to_string(),
+            currency: None,
         };
 
         let writer = Cursor::new(Vec::<u8>::new());
@@ -232,10 +926,9 @@ DESCRIPTION: "User transfer"
 
         let cur = Cursor::new(input.as_bytes());
         let mut parser = Parser::<YPBankTextRecord, _>::new(cur);
-        let rec = parser.next().expect("Should have a record");
+        let rec = parser.next().expect("Should have a record").unwrap();
 
         assert!(parser.next().is_none(), "Should be consumed");
-        assert!(parser.read_error.is_none(), "Read error: {:?}", parser.read_error);
 
         assert_eq!(rec.id, 2312321321);
         assert_eq!(rec.transaction_type, TransactionType::Transfer);
@@ -274,11 +967,10 @@ DESCRIPTION: "User withdrawal"
         let cur = Cursor::new(input.as_bytes());
         let mut parser = Parser::<YPBankTextRecord, _>::new(cur);
 
-        let r1 = parser.next().expect("Should have first record");
-        let r2 = parser.next().expect("Should have second record");
+        let r1 = parser.next().expect("Should have first record").unwrap();
+        let r2 = parser.next().expect("Should have second record").unwrap();
 
         assert!(parser.next().is_none());
-        assert!(parser.read_error.is_none(), "Read error: {}", parser.read_error.unwrap());
 
         assert_eq!(r1.id, 1);
         assert_eq!(r1.transaction_type, TransactionType::Deposit);
@@ -314,10 +1006,9 @@ DESCRIPTION: "No trailing blank"
 
         let cur = Cursor::new(input.as_bytes());
         let mut parser = Parser::<YPBankTextRecord, _>::new(cur);
-        let rec = parser.next().expect("Should have one record");
+        let rec = parser.next().expect("Should have one record").unwrap();
 
         assert!(parser.next().is_none());
-        assert!(parser.read_error.is_none(), "Read error: {}", parser.read_error.unwrap());
 
         assert_eq!(rec.id, 3);
         assert_eq!(rec.transaction_type, TransactionType::Transfer);
@@ -338,54 +1029,314 @@ TX_TYPE: DEPOSIT
 
         let cur = Cursor::new(input.as_bytes());
         let mut parser = Parser::<YPBankTextRecord, _>::new(cur);
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::MissingColonAfterKey { .. }))));
         assert!(parser.next().is_none());
+    }
 
-        if let Some(err) = parser.read_error {
-             assert!(matches!(err, TextRecordError::MissingColonAfterKey));
-        } else {
-             panic!("Expected read_error to be set");
+    #[test]
+    fn reads_ten_thousand_blocks_with_comments() {
+        let mut input = String::new();
+        for id in 0..10_000u64 {
+            if id % 100 == 0 {
+                input.push_str("# checkpoint\n");
+            }
+            input.push_str(&format!(
+                "TX_ID: {}\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: {}\nTIMESTAMP: 1\nSTATUS: SUCCESS\n\n",
+                id,
+                id * 2
+            ));
         }
+
+        let records = Parser::<YPBankTextRecord, _>::new(Cursor::new(input.as_bytes())).into_vec().unwrap();
+
+        assert_eq!(records.len(), 10_000);
+        assert!(records.iter().enumerate().all(|(i, r)| r.id == i as u64 && r.amount == 2 * i as u64));
     }
 
     #[test]
-    fn read_invalid_data_types_errors() {
-        let input_negative_id = r#"
-TX_ID: -5
-TX_TYPE: DEPOSIT
-"#;
-        let cur = Cursor::new(input_negative_id.as_bytes());
-        let mut parser = Parser::<YPBankTextRecord, _>::new(cur);
-        assert!(parser.next().is_none());
-        assert!(matches!(parser.read_error.unwrap(), TextRecordError::ParseError { .. }));
+    fn rewind_resets_line_numbers() {
+        let input = "TX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: x\nTIMESTAMP: 1\nSTATUS: SUCCESS\n";
+        let mut parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(input.as_bytes()));
 
-        let input_bad_amount = r#"
-TX_ID: 10
-AMOUNT: not_a_number
-"#;
-        let cur = Cursor::new(input_bad_amount.as_bytes());
-        let mut parser = Parser::<YPBankTextRecord, _>::new(cur);
-        assert!(parser.next().is_none());
-        assert!(matches!(parser.read_error.unwrap(), TextRecordError::ParseError { .. }));
+        for _ in 0..2 {
+            let error = parser.next().unwrap().unwrap_err();
+            assert!(matches!(error, TextRecordError::ParseError { line: 5, record_index: 0, .. }));
+            assert!(parser.next().is_none());
 
-        let input_bad_status = r#"
-TX_ID: 11
-STATUS: UNKNOWN_STATUS
-"#;
-        let cur = Cursor::new(input_bad_status.as_bytes());
-        let mut parser = Parser::<YPBankTextRecord, _>::new(cur);
-        assert!(parser.next().is_none());
-        assert!(matches!(parser.read_error.unwrap(), TextRecordError::ParseError { .. }));
+            parser.rewind().unwrap();
+        }
     }
 
     #[test]
-    fn read_errors_on_empty_source() {
+    fn errors_report_line_and_record_index() {
+        let input = "\
+# header comment
+
+TX_ID: 1
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 100
+TIMESTAMP: 1
+STATUS: SUCCESS
+
+
+# broken amount below
+TX_ID: 2
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 1x0
+TIMESTAMP: 2
+STATUS: SUCCESS
+
+TX_ID: 3
+TX_TYPE DEPOSIT
+";
+
+        let mut parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(input.as_bytes()));
+
+        assert_eq!(parser.next().unwrap().unwrap().id, 1);
+
+        let error = parser.next().unwrap().unwrap_err();
+        assert!(matches!(error, TextRecordError::ParseError { line: 17, record_index: 1, .. }));
+        assert_eq!(error.to_string(), "parse error at line 17: invalid digit found in string");
+
+        let error = parser.next().unwrap().unwrap_err();
+        assert!(matches!(error, TextRecordError::MissingColonAfterKey { line: 22, record_index: 2 }));
+    }
+
+    #[test]
+    fn errors_expose_underlying_source() {
+        use std::error::Error;
+
+        let input = "TX_ID: 1x\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nTIMESTAMP: 1\nSTATUS: SUCCESS\n";
+        let error = Parser::<YPBankTextRecord, _>::new(Cursor::new(input)).next().unwrap().unwrap_err();
+        assert!(matches!(error, TextRecordError::ParseError { .. }));
+        assert_eq!(error.source().unwrap().to_string(), "invalid digit found in string");
+
+        let error = Parser::<YPBankTextRecord, _>::new(Cursor::new(b"TX_ID: \xFF\n".to_vec())).next().unwrap().unwrap_err();
+        assert!(matches!(error, TextRecordError::InvalidUtf8 { .. }));
+        assert!(error.source().is_none());
+        assert!(!<YPBankTextRecord as Readable<Cursor<Vec<u8>>>>::is_recoverable(&error));
+    }
+
+    #[test]
+    fn invalid_utf8_reports_byte_offset_in_source() {
+        let mut input = b"TX_ID: 1\nTX_TYPE: DEPOSIT\nDESCRIPTION: \"Caf".to_vec();
+        let expected_offset = input.len();
+        input.extend_from_slice(b"\xE9\"\nSTATUS: SUCCESS\n");
+
+        let error = Parser::<YPBankTextRecord, _>::new(Cursor::new(input)).next().unwrap().unwrap_err();
+
+        assert!(matches!(error, TextRecordError::InvalidUtf8 { byte_offset } if byte_offset == expected_offset));
+        assert_eq!(error.to_string(), format!("invalid UTF-8 at byte {}", expected_offset));
+    }
+
+    #[test]
+    fn overflowing_amount_is_reported_separately_from_invalid_value() {
+        let block = |amount: &str| format!("TX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: {}\nTIMESTAMP: 1\nSTATUS: SUCCESS\n\n", amount);
+        let input = block("99999999999999999999") + &block("1x0");
+
+        let mut parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(input));
+        let error = parser.next().unwrap().unwrap_err();
+        assert!(matches!(&error, TextRecordError::FieldOverflow { field: "AMOUNT", value, line: 5, record_index: 0 } if value == "99999999999999999999"));
+        assert_eq!(error.to_string(), "value 99999999999999999999 of field AMOUNT at line 5 is out of range");
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::ParseError { line: 13, .. }))));
+    }
+
+    #[test]
+    fn currency_is_read_and_written_when_present() {
+        let block = "TX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nTIMESTAMP: 1\nSTATUS: SUCCESS\n";
+        let input = format!("{block}CURRENCY: EUR\n\n{block}\n{block}CURRENCY: E1R\n");
+
+        let mut parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(input));
+        let with_currency: Transaction = parser.next().unwrap().unwrap().into();
+        let without_currency: Transaction = parser.next().unwrap().unwrap().into();
+        assert_eq!(with_currency.currency.as_deref(), Some("EUR"));
+        assert_eq!(without_currency.currency, None);
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::ParseError { line: 25, .. }))));
+
+        let output = String::from_utf8(crate::serialize_to_vec(&[YPBankTextRecord::from(with_currency)]).unwrap()).unwrap();
+        assert!(output.ends_with("CURRENCY: EUR\n\n"));
+        let output = String::from_utf8(crate::serialize_to_vec(&[YPBankTextRecord::from(without_currency)]).unwrap()).unwrap();
+        assert!(!output.contains("CURRENCY"));
+    }
+
+    #[test]
+    fn missing_field_error_points_to_block_start() {
+        let input = "\n\nTX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nTIMESTAMP: 1\nSTATUS: SUCCESS\n\n";
+
+        let error = Parser::<YPBankTextRecord, _>::new(Cursor::new(input.as_bytes())).next().unwrap().unwrap_err();
+
+        assert!(matches!(error, TextRecordError::MissingField { field: "AMOUNT", line: 3, record_index: 0 }));
+        assert_eq!(error.to_string(), "missing field AMOUNT in record at line 3");
+    }
+
+    #[test]
+    fn block_cut_off_by_end_of_file_lists_missing_fields() {
+        let input = "TX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\n\nTX_ID: 2\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\n";
+        let mut parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(input.as_bytes()));
+
+        // блок, завершенный пустой строкой, по-прежнему дает MissingField
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::MissingField { field: "TIMESTAMP", .. }))));
+
+        let error = parser.next().unwrap().unwrap_err();
+        assert_eq!(error.to_string(), "incomplete record at line 7 at end of file, missing TIMESTAMP, STATUS");
+        match error {
+            TextRecordError::IncompleteRecord { missing, line, record_index } => {
+                assert_eq!(missing, vec!["TIMESTAMP", "STATUS"]);
+                assert_eq!((line, record_index), (7, 1));
+            }
+            other => panic!("Expected IncompleteRecord, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bad_middle_block_does_not_discard_later_blocks() {
+        let input = r#"
+TX_ID: 1
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 100
+TIMESTAMP: 1
+STATUS: SUCCESS
+
+TX_ID: 2
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: not_a_number
+TIMESTAMP: 2
+STATUS: SUCCESS
+
+TX_ID: 3
+TX_TYPE DEPOSIT
+AMOUNT: 300
+
+TX_ID: 4
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 400
+TIMESTAMP: 4
+STATUS: SUCCESS
+"#;
+
+        let mut parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(input.as_bytes()));
+
+        assert_eq!(parser.next().unwrap().unwrap().id, 1);
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::ParseError { .. }))));
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::MissingColonAfterKey { .. }))));
+        assert_eq!(parser.next().unwrap().unwrap().id, 4);
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn read_invalid_data_types_errors() {
+        let input_negative_id = r#"
+TX_ID: -5
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 1
+AMOUNT: 1
+TIMESTAMP: 1
+STATUS: SUCCESS
+"#;
+        let cur = Cursor::new(input_negative_id.as_bytes());
+        let mut parser = Parser::<YPBankTextRecord, _>::new(cur);
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::ParseError { .. }))));
+
+        let input_bad_amount = r#"
+TX_ID: 10
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 1
+AMOUNT: not_a_number
+TIMESTAMP: 1
+STATUS: SUCCESS
+"#;
+        let cur = Cursor::new(input_bad_amount.as_bytes());
+        let mut parser = Parser::<YPBankTextRecord, _>::new(cur);
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::ParseError { .. }))));
+
+        let input_bad_status = r#"
+TX_ID: 11
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 1
+AMOUNT: 1
+TIMESTAMP: 1
+STATUS: UNKNOWN_STATUS
+"#;
+        let cur = Cursor::new(input_bad_status.as_bytes());
+        let mut parser = Parser::<YPBankTextRecord, _>::new(cur);
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::ParseError { .. }))));
+    }
+
+    #[test]
+    fn read_errors_on_empty_source() {
         let input = "";
         let cur = Cursor::new(input.as_bytes());
         let mut parser = Parser::<YPBankTextRecord, _>::new(cur);
-        let result = parser.next();
+        assert!(parser.next().is_none());
+    }
+
+    const TWO_RECORDS: &str = "\
+# Record 1
+TX_ID: 1
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 100
+TIMESTAMP: 1
+STATUS: SUCCESS
+DESCRIPTION: \"Terminal deposit\"
+
+TX_ID: 2
+TX_TYPE: WITHDRAWAL
+FROM_USER_ID: 10
+TO_USER_ID: 0
+AMOUNT: 50
+TIMESTAMP: 2
+STATUS: PENDING
+DESCRIPTION: \"User withdrawal \"
+";
+
+    fn read_with_line_ending(line_ending: &str, capacity: usize) -> Vec<Transaction> {
+        let input = TWO_RECORDS.replace('\n', line_ending);
+        let reader = TextReader::with_capacity(Cursor::new(input.into_bytes()), capacity);
+
+        Parser::<YPBankTextRecord, _>::from_reader(reader).map(|r| r.map(Transaction::from)).collect::<Result<_, _>>().unwrap()
+    }
 
-        assert!(result.is_none());
-        assert!(parser.read_error.is_none(), "Expected no read error on empty source, got: {:?}", parser.read_error);
+    #[test]
+    fn crlf_and_lone_cr_line_endings_are_accepted() {
+        let expected = read_with_line_ending("\n", crate::DEFAULT_BUFFER_CAPACITY);
+
+        // маленький буфер разрывает `\r\n` между порциями чтения
+        for capacity in [crate::DEFAULT_BUFFER_CAPACITY, 1, 7] {
+            assert_eq!(read_with_line_ending("\r\n", capacity), expected);
+            assert_eq!(read_with_line_ending("\r", capacity), expected);
+        }
+        assert_eq!(expected.len(), 2);
+        assert_eq!(expected[1].description, "User withdrawal ");
+    }
+
+    #[test]
+    fn crlf_line_numbers_count_each_line_once() {
+        let input = "TX_ID: 1\r\nTX_TYPE: DEPOSIT\r\nbroken\r\n";
+        let mut parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(input.as_bytes()));
+
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::MissingColonAfterKey { line: 3, .. }))));
+    }
+
+    #[test]
+    fn blank_crlf_or_cr_source_has_no_records() {
+        for input in ["\r\n\r\n", "\r\r", "# comment\r\n\r\n"] {
+            assert!(Parser::<YPBankTextRecord, _>::new(Cursor::new(input.as_bytes())).next().is_none());
+        }
     }
 
     #[test]
@@ -403,7 +1354,604 @@ UNKNOWN_FIELD: some_value
 "#;
         let cur = Cursor::new(input.as_bytes());
         let mut parser = Parser::<YPBankTextRecord, _>::new(cur);
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::ParseError { .. }))));
+    }
+
+    #[test]
+    fn read_fails_on_line_longer_than_limit() {
+        // бесконечный поток без перевода строки: без лимита буфер рос бы до исчерпания памяти
+        let source = std::io::repeat(b'A');
+        let reader = TextReader::new(source).max_line_bytes(1024);
+        let mut parser = Parser::<YPBankTextRecord, _>::from_reader(reader);
+
+        match parser.next() {
+            Some(Err(TextRecordError::LineTooLong { bytes })) => assert!(bytes > 1024),
+            other => panic!("Expected LineTooLong, got: {:?}", other),
+        }
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn read_fails_on_block_with_too_many_fields() {
+        let mut input = String::new();
+        for i in 0..100 {
+            input.push_str(&format!("FIELD_{}: {}\n", i, i));
+        }
+        input.push_str("\nTX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nTIMESTAMP: 1\nSTATUS: SUCCESS\n");
+
+        let mut parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(input));
+
+        match parser.next() {
+            Some(Err(TextRecordError::RecordTooLarge { fields: 64, line: 65, record_index: 0 })) => {}
+            other => panic!("Expected RecordTooLarge, got: {:?}", other),
+        }
+        assert_eq!(parser.next().unwrap().unwrap().id, 1);
+    }
+
+    #[test]
+    fn read_accepts_line_within_limit() {
+        let input = "TX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nTIMESTAMP: 1\nSTATUS: SUCCESS\nDESCRIPTION: \"ok\"\n";
+        let reader = TextReader::new(Cursor::new(input.as_bytes())).max_line_bytes(32);
+        let mut parser = Parser::<YPBankTextRecord, _>::from_reader(reader);
+
+        let rec = parser.next().expect("Should have a record").unwrap();
+        assert_eq!(rec.description, "ok");
+    }
+
+    #[test]
+    fn require_version_rejects_unaccepted_version_before_records() {
+        let input = "#!ypbank-txt v3\nTX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nTIMESTAMP: 1\nSTATUS: SUCCESS\nDESCRIPTION: \"v3\"\n";
+        let mut parser = Parser::<YPBankTextRecord, _>::require_version(Cursor::new(input.as_bytes()), &[1, 2]);
+
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::UnsupportedVersion { version: 3 }))));
+        assert!(parser.next().is_none(), "No records should be yielded after a version error");
+    }
+
+    #[test]
+    fn require_version_reads_records_after_accepted_header() {
+        let input = "#!ypbank-txt v2\nTX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nTIMESTAMP: 1\nSTATUS: SUCCESS\nDESCRIPTION: \"v2\"\n";
+        let mut parser = Parser::<YPBankTextRecord, _>::require_version(Cursor::new(input.as_bytes()), &[1, 2]);
+
+        let rec = parser.next().expect("Should have a record").unwrap();
+
+        assert!(parser.next().is_none());
+        assert_eq!(rec.description, "v2");
+    }
+
+    #[test]
+    fn require_version_fails_without_header() {
+        let input = "TX_ID: 1\nTX_TYPE: DEPOSIT\n";
+        let mut parser = Parser::<YPBankTextRecord, _>::require_version(Cursor::new(input.as_bytes()), &[1]);
+
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::MissingVersion))));
         assert!(parser.next().is_none());
-        assert!(matches!(parser.read_error.unwrap(), TextRecordError::ParseError { .. }));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn with_comments_returns_preceding_comment_lines() {
+        let input = r#"# File header
+
+# Provenance: branch 17
+# Checked by: auditor
+TX_ID: 1
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 100
+TIMESTAMP: 1
+STATUS: SUCCESS
+DESCRIPTION: "Terminal deposit"
+
+TX_ID: 2
+TX_TYPE: WITHDRAWAL
+FROM_USER_ID: 10
+TO_USER_ID: 0
+AMOUNT: 50
+TIMESTAMP: 2
+STATUS: PENDING
+DESCRIPTION: "User withdrawal"
+"#;
+
+        let mut records = Parser::<YPBankTextRecord, _>::new(Cursor::new(input.as_bytes())).with_comments();
+
+        let (first, first_comments) = records.next().expect("Should have first record").unwrap();
+        let (second, second_comments) = records.next().expect("Should have second record").unwrap();
+        assert!(records.next().is_none());
+
+        assert_eq!(first.id, 1);
+        assert_eq!(first_comments, vec!["Provenance: branch 17", "Checked by: auditor"]);
+        assert_eq!(second.id, 2);
+        assert!(second_comments.is_empty());
+    }
+
+    #[test]
+    fn sentinel_renderings_are_written_and_read_back_as_zero() {
+        let transaction = Transaction {
+            id: 7,
+            transaction_type: TransactionType::Withdrawal,
+            from_user_id: 12,
+            to_user_id: 0,
+            amount: 300,
+            timestamp: 5,
+            transaction_status: TransactionStatus::Success,
+            description: "ATM".to_string(),
+            currency: None,
+        };
+
+        let cases = [
+            (SentinelRendering::Zero, "TO_USER_ID: 0\n"),
+            (SentinelRendering::Empty, "TO_USER_ID: \n"),
+            (SentinelRendering::Literal(crate::EXTERNAL_USER_LITERAL.to_string()), "TO_USER_ID: EXTERNAL\n"),
+        ];
+
+        for (sentinel, expected_line) in cases {
+            let mut serializer = Serializer::<YPBankTextRecord, _>::new(Vec::new()).with_sentinel(sentinel);
+            serializer.serialize(vec![YPBankTextRecord::from(transaction.clone())]).unwrap();
+            let bytes = serializer.into_inner().into_inner().unwrap();
+
+            assert!(String::from_utf8(bytes.clone()).unwrap().contains(expected_line));
+
+            let mut parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(bytes));
+            let reread: Transaction = parser.next().expect("Should have a record").unwrap().into();
+            assert_eq!(reread, transaction);
+        }
+    }
+
+    #[test]
+    fn with_presence_reports_missing_optional_field() {
+        let input = r#"
+TX_ID: 1
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 100
+TIMESTAMP: 1
+STATUS: SUCCESS
+DESCRIPTION: "Has description"
+
+TX_ID: 2
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 100
+TIMESTAMP: 2
+STATUS: SUCCESS
+"#;
+
+        let mut records = Parser::<YPBankTextRecord, _>::new(Cursor::new(input.as_bytes())).with_presence();
+
+        let (first, first_fields) = records.next().expect("Should have first record").unwrap();
+        let (second, second_fields) = records.next().expect("Should have second record").unwrap();
+        assert!(records.next().is_none());
+
+        assert_eq!(first.description, "Has description");
+        assert!(first_fields.contains(ProcessedFields::REQUIRED | ProcessedFields::DESCRIPTION));
+
+        assert_eq!(second.description, "");
+        assert!(second_fields.contains(ProcessedFields::REQUIRED));
+        assert!(!second_fields.contains(ProcessedFields::DESCRIPTION));
+    }
+
+    #[test]
+    fn defaults_fill_missing_timestamp() {
+        let input = "TX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nSTATUS: SUCCESS\n\n";
+        let defaults = Defaults {
+            timestamp: Some(1_700_000_000_000),
+            status: Some(TransactionStatus::Failure),
+            ..Defaults::default()
+        };
+
+        let reader = TextReader::new(Cursor::new(input.as_bytes())).defaults(defaults);
+        let mut parser = Parser::<YPBankTextRecord, _>::from_reader(reader);
+
+        let transaction: Transaction = parser.next().expect("Should have a record").unwrap().into();
+        assert_eq!(transaction.timestamp, 1_700_000_000_000);
+        // присутствующее поле не перезаписывается
+        assert_eq!(transaction.transaction_status, TransactionStatus::Success);
+
+        let mut parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(input.as_bytes()));
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::MissingField { field: "TIMESTAMP", .. }))));
+    }
+
+    #[test]
+    fn hex_mode_parses_numeric_fields() {
+        let input = |amount: &str| format!(
+            "TX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: {}\nTIMESTAMP: 0x10\nSTATUS: SUCCESS\n",
+            amount
+        );
+        let parse = |amount: &str| {
+            let reader = TextReader::new(Cursor::new(input(amount).into_bytes())).hex_numbers(true);
+            let mut parser = Parser::<YPBankTextRecord, _>::from_reader(reader);
+            parser.next().expect("Should have a record").map(Transaction::from)
+        };
+
+        let transaction = parse("0x1F4").unwrap();
+        assert_eq!(transaction.amount, 500);
+        assert_eq!(transaction.timestamp, 16);
+        assert_eq!(parse("500").unwrap().amount, 500);
+        assert!(matches!(parse("0xZZ"), Err(TextRecordError::ParseError { .. })));
+
+        let mut strict = Parser::<YPBankTextRecord, _>::new(Cursor::new(input("0x1F4").into_bytes()));
+        assert!(matches!(strict.next(), Some(Err(TextRecordError::ParseError { .. }))));
+    }
+
+    fn round_trip_description(description: &str) -> String {
+        let record = YPBankTextRecord::from(Transaction {
+            id: 1,
+            transaction_type: TransactionType::Deposit,
+            from_user_id: 0,
+            to_user_id: 1,
+            amount: 1,
+            timestamp: 1,
+            transaction_status: TransactionStatus::Success,
+            description: description.to_string(),
+            currency: None,
+        });
+        let bytes = crate::serialize_to_vec(&[record]).unwrap();
+
+        let mut parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(bytes));
+        let description = parser.next().unwrap().unwrap().description;
+        assert!(parser.next().is_none());
+        description
+    }
+
+    #[test]
+    fn multi_line_description_survives_round_trip() {
+        assert_eq!(round_trip_description("First line\n\nThird line\r\n"), "First line\n\nThird line\r\n");
+    }
+
+    #[test]
+    fn backslash_in_description_survives_round_trip() {
+        assert_eq!(round_trip_description("C:\\temp\\new"), "C:\\temp\\new");
+        assert_eq!(round_trip_description("ends with \\"), "ends with \\");
+    }
+
+    #[test]
+    fn description_that_looks_like_comment_survives_round_trip() {
+        assert_eq!(round_trip_description("# not a comment"), "# not a comment");
+        assert_eq!(round_trip_description("  padded: value  "), "  padded: value  ");
+        assert_eq!(round_trip_description("\"quoted\""), "\"quoted\"");
+    }
+
+    fn marker_reader(input: Vec<u8>) -> Parser<YPBankTextRecord, Cursor<Vec<u8>>> {
+        let reader = TextReader::new(Cursor::new(input)).record_separator(RecordSeparator::marker("---").unwrap());
+        Parser::from_reader(reader)
+    }
+
+    #[test]
+    fn marker_separated_records_round_trip() {
+        let transactions: Vec<Transaction> = ["First\n\nafter blank line", "---", "Third"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, description)| Transaction {
+                id: i as u64 + 1,
+                transaction_type: TransactionType::Deposit,
+                from_user_id: 0,
+                to_user_id: 1,
+                amount: 100,
+                timestamp: 1,
+                transaction_status: TransactionStatus::Success,
+                description: description.to_string(),
+                currency: None,
+            })
+            .collect();
+
+        let mut output = Vec::new();
+        let mut serializer = Serializer::<YPBankTextRecord, _>::new(&mut output).with_options(RecordSeparator::marker(" --- ").unwrap());
+        serializer.serialize(transactions.iter().cloned().map(YPBankTextRecord::from)).unwrap();
+        drop(serializer);
+        let text = String::from_utf8(output.clone()).unwrap();
+        assert_eq!(text.matches("\n---\n").count(), 3);
+        assert!(!text.contains("\n\n"));
+
+        let read_back: Vec<Transaction> = marker_reader(output).map(|r| r.map(Into::into)).collect::<Result<_, _>>().unwrap();
+        assert_eq!(read_back, transactions);
+    }
+
+    #[test]
+    fn blank_or_multi_line_marker_is_rejected() {
+        for marker in ["", "   ", "--\n--", "a\rb"] {
+            assert_eq!(RecordSeparator::marker(marker).unwrap_err().kind(), std::io::ErrorKind::InvalidInput, "{:?}", marker);
+        }
+    }
+
+    #[test]
+    fn marker_mode_allows_blank_lines_inside_block() {
+        let input = "\
+TX_ID: 1
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+
+TO_USER_ID: 1
+AMOUNT: 100
+TIMESTAMP: 1
+
+STATUS: SUCCESS
+DESCRIPTION: \"Salary\"
+---
+
+  ---
+TX_ID: 2
+TX_TYPE: WITHDRAWAL
+FROM_USER_ID: 1
+TO_USER_ID: 0
+AMOUNT: x
+TIMESTAMP: 2
+STATUS: SUCCESS
+DESCRIPTION: \"Bad\"
+---
+TX_ID: 3
+TX_TYPE: WITHDRAWAL
+FROM_USER_ID: 1
+TO_USER_ID: 0
+AMOUNT: 50
+TIMESTAMP: 3
+STATUS: SUCCESS
+DESCRIPTION: \"Cash\"
+";
+        let mut parser = marker_reader(input.as_bytes().to_vec());
+
+        assert_eq!(parser.next().unwrap().unwrap().id, 1);
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::ParseError { line: 18, record_index: 1, .. }))));
+        assert_eq!(parser.next().unwrap().unwrap().id, 3);
+        assert!(parser.next().is_none());
+
+        // в режиме пустой строки тот же файл распадается на неполные блоки
+        let mut blank_line_parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(input));
+        assert!(matches!(blank_line_parser.next(), Some(Err(TextRecordError::MissingField { .. }))));
+    }
+
+    #[test]
+    fn unknown_escape_is_read_literally() {
+        let input = "TX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nTIMESTAMP: 1\nSTATUS: SUCCESS\nDESCRIPTION: \"C:\\temp\"\n";
+        let rec = Parser::<YPBankTextRecord, _>::new(Cursor::new(input)).next().unwrap().unwrap();
+
+        assert_eq!(rec.description, "C:\\temp");
+    }
+
+    #[test]
+    fn leading_bom_is_ignored() {
+        let input = "TX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nTIMESTAMP: 1\nSTATUS: SUCCESS\nDESCRIPTION: \"ok\"\n";
+        let parse = |data: String| -> Vec<Transaction> {
+            Parser::<YPBankTextRecord, _>::new(Cursor::new(data))
+                .map(|r| r.map(Transaction::from))
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+
+        let with_bom = parse(format!("\u{feff}{}", input));
+        assert_eq!(with_bom, parse(input.to_string()));
+        assert_eq!(with_bom.len(), 1);
+    }
+
+    #[test]
+    fn blank_description_can_be_read_as_none() {
+        let read = |description_line: &str, enabled: bool| -> Transaction {
+            let input = format!(
+                "TX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nTIMESTAMP: 1\nSTATUS: SUCCESS\n{}\n",
+                description_line
+            );
+            let reader = TextReader::new(Cursor::new(input.into_bytes())).blank_description_as_none(enabled);
+            Parser::<YPBankTextRecord, _>::from_reader(reader).next().unwrap().unwrap().into()
+        };
+
+        assert_eq!(read("DESCRIPTION:", true).description_opt(), None);
+        assert_eq!(read("DESCRIPTION: \"  \"", true).description_opt(), None);
+        assert_eq!(read("DESCRIPTION: \"  \"", false).description_opt(), Some("  "));
+        assert_eq!(read("DESCRIPTION: \"Rent\"", true).description_opt(), Some("Rent"));
+
+        let mut output = Vec::new();
+        YPBankTextRecord::from(read("DESCRIPTION:", true)).write(&mut output).unwrap();
+        assert!(String::from_utf8(output).unwrap().contains("DESCRIPTION: \"\"\n"));
+    }
+
+    #[test]
+    fn empty_key_is_rejected_and_block_skipped() {
+        let input = "TX_ID: 1\n: value\nTX_TYPE: DEPOSIT\n\nTX_ID: 2\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nTIMESTAMP: 1\nSTATUS: SUCCESS\n";
+        let mut parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(input));
+
+        assert!(matches!(parser.next(), Some(Err(TextRecordError::EmptyKey { line: 2, record_index: 0 }))));
+        assert_eq!(parser.next().unwrap().unwrap().id, 2);
+        assert!(parser.next().is_none());
+    }
+
+    const DUPLICATE_AMOUNT: &str = "\
+TX_ID: 1
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 100
+AMOUNT: 200
+TIMESTAMP: 1
+STATUS: SUCCESS
+
+TX_ID: 2
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 300
+TIMESTAMP: 2
+STATUS: SUCCESS
+";
+
+    #[test]
+    fn duplicate_key_is_rejected_by_default() {
+        let mut parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(DUPLICATE_AMOUNT.as_bytes()));
+
+        match parser.next() {
+            Some(Err(TextRecordError::DuplicateKey { key, line, record_index })) => {
+                assert_eq!((key.as_str(), line, record_index), ("AMOUNT", 6, 0));
+            }
+            other => panic!("Expected DuplicateKey, got: {:?}", other),
+        }
+        assert_eq!(parser.next().unwrap().unwrap().id, 2);
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn lenient_mode_keeps_last_duplicate_value() {
+        let reader = TextReader::new(Cursor::new(DUPLICATE_AMOUNT.as_bytes())).allow_duplicate_keys(true);
+        let records: Vec<YPBankTextRecord> = Parser::from_reader(reader).collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].amount, 200);
+    }
+
+    #[test]
+    fn custom_comment_prefix_treats_hash_as_data() {
+        let input = "\
+; exported by legacy tool
+TX_ID: 1
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+  ; inline comment
+AMOUNT: 100
+TIMESTAMP: 1
+STATUS: SUCCESS
+DESCRIPTION: #1 payment
+";
+        let reader = TextReader::new(Cursor::new(input.as_bytes())).comment_prefix(';');
+        let (record, comments) = Parser::from_reader(reader).with_comments().next().unwrap().unwrap();
+
+        assert_eq!(record.amount, 100);
+        assert_eq!(record.description, "#1 payment");
+        assert_eq!(comments, vec!["exported by legacy tool"]);
+
+        let mut default = Parser::<YPBankTextRecord, _>::new(Cursor::new(input.as_bytes()));
+        assert!(matches!(default.next(), Some(Err(TextRecordError::MissingColonAfterKey { line: 1, .. }))));
+    }
+
+    #[test]
+    fn value_starting_with_comment_prefix_is_kept() {
+        let input = "TX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 10\nAMOUNT: 100\nTIMESTAMP: 1\nSTATUS: SUCCESS\nDESCRIPTION: #value\n";
+        let record = Parser::<YPBankTextRecord, _>::new(Cursor::new(input.as_bytes())).next().unwrap().unwrap();
+
+        assert_eq!(record.description, "#value");
+    }
+
+    #[test]
+    fn with_raw_returns_exact_block_bytes() {
+        let first = "TX_ID: 1\r\nTX_TYPE:   DEPOSIT\r\n# inside\r\nFROM_USER_ID: 0\r\nTO_USER_ID: 10\r\nAMOUNT: 100\r\nTIMESTAMP: 1\r\nSTATUS: SUCCESS\r\n";
+        let second = "STATUS: PENDING\nTX_ID: 2\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 10\nAMOUNT: 5\nTIMESTAMP: 2";
+        let input = format!("# header\r\n\r\n{}\r\n\r\n{}", first, second);
+
+        let records: Vec<RecordWithRaw> = Parser::<YPBankTextRecord, _>::new(Cursor::new(input.into_bytes()))
+            .with_raw()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].raw, first.as_bytes());
+        assert_eq!(records[0].parsed.amount, 100);
+        assert_eq!(records[1].raw, second.as_bytes());
+    }
+
+    #[test]
+    fn whitespace_around_key_is_trimmed() {
+        let input = "  TX_ID  : 1\nTX_TYPE\t: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nTIMESTAMP: 1\nSTATUS: SUCCESS\n";
+        let rec = Parser::<YPBankTextRecord, _>::new(Cursor::new(input)).next().unwrap().unwrap();
+
+        assert_eq!(rec.id, 1);
+    }
+
+    #[test]
+    fn colon_inside_value_is_kept() {
+        let input = "TX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nTIMESTAMP: 1\nSTATUS: SUCCESS\nDESCRIPTION: \"see https://example.com at 12:30\"\n";
+        let rec = Parser::<YPBankTextRecord, _>::new(Cursor::new(input)).next().unwrap().unwrap();
+
+        assert_eq!(rec.description, "see https://example.com at 12:30");
+    }
+
+    #[test]
+    fn field_names_match_processed_fields() {
+        let names: Vec<&str> = ProcessedFields::FIELDS.iter().map(|&(name, _)| name).collect();
+
+        assert_eq!(YPBankTextRecord::field_names(), names);
+    }
+
+    #[test]
+    fn case_insensitive_mode_parses_enum_fields() {
+        let input = |transaction_type: &str| format!(
+            "TX_ID: 1\nTX_TYPE: {}\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nTIMESTAMP: 1\nSTATUS: success\n",
+            transaction_type
+        );
+        let parse = |transaction_type: &str, enabled: bool| {
+            let reader = TextReader::new(Cursor::new(input(transaction_type).into_bytes())).case_insensitive_enums(enabled);
+            let mut parser = Parser::<YPBankTextRecord, _>::from_reader(reader);
+            parser.next().expect("Should have a record").map(Transaction::from)
+        };
+
+        let transaction = parse("deposit", true).unwrap();
+        assert_eq!(transaction.transaction_type, TransactionType::Deposit);
+        assert_eq!(transaction.transaction_status, TransactionStatus::Success);
+        assert_eq!(parse("Deposit", true).unwrap().transaction_type, TransactionType::Deposit);
+        assert!(matches!(parse("depsit", true), Err(TextRecordError::ParseError { .. })));
+        assert!(matches!(parse("DEPOSIT", false), Err(TextRecordError::ParseError { .. })));
+    }
+
+    #[test]
+    fn key_aliases_map_legacy_keys() {
+        let input = "ID: 7\nTYPE: TRANSFER\nSENDER: 10\nRECEIVER: 20\nAMOUNT: 50\nTIMESTAMP: 1000\nSTATUS: SUCCESS\n";
+        let aliases: HashMap<String, String> = [("ID", "TX_ID"), ("TYPE", "TX_TYPE"), ("SENDER", "FROM_USER_ID"), ("RECEIVER", "TO_USER_ID")]
+            .into_iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect();
+        let reader = TextReader::new(Cursor::new(input.as_bytes().to_vec())).key_aliases(aliases);
+
+        let transaction: Transaction = Parser::<YPBankTextRecord, _>::from_reader(reader).next().unwrap().unwrap().into();
+
+        assert_eq!(transaction.id, 7);
+        assert_eq!(transaction.transaction_type, TransactionType::Transfer);
+        assert_eq!((transaction.from_user_id, transaction.to_user_id), (10, 20));
+        assert_eq!(transaction.amount, 50);
+
+        let mut parser = Parser::<YPBankTextRecord, _>::new(Cursor::new(input.as_bytes().to_vec()));
+        assert!(parser.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn csv_record_round_trips_through_text_record() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+5,TRANSFER,10,20,700,1700000000000,SUCCESS,\"Rent\"
+";
+        let read = || Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data)).next().unwrap().unwrap();
+
+        let text = YPBankTextRecord::from(read());
+        assert_eq!(text.description, "Rent");
+        assert_eq!(YPBankCsvRecord::from(text), read());
+    }
+
+    #[test]
+    fn transaction_read_from_csv_is_written_as_text() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+5,TRANSFER,10,20,700,1700000000000,SUCCESS,\"Rent\"
+";
+        let transaction: Transaction = Parser::<crate::YPBankCsvRecord, _>::new(Cursor::new(csv_data))
+            .next()
+            .expect("Should have a record")
+            .unwrap()
+            .into();
+
+        let mut serializer = Serializer::<YPBankTextRecord, _>::new(Vec::new());
+        serializer.serialize(vec![transaction.clone().into()]).unwrap();
+        let bytes = serializer.into_inner().into_inner().unwrap();
+
+        let reread: Transaction = Parser::<YPBankTextRecord, _>::new(Cursor::new(bytes))
+            .next()
+            .expect("Should have a record")
+            .unwrap()
+            .into();
+        assert_eq!(reread, transaction);
+    }
+
+    #[test]
+    fn description_quotes_are_stripped_once() {
+        let input = "TX_ID: 1\nTX_TYPE: DEPOSIT\nFROM_USER_ID: 0\nTO_USER_ID: 1\nAMOUNT: 1\nTIMESTAMP: 1\nSTATUS: SUCCESS\nDESCRIPTION: \"\"quoted\" and \"more\"\"\n";
+        let rec = Parser::<YPBankTextRecord, _>::new(Cursor::new(input)).next().unwrap().unwrap();
+
+        assert_eq!(rec.description, "\"quoted\" and \"more\"");
+    }
+}
@@ -0,0 +1,97 @@
+use crate::common::{Transaction, ValidationConfig};
+use crate::errors::ParseError;
+use crate::{Parser, Readable};
+use std::io::Read;
+
+/// Итератор транзакций, проверяющий каждую запись `Transaction::validate_with`.
+///
+/// Записи, нарушающие бизнес-правила, выдаются как `ParseError::Validation`;
+/// чтение продолжается со следующей записи.
+pub struct ValidatedParser<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read
+{
+    parser: Parser<TRecord, Source>,
+    config: ValidationConfig,
+}
+
+impl<TRecord, Source> Iterator for ValidatedParser<TRecord, Source>
+where
+    TRecord: Readable<Source> + Into<Transaction>,
+    TRecord::Error: Into<ParseError>,
+    Source: Read
+{
+    type Item = Result<Transaction, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let transaction: Transaction = match self.parser.next()? {
+            Ok(record) => record.into(),
+            Err(e) => return Some(Err(e.into())),
+        };
+
+        Some(transaction.validate_with(&self.config).map(|_| transaction).map_err(Into::into))
+    }
+}
+
+impl<TRecord, Source> Parser<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read
+{
+    /// Оборачивает парсер в итератор транзакций с проверкой бизнес-правил.
+    pub fn validated(self) -> ValidatedParser<TRecord, Source> {
+        self.validated_with(ValidationConfig::default())
+    }
+
+    /// Оборачивает парсер в итератор транзакций с проверкой включенных в `config` правил.
+    pub fn validated_with(self, config: ValidationConfig) -> ValidatedParser<TRecord, Source> {
+        ValidatedParser { parser: self, config }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ValidationError;
+    use crate::YPBankCsvRecord;
+    use std::io::Cursor;
+
+    #[test]
+    fn invalid_records_are_errors_and_reading_continues() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1000,SUCCESS,\"Ok\"
+2,WITHDRAWAL,10,20,50,2000,SUCCESS,\"Has receiver\"
+3,TRANSFER,10,20,0,3000,SUCCESS,\"Zero amount\"
+4,TRANSFER,10,20,70,4000,SUCCESS,\"Ok\"
+";
+        let mut parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data)).validated();
+
+        assert_eq!(parser.next().unwrap().unwrap().id, 1);
+        assert!(matches!(
+            parser.next(),
+            Some(Err(ParseError::Validation(ValidationError::UnexpectedReceiver { id: 2, to_user_id: 20 })))
+        ));
+        assert!(matches!(
+            parser.next(),
+            Some(Err(ParseError::Validation(ValidationError::NonPositiveAmount { id: 3, amount: 0 })))
+        ));
+        assert_eq!(parser.next().unwrap().unwrap().id, 4);
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn self_transfer_is_accepted_when_rule_is_disabled() {
+        let csv_data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,TRANSFER,10,10,100,1000,SUCCESS,\"Self\"
+";
+        let strict = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data)).validated().next().unwrap();
+        assert!(matches!(strict, Err(ParseError::Validation(ValidationError::SelfTransfer { id: 1, user_id: 10 }))));
+
+        let config = ValidationConfig { no_self_transfer: false, ..ValidationConfig::default() };
+        let lenient = Parser::<YPBankCsvRecord, _>::new(Cursor::new(csv_data)).validated_with(config).next().unwrap();
+        assert_eq!(lenient.unwrap().to_user_id, 10);
+    }
+}
@@ -0,0 +1,165 @@
+use crate::common::Transaction;
+use crate::{Parser, Readable};
+use std::io::Read;
+
+/// Шаг, кратность которому делает сумму подозрительно «круглой» (10 000.00 в основных единицах).
+const ROUND_AMOUNT_STEP: i64 = 1_000_000;
+
+/// Фрагменты, характерные для незаполненных шаблонов описания.
+const PLACEHOLDER_MARKERS: [&str; 6] = ["{{", "}}", "${", "%s", "TODO", "TBD"];
+
+/// Вид предупреждения о записи.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    /// Сумма подозрительно круглая (кратна 10 000.00).
+    RoundAmount,
+    /// Описание пустое или похоже на незаполненный шаблон.
+    PlaceholderDescription,
+}
+
+/// Предупреждение о записи, не мешающее её обработке.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// Идентификатор транзакции, к которой относится предупреждение.
+    pub id: u64,
+    /// Вид предупреждения.
+    pub kind: WarningKind,
+}
+
+/// Набор включенных проверок. По умолчанию включены все.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarningPolicy {
+    /// Проверять сумму на «круглость».
+    pub round_amount: bool,
+    /// Проверять описание на шаблонные заглушки.
+    pub placeholder_description: bool,
+}
+
+impl Default for WarningPolicy {
+    fn default() -> Self {
+        Self {
+            round_amount: true,
+            placeholder_description: true,
+        }
+    }
+}
+
+impl WarningPolicy {
+    /// Возвращает виды предупреждений, которые включенные проверки выдают для транзакции.
+    pub fn check(&self, transaction: &Transaction) -> Vec<WarningKind> {
+        let mut kinds = Vec::new();
+
+        if self.round_amount && transaction.amount != 0 && transaction.amount % ROUND_AMOUNT_STEP == 0 {
+            kinds.push(WarningKind::RoundAmount);
+        }
+
+        if self.placeholder_description {
+            let description = transaction.description.trim();
+            if description.is_empty() || PLACEHOLDER_MARKERS.iter().any(|marker| description.contains(marker)) {
+                kinds.push(WarningKind::PlaceholderDescription);
+            }
+        }
+
+        kinds
+    }
+}
+
+/// Итератор транзакций, собирающий предупреждения по мере чтения.
+///
+/// Записи с предупреждениями выдаются как обычно; сами предупреждения накапливаются в `warnings`.
+/// Ошибки чтения передаются дальше без изменений.
+pub struct WarningReader<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read
+{
+    parser: Parser<TRecord, Source>,
+    policy: WarningPolicy,
+    /// Предупреждения по всем прочитанным к этому моменту записям.
+    pub warnings: Vec<Warning>,
+}
+
+impl<TRecord, Source> Iterator for WarningReader<TRecord, Source>
+where
+    TRecord: Readable<Source> + Into<Transaction>,
+    Source: Read
+{
+    type Item = Result<Transaction, TRecord::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let transaction: Transaction = match self.parser.next()? {
+            Ok(record) => record.into(),
+            Err(e) => return Some(Err(e)),
+        };
+
+        let id = transaction.id;
+        self.warnings.extend(
+            self.policy
+                .check(&transaction)
+                .into_iter()
+                .map(|kind| Warning { id, kind }),
+        );
+
+        Some(Ok(transaction))
+    }
+}
+
+impl<TRecord, Source> Parser<TRecord, Source>
+where
+    TRecord: Readable<Source>,
+    Source: Read
+{
+    /// Оборачивает парсер в итератор транзакций, собирающий предупреждения согласно `policy`.
+    pub fn with_warnings(self, policy: WarningPolicy) -> WarningReader<TRecord, Source> {
+        WarningReader {
+            parser: self,
+            policy,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::YPBankCsvRecord;
+    use std::io::Cursor;
+
+    const CSV_DATA: &str = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,5000000,1000,SUCCESS,\"Bonus\"
+2,DEPOSIT,0,10,1234,2000,SUCCESS,\"Payment for {{invoice}}\"
+3,DEPOSIT,0,10,1234,3000,SUCCESS,\"Regular payment\"
+";
+
+    #[test]
+    fn warnings_are_collected_and_records_still_yielded() {
+        let parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(CSV_DATA));
+        let mut reader = parser.with_warnings(WarningPolicy::default());
+
+        let transactions: Vec<Transaction> = reader.by_ref().collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(transactions.len(), 3);
+        assert_eq!(transactions[0].amount, 5000000);
+        assert_eq!(
+            reader.warnings,
+            vec![
+                Warning { id: 1, kind: WarningKind::RoundAmount },
+                Warning { id: 2, kind: WarningKind::PlaceholderDescription },
+            ]
+        );
+    }
+
+    #[test]
+    fn disabled_checks_produce_no_warnings() {
+        let policy = WarningPolicy {
+            round_amount: false,
+            ..WarningPolicy::default()
+        };
+        let parser = Parser::<YPBankCsvRecord, _>::new(Cursor::new(CSV_DATA));
+        let mut reader = parser.with_warnings(policy);
+
+        assert_eq!(reader.by_ref().count(), 3);
+        assert_eq!(reader.warnings, vec![Warning { id: 2, kind: WarningKind::PlaceholderDescription }]);
+    }
+}